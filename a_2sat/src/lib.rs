@@ -1,5 +1,6 @@
-use std::collections::{btree_map::BTreeMap, HashMap, HashSet};
+use std::collections::btree_map::BTreeMap;
 
+use a_scc::StronglyConnected;
 use anyhow::Error;
 
 use std::path::Path;
@@ -96,76 +97,27 @@ fn get_node(variable: isize) -> usize {
     }
 }
 
-fn get_variable(node: usize) -> isize {
-    if node % 2 == 0 {
-        (node / 2) as isize
-    } else {
-        -(((node + 1) / 2) as isize)
-    }
-}
-
-fn get_negated_node(node: usize) -> usize {
-    let variable = get_variable(node);
-    if variable < 0 {
-        node + 1
-    } else {
-        node - 1
-    }
-}
-
 fn get_assignment(lines: &mut dyn Iterator<Item = String>) -> Result<Option<Vec<isize>>, Error> {
-    let mut graph = utility::graph::IntegerGraph::from_2sat_adjacency_list(lines, false)?;
-    let graph_reverse = graph.get_reverse_graph(true);
-    let mut node_order = graph_reverse
-        .postvisit
-        .into_iter()
-        .enumerate()
-        .collect::<Vec<_>>();
-    node_order.sort_by(|a, b| b.1.cmp(&a.1));
-    let node_order: Vec<usize> = node_order.iter().map(|(i, _)| *i).collect();
-    graph.run_dfs_given_node_order(&node_order);
-    let mut satisfiable = true;
-    for node in (0..graph.num_nodes - 1).step_by(2) {
-        if graph.connected_components[node] == graph.connected_components[node + 1] {
-            satisfiable = false;
-            break;
-        }
-    }
-    if satisfiable {
-        let mut component_to_nodes = HashMap::new();
-        for (i, component) in graph.connected_components.iter().enumerate() {
-            component_to_nodes
-                .entry(component)
-                .or_insert_with(Vec::new)
-                .push(graph.nodes[i]);
+    let graph = utility::graph::IntegerGraph::from_2sat_adjacency_list(lines, false)?;
+    let scc = graph.get_strongly_connected_components();
+    let mut assignment = Vec::with_capacity(graph.num_nodes / 2);
+    for variable in 1..=(graph.num_nodes / 2) as isize {
+        let (true_component, false_component) = (
+            scc.component(get_node(variable)),
+            scc.component(get_node(-variable)),
+        );
+        if true_component == false_component {
+            return Ok(None);
         }
-        let mut seen = HashSet::with_capacity(graph.num_nodes);
-        let mut assignment = (0..graph.num_nodes).map(|_| false).collect::<Vec<_>>();
-        let mut n_node;
-        for node_index in node_order {
-            if !seen.contains(&graph.nodes[node_index]) {
-                for c_node in &component_to_nodes[&graph.connected_components[node_index]] {
-                    if !seen.contains(c_node) {
-                        n_node = get_negated_node(*c_node);
-                        seen.insert(*c_node);
-                        seen.insert(n_node);
-                        assignment[graph.node_to_index[c_node]] = true;
-                        assignment[graph.node_to_index[&n_node]] = false;
-                    }
-                }
-            }
+        // a literal is set to true if it comes after its negation in topological order,
+        // so that no implication leads from true to false
+        if true_component > false_component {
+            assignment.push(variable);
+        } else {
+            assignment.push(-variable);
         }
-        Ok(Some(
-            assignment
-                .into_iter()
-                .enumerate()
-                .filter(|(_, s)| *s)
-                .map(|(i, _)| get_variable(graph.nodes[i]))
-                .collect(),
-        ))
-    } else {
-        Ok(None)
     }
+    Ok(Some(assignment))
 }
 
 #[cfg(test)]
@@ -174,23 +126,50 @@ mod tests {
 
     use super::*;
 
+    /// Clauses of each formula in a 2SAT input file
+    fn read_clauses(input: &str) -> Result<Vec<Vec<Vec<isize>>>, Error> {
+        let mut lines = input.split('\n').filter(|s| !s.trim().is_empty());
+        let num_sections = lines.next().unwrap().parse::<usize>()?;
+        let mut formulas = Vec::with_capacity(num_sections);
+        for _ in 0..num_sections {
+            let num_clauses = usize::parse_line(lines.next().unwrap())?[1];
+            formulas.push(
+                (0..num_clauses)
+                    .map(|_| isize::parse_line(lines.next().unwrap()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+        Ok(formulas)
+    }
+
     #[test]
     fn _2sat() -> Result<(), Error> {
         let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_2sat")?;
         let result = rosalind_2sat(&input_file)?;
-        for (input_assignment, output_assignment) in result.into_iter().zip(
-            utility::io::input_from_file(&output_file)?
-                .split('\n')
-                .filter(|line| !line.trim().is_empty()),
-        ) {
+        let formulas = read_clauses(&utility::io::input_from_file(&input_file)?)?;
+        for ((input_assignment, output_assignment), clauses) in result
+            .into_iter()
+            .zip(
+                utility::io::input_from_file(&output_file)?
+                    .split('\n')
+                    .filter(|line| !line.trim().is_empty()),
+            )
+            .zip(formulas)
+        {
             if let Ok(0) = output_assignment.trim().parse::<usize>() {
                 assert!(input_assignment.is_none())
             } else {
-                assert!(input_assignment.is_some());
+                // any satisfying assignment is accepted
+                let input_assignment = input_assignment.unwrap();
                 assert_eq!(
-                    input_assignment.unwrap()[..],
-                    isize::parse_line(output_assignment.trim())?[1..]
+                    input_assignment.len(),
+                    isize::parse_line(output_assignment.trim())?.len() - 1
                 );
+                for clause in clauses {
+                    assert!(clause
+                        .iter()
+                        .any(|literal| input_assignment.contains(literal)));
+                }
             }
         }
         Ok(())
@@ -1,3 +1,6 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
 use anyhow::Error;
 
 use std::path::Path;
@@ -14,20 +17,12 @@ pub fn rosalind_2sum(filename: &Path) -> Result<Vec<Option<(usize, usize)>>, Err
     let input = utility::io::input_from_file(filename)?;
     let mut lines = input.split('\n');
     let length_input = usize::parse_line(lines.next().unwrap())?;
-    let length = length_input[1];
     let mut output = Vec::with_capacity(length_input[0]);
     for line in lines {
-        let mut array_indices = isize::parse_line(line)?
-            .into_iter()
-            .enumerate()
-            .collect::<Vec<_>>();
-        array_indices.sort_by(|a, b| a.1.cmp(&b.1));
-        let array = array_indices.iter().map(|a| a.1).collect::<Vec<_>>();
-        let indices = array_indices.iter().map(|a| a.0).collect::<Vec<_>>();
-        match two_sum(length, &array, 0) {
-            Some((index_1, index_2)) => {
-                let mut real_indices = vec![indices[index_1] + 1, indices[index_2] + 1];
-                real_indices.sort_unstable();
+        let array = isize::parse_line(line)?;
+        match k_sum_first(&array, 2, 0, KSumStrategy::TwoPointer) {
+            Some(indices) => {
+                let real_indices: Vec<_> = indices.into_iter().map(|i| i + 1).collect();
                 println!("{}", utility::io::format_array(&real_indices));
                 output.push(Some((real_indices[0], real_indices[1])));
             }
@@ -40,28 +35,246 @@ pub fn rosalind_2sum(filename: &Path) -> Result<Vec<Option<(usize, usize)>>, Err
     Ok(output)
 }
 
-fn two_sum(length: usize, array: &[isize], target: isize) -> Option<(usize, usize)> {
-    let mut index_1 = 0;
-    let mut index_2 = length - 1;
-    while index_1 < length {
-        let sum = array[index_1] + array[index_2];
-        if sum == target {
-            return Some((index_1, index_2));
+/// How the innermost pair of a k-SUM search is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KSumStrategy {
+    /// Walk inwards from both ends of the sorted array: O(n) time, O(1) extra memory
+    TwoPointer,
+    /// Look up complements in a hash map: O(n) time and memory
+    Hash,
+}
+
+/// First set of `k` distinct indices (0-based, ascending) into `array` whose values sum to `target`
+pub fn k_sum_first(
+    array: &[isize],
+    k: usize,
+    target: isize,
+    strategy: KSumStrategy,
+) -> Option<Vec<usize>> {
+    KSumSearch::new(array, strategy, true)
+        .run(k, target)
+        .into_iter()
+        .next()
+}
+
+/// Every set of `k` distinct indices (0-based, ascending) into `array` whose values sum to `target`.
+///
+/// Takes O(n^(k-1)) time
+pub fn k_sum_all(
+    array: &[isize],
+    k: usize,
+    target: isize,
+    strategy: KSumStrategy,
+) -> Vec<Vec<usize>> {
+    KSumSearch::new(array, strategy, false).run(k, target)
+}
+
+/// 4-SUM by meet in the middle: pair sums are stored in a hash map and matched against
+/// complementary pairs, in O(n² + output) time and O(n²) memory
+pub fn four_sum_meet_in_the_middle(
+    array: &[isize],
+    target: isize,
+    first_only: bool,
+) -> Vec<Vec<usize>> {
+    let search = KSumSearch::new(array, KSumStrategy::Hash, first_only);
+    let values = &search.values;
+    let mut pair_sums: HashMap<isize, Vec<(usize, usize)>> = HashMap::new();
+    let mut results = Vec::new();
+    for third in 0..values.len() {
+        // pair_sums holds pairs (first, second) with second < third, so each tuple is found once
+        for fourth in (third + 1)..values.len() {
+            if let Some(pairs) = pair_sums.get(&(target - values[third] - values[fourth])) {
+                for (first, second) in pairs {
+                    results.push(search.to_indices(&[*first, *second, third, fourth]));
+                    if first_only {
+                        return results;
+                    }
+                }
+            }
+        }
+        for first in 0..third {
+            pair_sums
+                .entry(values[first] + values[third])
+                .or_default()
+                .push((first, third));
+        }
+    }
+    results
+}
+
+/// Every multiset of `k` indices (0-based, ascending, possibly repeated) into `array` whose
+/// values sum to `target`, i.e. k-SUM where each element may be used more than once
+pub fn k_sum_all_with_repetition(
+    array: &[isize],
+    k: usize,
+    target: isize,
+    strategy: KSumStrategy,
+) -> Vec<Vec<usize>> {
+    let mut search = KSumSearch::new(array, strategy, false);
+    search.repetition = true;
+    search.run(k, target)
+}
+
+/// Multisets of `k` integer masses adding up to the precursor mass, as ascending indices into
+/// `masses`. A mass may be used any number of times, as an amino acid may occur more than once
+/// in a peptide.
+pub fn mass_combinations(masses: &[usize], precursor_mass: usize, k: usize) -> Vec<Vec<usize>> {
+    let masses: Vec<_> = masses.iter().map(|mass| *mass as isize).collect();
+    k_sum_all_with_repetition(
+        &masses,
+        k,
+        precursor_mass as isize,
+        KSumStrategy::TwoPointer,
+    )
+}
+
+/// Recursive k-SUM over the sorted array: the smallest element of each tuple is fixed
+/// and the rest is a (k-1)-SUM on the elements after it (or from it, with repetition)
+struct KSumSearch {
+    values: Vec<isize>,
+    /// original index of each sorted value
+    indices: Vec<usize>,
+    strategy: KSumStrategy,
+    first_only: bool,
+    /// whether an element may be used more than once in a tuple
+    repetition: bool,
+    results: Vec<Vec<usize>>,
+}
+
+impl KSumSearch {
+    fn new(array: &[isize], strategy: KSumStrategy, first_only: bool) -> Self {
+        let mut array_indices = array.iter().copied().enumerate().collect::<Vec<_>>();
+        array_indices.sort_by_key(|a| a.1);
+        KSumSearch {
+            values: array_indices.iter().map(|a| a.1).collect(),
+            indices: array_indices.iter().map(|a| a.0).collect(),
+            strategy,
+            first_only,
+            repetition: false,
+            results: Vec::new(),
+        }
+    }
+
+    fn run(mut self, k: usize, target: isize) -> Vec<Vec<usize>> {
+        if k > 0 {
+            self.search(0, k, target, &mut Vec::with_capacity(k));
+        }
+        self.results
+    }
+
+    /// Convert positions in the sorted array to ascending original indices
+    fn to_indices(&self, positions: &[usize]) -> Vec<usize> {
+        let mut indices: Vec<_> = positions.iter().map(|p| self.indices[*p]).collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Records a result, returning true if the search should stop
+    fn found(&mut self, prefix: &[usize], last: &[usize]) -> bool {
+        let positions: Vec<_> = prefix.iter().chain(last).copied().collect();
+        let indices = self.to_indices(&positions);
+        self.results.push(indices);
+        self.first_only
+    }
+
+    /// Search `values[start..]` for k elements summing to target, returning true if the search should stop
+    fn search(&mut self, start: usize, k: usize, target: isize, prefix: &mut Vec<usize>) -> bool {
+        match k {
+            1 => {
+                for position in start..self.values.len() {
+                    if self.values[position] == target && self.found(prefix, &[position]) {
+                        return true;
+                    }
+                }
+                false
+            }
+            2 => match self.strategy {
+                KSumStrategy::TwoPointer => self.two_sum_two_pointer(start, target, prefix),
+                KSumStrategy::Hash => self.two_sum_hash(start, target, prefix),
+            },
+            _ => {
+                let (next, remaining) = if self.repetition { (0, 0) } else { (1, k - 1) };
+                for position in start..self.values.len().saturating_sub(remaining) {
+                    prefix.push(position);
+                    let stop = self.search(
+                        position + next,
+                        k - 1,
+                        target - self.values[position],
+                        prefix,
+                    );
+                    prefix.pop();
+                    if stop {
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    fn two_sum_two_pointer(&mut self, start: usize, target: isize, prefix: &[usize]) -> bool {
+        let (next, remaining) = if self.repetition { (0, 1) } else { (1, 2) };
+        if self.values.len() < start + remaining {
+            return false;
         }
-        if sum > target {
-            if index_2 >= 1 {
-                index_2 -= 1;
-            } else {
-                break;
+        let (mut left, mut right) = (start, self.values.len() - 1);
+        while left + next <= right {
+            let (left_value, right_value) = (self.values[left], self.values[right]);
+            match (left_value + right_value).cmp(&target) {
+                Ordering::Less => left += 1,
+                Ordering::Greater => right -= 1,
+                Ordering::Equal if left_value == right_value => {
+                    // everything between left and right is equal, so any pair of them works
+                    for first in left..=right {
+                        for second in (first + next)..=right {
+                            if self.found(prefix, &[first, second]) {
+                                return true;
+                            }
+                        }
+                    }
+                    return false;
+                }
+                Ordering::Equal => {
+                    let left_end = (left..right)
+                        .find(|p| self.values[*p] != left_value)
+                        .unwrap_or(right);
+                    let right_start = ((left_end..=right).rev())
+                        .find(|p| self.values[*p] != right_value)
+                        .map_or(left_end, |p| p + 1);
+                    for first in left..left_end {
+                        for second in right_start..=right {
+                            if self.found(prefix, &[first, second]) {
+                                return true;
+                            }
+                        }
+                    }
+                    left = left_end;
+                    right = right_start - 1;
+                }
             }
-        } else {
-            index_1 += 1;
         }
-        if index_1 > index_2 {
-            break;
+        false
+    }
+
+    fn two_sum_hash(&mut self, start: usize, target: isize, prefix: &[usize]) -> bool {
+        let mut seen: HashMap<isize, Vec<usize>> = HashMap::new();
+        for second in start..self.values.len() {
+            if self.repetition {
+                seen.entry(self.values[second]).or_default().push(second);
+            }
+            if let Some(firsts) = seen.get(&(target - self.values[second])).cloned() {
+                for first in firsts {
+                    if self.found(prefix, &[first, second]) {
+                        return true;
+                    }
+                }
+            }
+            if !self.repetition {
+                seen.entry(self.values[second]).or_default().push(second);
+            }
         }
+        false
     }
-    None
 }
 
 #[cfg(test)]
@@ -90,4 +303,76 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn k_sum_strategies() {
+        let array = [2, -1, 3, -1, 0, 4, -3, 1];
+        let brute_force = |k: usize, target: isize| {
+            let mut tuples: Vec<_> = (0..1u32 << array.len())
+                .filter(|mask| mask.count_ones() as usize == k)
+                .map(|mask| {
+                    (0..array.len())
+                        .filter(|i| mask & (1 << i) != 0)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|tuple| tuple.iter().map(|i| array[*i]).sum::<isize>() == target)
+                .collect();
+            tuples.sort();
+            tuples
+        };
+        for k in 1..=5 {
+            for target in -4..=6 {
+                let expected = brute_force(k, target);
+                for strategy in &[KSumStrategy::TwoPointer, KSumStrategy::Hash] {
+                    let mut all = k_sum_all(&array, k, target, *strategy);
+                    all.sort();
+                    assert_eq!(all, expected);
+                    match k_sum_first(&array, k, target, *strategy) {
+                        Some(first) => assert!(expected.contains(&first)),
+                        None => assert!(expected.is_empty()),
+                    }
+                }
+                if k == 4 {
+                    let mut all = four_sum_meet_in_the_middle(&array, target, false);
+                    all.sort();
+                    assert_eq!(all, expected);
+                }
+            }
+        }
+        let masses = [57, 71, 87, 97, 99, 101, 103];
+        assert_eq!(
+            mass_combinations(&masses, 57 + 87 + 103, 3),
+            vec![vec![0, 2, 6]]
+        );
+        assert_eq!(mass_combinations(&masses, 3 * 57, 3), vec![vec![0, 0, 0]]);
+        let mut combinations = mass_combinations(&masses, 261, 3);
+        combinations.sort();
+        assert_eq!(
+            combinations,
+            vec![vec![0, 5, 6], vec![1, 2, 6], vec![2, 2, 2]]
+        );
+        assert!(mass_combinations(&masses, 56, 1).is_empty());
+
+        // with repetition, tuples are nondecreasing index sequences
+        for k in 1..=4 {
+            let n = array.len();
+            let candidates: Vec<Vec<usize>> = (0..n.pow(k as u32))
+                .map(|code| (0..k).rev().map(|i| code / n.pow(i as u32) % n).collect())
+                .filter(|tuple: &Vec<usize>| tuple.windows(2).all(|w| w[0] <= w[1]))
+                .collect();
+            for target in -6..=8 {
+                let mut expected: Vec<_> = candidates
+                    .iter()
+                    .filter(|tuple| tuple.iter().map(|i| array[*i]).sum::<isize>() == target)
+                    .cloned()
+                    .collect();
+                expected.sort();
+                for strategy in &[KSumStrategy::TwoPointer, KSumStrategy::Hash] {
+                    let mut all = k_sum_all_with_repetition(&array, k, target, *strategy);
+                    all.sort();
+                    assert_eq!(all, expected, "{} {} {:?}", k, target, strategy);
+                }
+            }
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use a_2sum::{k_sum_first, KSumStrategy};
 use std::path::Path;
 use utility::io::Parseable;
 
@@ -12,24 +13,12 @@ pub fn rosalind_3sum(filename: &Path) -> Result<Vec<Option<(usize, usize, usize)
     let input = utility::io::input_from_file(filename)?;
     let mut lines = input.split('\n');
     let length_input = usize::parse_line(lines.next().unwrap())?;
-    let length = length_input[1];
     let mut output = Vec::with_capacity(length_input[0]);
     for line in lines {
-        let mut array_indices = isize::parse_line(line)?
-            .into_iter()
-            .enumerate()
-            .collect::<Vec<_>>();
-        array_indices.sort_by(|a, b| a.1.cmp(&b.1));
-        let array = array_indices.iter().map(|a| a.1).collect::<Vec<_>>();
-        let indices = array_indices.iter().map(|a| a.0).collect::<Vec<_>>();
-        match three_sum(length, &array, 0) {
-            Some((index_1, index_2, index_3)) => {
-                let mut real_indices = vec![
-                    indices[index_1] + 1,
-                    indices[index_2] + 1,
-                    indices[index_3] + 1,
-                ];
-                real_indices.sort_unstable();
+        let array = isize::parse_line(line)?;
+        match k_sum_first(&array, 3, 0, KSumStrategy::TwoPointer) {
+            Some(indices) => {
+                let real_indices: Vec<_> = indices.into_iter().map(|i| i + 1).collect();
                 println!("{}", utility::io::format_array(&real_indices));
                 output.push(Some((real_indices[0], real_indices[1], real_indices[2])));
             }
@@ -42,22 +31,6 @@ pub fn rosalind_3sum(filename: &Path) -> Result<Vec<Option<(usize, usize, usize)
     Ok(output)
 }
 
-fn three_sum(length: usize, array: &[isize], target: isize) -> Option<(usize, usize, usize)> {
-    for i in 0..=(length - 2) {
-        let a = array[i];
-        let (mut start, mut end) = (i + 1, length - 1);
-        while start < end {
-            let (b, c) = (array[start], array[end]);
-            match (a + b + c).cmp(&target) {
-                std::cmp::Ordering::Equal => return Some((i, start, end)),
-                std::cmp::Ordering::Less => start += 1,
-                std::cmp::Ordering::Greater => end -= 1,
-            }
-        }
-    }
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use utility::io::Parseable;
@@ -1,8 +1,6 @@
-use std::collections::BinaryHeap;
-
 use anyhow::Error;
 
-use a_dij::State;
+use a_dij::Dijkstra;
 use std::path::Path;
 use utility::io::Parseable;
 
@@ -41,30 +39,7 @@ pub trait DijkstraStartToAll {
 
 impl DijkstraStartToAll for utility::graph::WeightedGraph {
     fn get_dijkstra_start_to_all_distances(&self, start_node: usize) -> Vec<Option<usize>> {
-        let mut distances = (0..self.num_nodes).map(|_| None).collect::<Vec<_>>();
-        let mut heap = BinaryHeap::with_capacity(self.num_nodes);
-        heap.push(State {
-            cost: 0,
-            node: start_node,
-        });
-        while let Some(State { cost, node }) = heap.pop() {
-            if distances[node].is_some() {
-                continue;
-            }
-            distances[node] = Some(cost);
-            if let Some(edge_list) = self.adjacency_list.get(&self.nodes[node]) {
-                for (child, weight) in edge_list {
-                    let next = State {
-                        cost: cost + (*weight as usize),
-                        node: self.node_to_index[child],
-                    };
-                    if distances[next.node].is_none() {
-                        heap.push(next);
-                    }
-                }
-            }
-        }
-        distances
+        self.get_dijkstra_distances(start_node, None)
     }
 }
 
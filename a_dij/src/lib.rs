@@ -1,8 +1,6 @@
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-
 use anyhow::Error;
 
+use a_hea::indexed_heap::IndexedHeap;
 use std::path::Path;
 
 /// Dijkstra's Algorithm
@@ -17,36 +15,59 @@ pub fn rosalind_dij(filename: &Path) -> Result<Vec<isize>, Error> {
         .filter(|s| !s.trim().is_empty())
         .map(|s| s.to_owned());
     let graph = utility::graph::WeightedGraph::from_weighted_edge_list(&mut lines)?;
-    let mut lengths = Vec::with_capacity(graph.num_nodes);
-    for node in 0..graph.num_nodes {
-        match graph.get_dijkstra_start_to_end_distance(0, node) {
-            Some(cost) => lengths.push(cost as isize),
-            None => lengths.push(-1),
-        }
-    }
+    let lengths: Vec<_> = graph
+        .get_dijkstra_distances(0, None)
+        .into_iter()
+        .map(|cost| cost.map_or(-1, |cost| cost as isize))
+        .collect();
     println!("{}", utility::io::format_array(&lengths));
     Ok(lengths)
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-pub struct State {
-    pub cost: usize,
-    pub node: usize,
-}
-
-/// Makes State min-heapable
-impl Ord for State {
-    fn cmp(&self, other: &State) -> Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.node.cmp(&other.node))
-    }
+pub trait Dijkstra {
+    /// Lengths of shortest (weighted) paths from start_node to every node (None if unreachable).
+    /// Stops early once end_node is reached, if given.
+    fn get_dijkstra_distances(
+        &self,
+        start_node: usize,
+        end_node: Option<usize>,
+    ) -> Vec<Option<usize>>;
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Dijkstra for utility::graph::WeightedGraph {
+    fn get_dijkstra_distances(
+        &self,
+        start_node: usize,
+        end_node: Option<usize>,
+    ) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.num_nodes];
+        let mut handles = vec![None; self.num_nodes];
+        // a d-ary heap with d ≈ average degree balances decrease_key against pop
+        let arity = (self.num_edges / self.num_nodes.max(1)).max(2);
+        let mut heap = IndexedHeap::with_arity(arity);
+        handles[start_node] = Some(heap.push(start_node, 0));
+        while let Some((node, cost)) = heap.pop() {
+            distances[node] = Some(cost);
+            if Some(node) == end_node {
+                break;
+            }
+            if let Some(edge_list) = self.adjacency_list.get(&self.nodes[node]) {
+                for (child, weight) in edge_list {
+                    let child = self.node_to_index[child];
+                    if distances[child].is_some() {
+                        continue;
+                    }
+                    let next_cost = cost + (*weight as usize);
+                    match handles[child] {
+                        Some(handle) => {
+                            heap.decrease_key(handle, next_cost);
+                        }
+                        None => handles[child] = Some(heap.push(child, next_cost)),
+                    }
+                }
+            }
+        }
+        distances
     }
 }
 
@@ -64,36 +85,7 @@ impl DijkstraStartToEnd for utility::graph::WeightedGraph {
         start_node: usize,
         end_node: usize,
     ) -> Option<usize> {
-        let mut distances = (0..self.num_nodes)
-            .map(|_| ::std::usize::MAX)
-            .collect::<Vec<_>>();
-        distances[start_node] = 0;
-        let mut heap = BinaryHeap::with_capacity(self.num_nodes);
-        heap.push(State {
-            cost: distances[start_node],
-            node: start_node,
-        });
-        while let Some(State { cost, node }) = heap.pop() {
-            if node == end_node {
-                return Some(cost);
-            }
-            if cost > distances[node] {
-                continue;
-            }
-            if let Some(edge_list) = self.adjacency_list.get(&self.nodes[node]) {
-                for (child, weight) in edge_list {
-                    let next = State {
-                        cost: cost + (*weight as usize),
-                        node: self.node_to_index[child],
-                    };
-                    if next.cost < distances[next.node] {
-                        distances[next.node] = next.cost;
-                        heap.push(next);
-                    }
-                }
-            }
-        }
-        None
+        self.get_dijkstra_distances(start_node, Some(end_node))[end_node]
     }
 }
 
@@ -0,0 +1,227 @@
+//! Indexed d-ary min-heap with decrease-key.
+//!
+//! Every pushed entry gets a `Handle` which stays valid until the entry is popped or removed,
+//! so its priority can be changed in place instead of pushing a duplicate entry.
+
+/// Refers to an entry in an `IndexedHeap`.
+/// Handles of popped or removed entries are detected as stale rather than aliasing new entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<K, P> {
+    key: K,
+    priority: P,
+    /// index of this entry in `IndexedHeap::heap`
+    position: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<K, P> {
+    arity: usize,
+    /// slots, in heap order
+    heap: Vec<usize>,
+    entries: Vec<Option<Entry<K, P>>>,
+    generations: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl<K, P: Ord> Default for IndexedHeap<K, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, P: Ord> IndexedHeap<K, P> {
+    /// Binary min-heap
+    pub fn new() -> Self {
+        Self::with_arity(2)
+    }
+
+    /// d-ary min-heap. Higher arity makes `decrease_key` cheaper and `pop` more expensive,
+    /// which suits dense graphs
+    pub fn with_arity(arity: usize) -> Self {
+        assert!(arity >= 2, "heap arity must be at least 2");
+        IndexedHeap {
+            arity,
+            heap: Vec::new(),
+            entries: Vec::new(),
+            generations: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Is the handle's entry still in the heap
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.generations.get(handle.slot) == Some(&handle.generation)
+            && self.entries[handle.slot].is_some()
+    }
+
+    pub fn push(&mut self, key: K, priority: P) -> Handle {
+        let position = self.heap.len();
+        let entry = Some(Entry {
+            key,
+            priority,
+            position,
+        });
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.entries[slot] = entry;
+                slot
+            }
+            None => {
+                self.entries.push(entry);
+                self.generations.push(0);
+                self.entries.len() - 1
+            }
+        };
+        self.heap.push(slot);
+        self.sift_up(position);
+        Handle {
+            slot,
+            generation: self.generations[slot],
+        }
+    }
+
+    /// Entry with the smallest priority
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|slot| {
+            let entry = self.entry(*slot);
+            (&entry.key, &entry.priority)
+        })
+    }
+
+    /// Remove and return the entry with the smallest priority
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        let slot = *self.heap.first()?;
+        self.remove_slot(slot)
+    }
+
+    pub fn key(&self, handle: Handle) -> Option<&K> {
+        if self.contains(handle) {
+            Some(&self.entry(handle.slot).key)
+        } else {
+            None
+        }
+    }
+
+    pub fn priority(&self, handle: Handle) -> Option<&P> {
+        if self.contains(handle) {
+            Some(&self.entry(handle.slot).priority)
+        } else {
+            None
+        }
+    }
+
+    /// Lower the priority of an entry.
+    /// Returns false (and leaves the heap unchanged) if the handle is stale or the new priority is higher
+    pub fn decrease_key(&mut self, handle: Handle, priority: P) -> bool {
+        if !self.contains(handle) || priority > self.entry(handle.slot).priority {
+            return false;
+        }
+        let entry = self.entry_mut(handle.slot);
+        entry.priority = priority;
+        let position = entry.position;
+        self.sift_up(position);
+        true
+    }
+
+    /// Raise the priority of an entry.
+    /// Returns false (and leaves the heap unchanged) if the handle is stale or the new priority is lower
+    pub fn increase_key(&mut self, handle: Handle, priority: P) -> bool {
+        if !self.contains(handle) || priority < self.entry(handle.slot).priority {
+            return false;
+        }
+        let entry = self.entry_mut(handle.slot);
+        entry.priority = priority;
+        let position = entry.position;
+        self.sift_down(position);
+        true
+    }
+
+    /// Remove an entry by handle, returning None if it was already popped or removed
+    pub fn remove(&mut self, handle: Handle) -> Option<(K, P)> {
+        if self.contains(handle) {
+            self.remove_slot(handle.slot)
+        } else {
+            None
+        }
+    }
+
+    fn remove_slot(&mut self, slot: usize) -> Option<(K, P)> {
+        let position = self.entry(slot).position;
+        let last = self.heap.len() - 1;
+        self.swap(position, last);
+        self.heap.pop();
+        let entry = self.entries[slot].take()?;
+        self.generations[slot] += 1;
+        self.free.push(slot);
+        if position < self.heap.len() {
+            self.sift_up(position);
+            self.sift_down(position);
+        }
+        Some((entry.key, entry.priority))
+    }
+
+    fn entry(&self, slot: usize) -> &Entry<K, P> {
+        self.entries[slot].as_ref().expect("heap slot is empty")
+    }
+
+    fn entry_mut(&mut self, slot: usize) -> &mut Entry<K, P> {
+        self.entries[slot].as_mut().expect("heap slot is empty")
+    }
+
+    fn less(&self, position_1: usize, position_2: usize) -> bool {
+        self.entry(self.heap[position_1]).priority < self.entry(self.heap[position_2]).priority
+    }
+
+    fn swap(&mut self, position_1: usize, position_2: usize) {
+        self.heap.swap(position_1, position_2);
+        let (slot_1, slot_2) = (self.heap[position_1], self.heap[position_2]);
+        self.entry_mut(slot_1).position = position_1;
+        self.entry_mut(slot_2).position = position_2;
+    }
+
+    fn sift_up(&mut self, position: usize) {
+        let mut position = position;
+        while position > 0 {
+            let parent = (position - 1) / self.arity;
+            if !self.less(position, parent) {
+                return;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, position: usize) {
+        let mut position = position;
+        loop {
+            let first_child = self.arity * position + 1;
+            let last_child = (first_child + self.arity).min(self.heap.len());
+            let smallest = (first_child..last_child).fold(position, |smallest, child| {
+                if self.less(child, smallest) {
+                    child
+                } else {
+                    smallest
+                }
+            });
+            if smallest == position {
+                return;
+            }
+            self.swap(position, smallest);
+            position = smallest;
+        }
+    }
+}
@@ -1,5 +1,8 @@
+pub mod indexed_heap;
+
 use anyhow::Error;
 
+use std::cmp::Ordering;
 use std::path::Path;
 
 /// Building a Heap
@@ -8,33 +11,48 @@ use std::path::Path;
 ///
 /// Return: A permuted array A satisfying the binary max heap property: for any 2≤i≤n, A[⌊i/2⌋]≥A[i].
 pub fn rosalind_hea(filename: &Path) -> Result<Vec<isize>, Error> {
-    let (length, mut array) = utility::io::read_isize_array(filename)?;
-    build_max_heap(&mut array, length);
+    let (_, mut array) = utility::io::read_isize_array(filename)?;
+    build_max_heap(&mut array);
     println!("{}", utility::io::format_array(&array));
     Ok(array)
 }
 
-fn build_max_heap<T: PartialOrd + PartialEq + Copy>(array: &mut [T], length: usize) {
-    let mid = ((length as f64) / 2.).floor() as usize;
-    for i in (0..mid).rev() {
-        max_heapify(array, i)
+/// Rearrange `array` so that it satisfies the binary max heap property
+pub fn build_max_heap<T: Ord>(array: &mut [T]) {
+    build_max_heap_by(array, T::cmp)
+}
+
+/// Build a binary heap ordered by `compare`, with the greatest element at the root
+pub fn build_max_heap_by<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], mut compare: F) {
+    for i in (0..array.len() / 2).rev() {
+        max_heapify_by(array, i, &mut compare)
     }
 }
 
-fn max_heapify<T: PartialOrd + PartialEq + Copy>(array: &mut [T], i: usize) {
-    let left = 2 * i + 1;
-    let right = left + 1;
-    let mut largest = i;
+/// Sift the element at `i` down until its subtree satisfies the max heap property
+pub fn max_heapify<T: Ord>(array: &mut [T], i: usize) {
+    max_heapify_by(array, i, T::cmp)
+}
+
+/// Sift the element at `i` down according to `compare`
+pub fn max_heapify_by<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], i: usize, mut compare: F) {
     let length = array.len();
-    if left < length && array[left] > array[largest] {
-        largest = left;
-    }
-    if right < length && array[right] > array[largest] {
-        largest = right;
-    }
-    if largest != i {
+    let mut i = i;
+    loop {
+        let left = 2 * i + 1;
+        let right = left + 1;
+        let mut largest = i;
+        if left < length && compare(&array[left], &array[largest]) == Ordering::Greater {
+            largest = left;
+        }
+        if right < length && compare(&array[right], &array[largest]) == Ordering::Greater {
+            largest = right;
+        }
+        if largest == i {
+            return;
+        }
         array.swap(i, largest);
-        max_heapify(array, largest);
+        i = largest;
     }
 }
 
@@ -49,4 +67,28 @@ mod tests {
         assert!((2..=result.len()).all(|i| result[(i / 2) - 1] >= result[i - 1]));
         Ok(())
     }
+
+    #[test]
+    fn hea_indexed() {
+        let mut heap = indexed_heap::IndexedHeap::with_arity(3);
+        let handles: Vec<_> = [5, 3, 8, 1, 9, 7]
+            .iter()
+            .enumerate()
+            .map(|(key, priority)| heap.push(key, *priority))
+            .collect();
+        assert_eq!(heap.peek(), Some((&3, &1)));
+        assert!(heap.decrease_key(handles[4], 0));
+        assert!(!heap.decrease_key(handles[2], 10));
+        assert!(heap.increase_key(handles[3], 6));
+        assert_eq!(heap.remove(handles[0]), Some((0, 5)));
+        assert_eq!(heap.remove(handles[0]), None);
+        let mut popped = Vec::new();
+        while let Some(entry) = heap.pop() {
+            popped.push(entry);
+        }
+        assert_eq!(popped, vec![(4, 0), (1, 3), (3, 6), (5, 7), (2, 8)]);
+        assert!(!heap.contains(handles[1]));
+        let handle = heap.push(10, 1);
+        assert!(heap.contains(handle) && !heap.contains(handles[5]));
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use std::cmp::Ordering;
 use std::path::Path;
 
 /// Heap Sort
@@ -8,28 +9,45 @@ use std::path::Path;
 ///
 /// Return: A sorted array A.
 pub fn rosalind_hs(filename: &Path) -> Result<Vec<isize>, Error> {
-    let (length, mut array) = utility::io::read_isize_array(filename)?;
-    heap_sort(&mut array, length);
+    let (_, mut array) = utility::io::read_isize_array(filename)?;
+    heap_sort(&mut array);
     println!("{}", utility::io::format_array(&array));
     Ok(array)
 }
 
-fn heap_sort<T: Copy + PartialOrd + PartialEq>(array: &mut [T], length: usize) {
-    heapify(array, length);
+/// Heap sort (not stable)
+pub fn heap_sort<T: Ord>(array: &mut [T]) {
+    heap_sort_by(array, T::cmp)
+}
+
+/// Heap sort with a comparator
+pub fn heap_sort_by<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], mut compare: F) {
+    let length = array.len();
+    heapify(array, &mut compare);
     for last in (1..length).rev() {
         array.swap(last, 0);
-        sift_down(array, 0, last);
+        sift_down(array, 0, last, &mut compare);
     }
 }
 
-fn heapify<T: Copy + PartialEq + PartialOrd>(array: &mut [T], length: usize) {
-    let mid = (((length - 1) as f64) / 2.).floor() as usize;
-    for i in (0..mid).rev() {
-        sift_down(array, i, length);
+/// Heap sort by a key extracted from each element
+pub fn heap_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(array: &mut [T], mut key: F) {
+    heap_sort_by(array, |a, b| key(a).cmp(&key(b)))
+}
+
+fn heapify<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], compare: &mut F) {
+    let length = array.len();
+    for i in (0..length / 2).rev() {
+        sift_down(array, i, length, compare);
     }
 }
 
-fn sift_down<T: PartialOrd + PartialEq + Copy>(array: &mut [T], i: usize, length: usize) {
+fn sift_down<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    i: usize,
+    length: usize,
+    compare: &mut F,
+) {
     let mut root = i;
     let mut left;
     let mut right;
@@ -38,10 +56,10 @@ fn sift_down<T: PartialOrd + PartialEq + Copy>(array: &mut [T], i: usize, length
         largest = root;
         left = 2 * root + 1;
         right = left + 1;
-        if left < length && array[left] > array[largest] {
+        if left < length && compare(&array[left], &array[largest]) == Ordering::Greater {
             largest = left;
         }
-        if right < length && array[right] > array[largest] {
+        if right < length && compare(&array[right], &array[largest]) == Ordering::Greater {
             largest = right;
         }
         if root == largest {
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use std::cmp::Ordering;
 use std::path::Path;
 use utility::io::Parseable;
 
@@ -11,19 +12,27 @@ use utility::io::Parseable;
 pub fn rosalind_ins(filename: &Path) -> Result<usize, Error> {
     let input = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = input.split('\n').collect();
-    let length = lines[0].parse::<usize>()?;
     let mut array = isize::parse_line(lines[1])?;
-    let count = insertion_sort(length, &mut array);
+    let count = insertion_sort(&mut array);
     println!("{}", count);
     Ok(count)
 }
 
-fn insertion_sort<T: PartialOrd>(length: usize, array: &mut [T]) -> usize {
+/// Stable insertion sort, returning the number of swaps performed
+pub fn insertion_sort<T: Ord>(array: &mut [T]) -> usize {
+    insertion_sort_by(array, T::cmp)
+}
+
+/// Insertion sort with a comparator, returning the number of swaps performed
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    mut compare: F,
+) -> usize {
     let mut count = 0;
     let mut k;
-    for i in 1..length {
+    for i in 1..array.len() {
         k = i;
-        while k > 0 && array[k] < array[k - 1] {
+        while k > 0 && compare(&array[k], &array[k - 1]) == Ordering::Less {
             array.swap(k - 1, k);
             count += 1;
             k -= 1;
@@ -32,6 +41,11 @@ fn insertion_sort<T: PartialOrd>(length: usize, array: &mut [T]) -> usize {
     count
 }
 
+/// Insertion sort by a key extracted from each element, returning the number of swaps performed
+pub fn insertion_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(array: &mut [T], mut key: F) -> usize {
+    insertion_sort_by(array, |a, b| key(a).cmp(&key(b)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use a_med::order_statistic_tree::OrderStatisticTree;
 use std::path::Path;
 
 /// Counting Inversions
@@ -9,45 +10,20 @@ use std::path::Path;
 /// Return: The number of inversions in A.
 pub fn rosalind_inv(filename: &Path) -> Result<usize, Error> {
     let (_, array) = utility::io::read_isize_array(filename)?;
-    let count = merge_sort_count(&array).1;
+    let count = count_inversions(&array);
     println!("{}", count);
     Ok(count)
 }
 
-fn merge_sort_count<T: PartialOrd + PartialEq + Copy>(array: &[T]) -> (Vec<T>, usize) {
-    let length = array.len();
-    if length > 1 {
-        let mid = length / 2;
-        let (left_array, count_left) = merge_sort_count(&array[..mid]);
-        let (right_array, count_right) = merge_sort_count(&array[mid..]);
-        let (merged_array, count_merge) = merge_count(&left_array, &right_array);
-        (merged_array, count_left + count_right + count_merge)
-    } else {
-        (array.to_vec(), 0)
+/// Each element forms an inversion with every earlier element greater than it
+fn count_inversions<T: Ord + Clone>(array: &[T]) -> usize {
+    let mut seen = OrderStatisticTree::new();
+    let mut count = 0;
+    for (i, element) in array.iter().enumerate() {
+        count += i - seen.rank_inclusive(element);
+        seen.insert(element.clone());
     }
-}
-
-pub fn merge_count<T: PartialOrd + PartialEq + Copy>(
-    left_array: &[T],
-    right_array: &[T],
-) -> (Vec<T>, usize) {
-    let mut i = 0;
-    let mut j = 0;
-    let mut inversions = 0;
-    let mut sorted_array = Vec::new();
-    while i < left_array.len() && j < right_array.len() {
-        if left_array[i] <= right_array[j] {
-            sorted_array.push(left_array[i]);
-            i += 1;
-        } else {
-            sorted_array.push(right_array[j]);
-            j += 1;
-            inversions += left_array.len() - i;
-        }
-    }
-    sorted_array.extend(&left_array[i..]);
-    sorted_array.extend(&right_array[j..]);
-    (sorted_array, inversions)
+    count
 }
 
 #[cfg(test)]
@@ -1,8 +1,18 @@
+pub mod order_statistic_tree;
+pub mod quantile;
+
 use anyhow::Error;
 use rand::{thread_rng, Rng};
+use std::cmp::Ordering;
 use std::path::Path;
 use utility::io::Parseable;
 
+use a_ins::insertion_sort_by;
+use a_par3::partition_by as three_way_partition_by;
+
+/// Slices at most this long are finished off with insertion sort during selection
+const SMALL_SELECT_LENGTH: usize = 5;
+
 /// Median
 ///
 /// Given: A positive integer n≤105 and an array A[1..n] of integers
@@ -12,20 +22,32 @@ use utility::io::Parseable;
 pub fn rosalind_med(filename: &Path) -> Result<isize, Error> {
     let input = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = input.split('\n').collect();
-    let length = lines[0].parse::<usize>()?;
     let mut array = isize::parse_line(lines[1])?;
     let k = lines[2].parse::<usize>()?;
-    let output = select(&mut array, 0, length - 1, k - 1);
+    let output = *quickselect(&mut array, k - 1);
     println!("{}", output);
     Ok(output)
 }
 
-pub fn partition(array: &mut [isize], left: usize, right: usize, pivot_index: usize) -> usize {
-    let pivot = array[pivot_index];
+/// Lomuto partition of `array[left..=right]` around the element at `pivot_index`.
+///
+/// Returns the final index of the pivot
+pub fn partition<T: Ord>(array: &mut [T], left: usize, right: usize, pivot_index: usize) -> usize {
+    partition_by(array, left, right, pivot_index, T::cmp)
+}
+
+/// Lomuto partition with a comparator
+pub fn partition_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    left: usize,
+    right: usize,
+    pivot_index: usize,
+    mut compare: F,
+) -> usize {
     array.swap(pivot_index, right);
     let mut store_index = left;
     for i in left..right {
-        if array[i] < pivot {
+        if compare(&array[i], &array[right]) == Ordering::Less {
             array.swap(store_index, i);
             store_index += 1;
         }
@@ -34,23 +56,133 @@ pub fn partition(array: &mut [isize], left: usize, right: usize, pivot_index: us
     store_index
 }
 
-fn select(array: &mut [isize], left: usize, right: usize, k: usize) -> isize {
-    let mut pivot_index;
-    let (mut left, mut right) = (left, right);
+/// 3-way partition of `array` around the element at `pivot_index`.
+///
+/// Returns `(q, r)` such that `array[..q]` is less than the pivot, `array[q..r]` is equal to it
+/// and `array[r..]` is greater
+pub fn three_way_partition_at<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    pivot_index: usize,
+    mut compare: F,
+) -> (usize, usize) {
+    array.swap(0, pivot_index);
+    let (pivot, rest) = array.split_first_mut().unwrap();
+    let (less_end, greater_start) = three_way_partition_by(rest, pivot, |a, b| compare(a, b));
+    // move the pivot from the front to the end of the smaller elements
+    array.swap(0, less_end);
+    (less_end, greater_start + 1)
+}
+
+/// Rearrange `array` so that its k-th smallest (0-indexed) element is at index k,
+/// choosing each pivot with `choose_pivot`
+fn select_with<T, F, P>(array: &mut [T], k: usize, compare: &mut F, mut choose_pivot: P)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    P: FnMut(&mut [T], &mut F) -> usize,
+{
+    assert!(k < array.len(), "k = {} out of range", k);
+    let (mut left, mut right) = (0, array.len());
     loop {
-        if left == right {
-            return array[left];
+        let sub_array = &mut array[left..right];
+        if sub_array.len() <= SMALL_SELECT_LENGTH {
+            insertion_sort_by(sub_array, |a, b| compare(a, b));
+            return;
         }
-        pivot_index = thread_rng().gen_range(left..right);
-        pivot_index = partition(array, left, right, pivot_index);
-        match pivot_index.cmp(&k) {
-            std::cmp::Ordering::Less => left = pivot_index + 1,
-            std::cmp::Ordering::Greater => right = pivot_index - 1,
-            std::cmp::Ordering::Equal => return array[pivot_index],
+        let pivot_index = choose_pivot(sub_array, compare);
+        let (less_end, greater_start) =
+            three_way_partition_at(sub_array, pivot_index, |a, b| compare(a, b));
+        if k < left + less_end {
+            right = left + less_end;
+        } else if k >= left + greater_start {
+            left += greater_start;
+        } else {
+            return;
         }
     }
 }
 
+/// Moves the medians of each group of 5 to the front of `array`
+/// and returns the index of the median of those medians
+fn median_of_medians_pivot<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    compare: &mut F,
+) -> usize {
+    let mut num_medians = 0;
+    for group_start in (0..array.len()).step_by(5) {
+        let group_end = (group_start + 5).min(array.len());
+        insertion_sort_by(&mut array[group_start..group_end], |a, b| compare(a, b));
+        array.swap(num_medians, group_start + (group_end - group_start - 1) / 2);
+        num_medians += 1;
+    }
+    let mid = (num_medians - 1) / 2;
+    select_with(
+        &mut array[..num_medians],
+        mid,
+        compare,
+        median_of_medians_pivot,
+    );
+    mid
+}
+
+/// Randomized quickselect: k-th smallest (0-indexed) element in expected linear time.
+/// `array` is rearranged so that the result is at index k
+pub fn quickselect<T: Ord>(array: &mut [T], k: usize) -> &T {
+    quickselect_by(array, k, T::cmp)
+}
+
+/// Randomized quickselect with a comparator
+pub fn quickselect_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &T {
+    let mut rng = thread_rng();
+    select_with(array, k, &mut compare, |sub_array, _| {
+        rng.gen_range(0..sub_array.len())
+    });
+    &array[k]
+}
+
+/// Deterministic linear-time selection using the median of medians as pivot
+pub fn median_of_medians_select<T: Ord>(array: &mut [T], k: usize) -> &T {
+    median_of_medians_select_by(array, k, T::cmp)
+}
+
+/// Deterministic linear-time selection with a comparator
+pub fn median_of_medians_select_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &T {
+    select_with(array, k, &mut compare, median_of_medians_pivot);
+    &array[k]
+}
+
+/// Introselect: quickselect with random pivots, falling back to median of medians pivots
+/// after 2·log2(n) rounds so the worst case stays linear
+pub fn introselect<T: Ord>(array: &mut [T], k: usize) -> &T {
+    introselect_by(array, k, T::cmp)
+}
+
+/// Introselect with a comparator
+pub fn introselect_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> &T {
+    let mut rng = thread_rng();
+    let mut random_rounds = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    select_with(array, k, &mut compare, |sub_array, compare| {
+        if random_rounds > 0 {
+            random_rounds -= 1;
+            rng.gen_range(0..sub_array.len())
+        } else {
+            median_of_medians_pivot(sub_array, compare)
+        }
+    });
+    &array[k]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,4 +194,35 @@ mod tests {
         assert_eq!(rosalind_med(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn med_selection() {
+        let array: Vec<isize> = (0..200).map(|i| (i * 37) % 23 - 11).collect();
+        let mut sorted = array.clone();
+        sorted.sort_unstable();
+        for k in [0, 17, 100, 199] {
+            assert_eq!(*quickselect(&mut array.clone(), k), sorted[k]);
+            assert_eq!(*median_of_medians_select(&mut array.clone(), k), sorted[k]);
+            assert_eq!(*introselect(&mut array.clone(), k), sorted[k]);
+        }
+
+        let mut tree = order_statistic_tree::OrderStatisticTree::new();
+        for value in &array {
+            tree.insert(*value);
+        }
+        assert_eq!(tree.len(), array.len());
+        assert_eq!(tree.select(100), Some(&sorted[100]));
+        assert_eq!(tree.rank(&0), sorted.iter().filter(|v| **v < 0).count());
+        assert!(tree.remove(&0));
+        assert_eq!(
+            tree.rank_inclusive(&0),
+            sorted.iter().filter(|v| **v <= 0).count() - 1
+        );
+
+        let mut estimator = quantile::P2Quantile::new(0.5);
+        for i in 0..10_001 {
+            estimator.push(((i * 7919) % 10_001) as f64);
+        }
+        assert!((estimator.estimate().unwrap() - 5000.).abs() < 100.);
+    }
 }
@@ -0,0 +1,195 @@
+//! Order-statistic tree: a multiset supporting rank and select queries under insertions and
+//! removals in expected O(log n) time, implemented as a treap with subtree sizes.
+use rand::{thread_rng, Rng};
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderStatisticTree<T> {
+    nodes: Vec<Node<T>>,
+    /// indices of removed nodes, reused by later insertions
+    free: Vec<usize>,
+    root: Option<usize>,
+}
+
+impl<T: Ord> Default for OrderStatisticTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> OrderStatisticTree<T> {
+    pub fn new() -> Self {
+        OrderStatisticTree {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Add a value (duplicates are kept)
+    pub fn insert(&mut self, value: T) {
+        let (less, greater_equal) = self.split(self.root, &value, false);
+        let node = self.new_node(value);
+        let less = self.merge(less, Some(node));
+        self.root = self.merge(less, greater_equal);
+    }
+
+    /// Remove one occurrence of a value, returning whether it was present
+    pub fn remove(&mut self, value: &T) -> bool {
+        let (less, greater_equal) = self.split(self.root, value, false);
+        let (equal, greater) = self.split(greater_equal, value, true);
+        let removed = equal.is_some();
+        let equal = match equal {
+            Some(node) => {
+                self.free.push(node);
+                self.merge(self.nodes[node].left, self.nodes[node].right)
+            }
+            None => None,
+        };
+        let greater_equal = self.merge(equal, greater);
+        self.root = self.merge(less, greater_equal);
+        removed
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.rank_inclusive(value) > self.rank(value)
+    }
+
+    /// Number of values strictly less than `value`
+    pub fn rank(&self, value: &T) -> usize {
+        self.count_while(|node_value| node_value < value)
+    }
+
+    /// Number of values less than or equal to `value`
+    pub fn rank_inclusive(&self, value: &T) -> usize {
+        self.count_while(|node_value| node_value <= value)
+    }
+
+    /// k-th smallest value (0-indexed)
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut current = self.root;
+        while let Some(node) = current {
+            let left_size = self.size(self.nodes[node].left);
+            match k.cmp(&left_size) {
+                std::cmp::Ordering::Less => current = self.nodes[node].left,
+                std::cmp::Ordering::Equal => return Some(&self.nodes[node].value),
+                std::cmp::Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = self.nodes[node].right;
+                }
+            }
+        }
+        None
+    }
+
+    /// Number of values for which `goes_left` is true, given that it is true
+    /// for a prefix of the values in sorted order
+    fn count_while<F: Fn(&T) -> bool>(&self, goes_left: F) -> usize {
+        let mut count = 0;
+        let mut current = self.root;
+        while let Some(node) = current {
+            if goes_left(&self.nodes[node].value) {
+                count += self.size(self.nodes[node].left) + 1;
+                current = self.nodes[node].right;
+            } else {
+                current = self.nodes[node].left;
+            }
+        }
+        count
+    }
+
+    fn new_node(&mut self, value: T) -> usize {
+        let node = Node {
+            value,
+            priority: thread_rng().gen(),
+            size: 1,
+            left: None,
+            right: None,
+        };
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].size)
+    }
+
+    fn update_size(&mut self, node: usize) {
+        self.nodes[node].size =
+            1 + self.size(self.nodes[node].left) + self.size(self.nodes[node].right);
+    }
+
+    /// Split a subtree into values less than `value` (or less than or equal to, if `inclusive`)
+    /// and the rest
+    fn split(
+        &mut self,
+        node: Option<usize>,
+        value: &T,
+        inclusive: bool,
+    ) -> (Option<usize>, Option<usize>) {
+        let node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+        let goes_left = if inclusive {
+            self.nodes[node].value <= *value
+        } else {
+            self.nodes[node].value < *value
+        };
+        if goes_left {
+            let (less, greater) = self.split(self.nodes[node].right, value, inclusive);
+            self.nodes[node].right = less;
+            self.update_size(node);
+            (Some(node), greater)
+        } else {
+            let (less, greater) = self.split(self.nodes[node].left, value, inclusive);
+            self.nodes[node].left = greater;
+            self.update_size(node);
+            (less, Some(node))
+        }
+    }
+
+    /// Merge two subtrees where every value in `left` is at most every value in `right`
+    fn merge(&mut self, left: Option<usize>, right: Option<usize>) -> Option<usize> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(left), Some(right)) => {
+                if self.nodes[left].priority > self.nodes[right].priority {
+                    self.nodes[left].right = self.merge(self.nodes[left].right, Some(right));
+                    self.update_size(left);
+                    Some(left)
+                } else {
+                    self.nodes[right].left = self.merge(Some(left), self.nodes[right].left);
+                    self.update_size(right);
+                    Some(right)
+                }
+            }
+        }
+    }
+}
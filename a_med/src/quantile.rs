@@ -0,0 +1,110 @@
+//! Streaming quantile estimation in constant memory with the P² algorithm
+//! (Jain & Chlamtac, 1985), which tracks five markers whose heights approximate
+//! the minimum, p/2, p, (1+p)/2 quantiles and the maximum.
+
+#[derive(Debug, Clone)]
+pub struct P2Quantile {
+    quantile: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired_positions: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    /// Estimator for the given quantile (between 0 and 1, e.g. 0.5 for the median)
+    pub fn new(quantile: f64) -> Self {
+        assert!((0. ..=1.).contains(&quantile), "quantile must be in [0, 1]");
+        P2Quantile {
+            quantile,
+            count: 0,
+            heights: [0.; 5],
+            positions: [1., 2., 3., 4., 5.],
+            desired_positions: [
+                1.,
+                1. + 2. * quantile,
+                1. + 4. * quantile,
+                3. + 2. * quantile,
+                5.,
+            ],
+            increments: [0., quantile / 2., quantile, (1. + quantile) / 2., 1.],
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights
+                    .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            }
+            return;
+        }
+        self.count += 1;
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|i| value < self.heights[*i]).unwrap() - 1
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.;
+        }
+        for (desired_position, increment) in self.desired_positions.iter_mut().zip(&self.increments)
+        {
+            *desired_position += increment;
+        }
+        for i in 1..4 {
+            let offset = self.desired_positions[i] - self.positions[i];
+            if (offset >= 1. && self.positions[i + 1] - self.positions[i] > 1.)
+                || (offset <= -1. && self.positions[i - 1] - self.positions[i] < -1.)
+            {
+                let direction = offset.signum();
+                let height = self.parabolic(i, direction);
+                self.heights[i] = if self.heights[i - 1] < height && height < self.heights[i + 1] {
+                    height
+                } else {
+                    self.linear(i, direction)
+                };
+                self.positions[i] += direction;
+            }
+        }
+    }
+
+    /// Current estimate of the quantile, or None if no values have been seen
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            1..=4 => {
+                let mut seen = self.heights[..self.count].to_vec();
+                seen.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let index = ((self.count - 1) as f64 * self.quantile).round() as usize;
+                Some(seen[index])
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+
+    fn parabolic(&self, i: usize, direction: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + direction / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + direction) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - direction) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, direction: f64) -> f64 {
+        let j = if direction > 0. { i + 1 } else { i - 1 };
+        self.heights[i]
+            + direction * (self.heights[j] - self.heights[i])
+                / (self.positions[j] - self.positions[i])
+    }
+}
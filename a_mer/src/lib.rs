@@ -1,4 +1,5 @@
 use anyhow::Error;
+use std::cmp::Ordering;
 
 use std::path::Path;
 use utility::io::Parseable;
@@ -19,20 +20,32 @@ pub fn rosalind_mer(filename: &Path) -> Result<Vec<isize>, Error> {
 }
 
 pub fn merge<T: PartialOrd + PartialEq + Copy>(left_array: &[T], right_array: &[T]) -> Vec<T> {
+    merge_by(left_array, right_array, |a, b| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Merge two arrays sorted according to `compare`.
+/// Stable: on ties, elements of `left_array` come first.
+pub fn merge_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+    left_array: &[T],
+    right_array: &[T],
+    mut compare: F,
+) -> Vec<T> {
     let mut i = 0;
     let mut j = 0;
-    let mut sorted_array = Vec::new();
+    let mut sorted_array = Vec::with_capacity(left_array.len() + right_array.len());
     while i < left_array.len() && j < right_array.len() {
-        if left_array[i] <= right_array[j] {
-            sorted_array.push(left_array[i]);
-            i += 1;
-        } else {
-            sorted_array.push(right_array[j]);
+        if compare(&right_array[j], &left_array[i]) == Ordering::Less {
+            sorted_array.push(right_array[j].clone());
             j += 1;
+        } else {
+            sorted_array.push(left_array[i].clone());
+            i += 1;
         }
     }
-    sorted_array.extend(&left_array[i..]);
-    sorted_array.extend(&right_array[j..]);
+    sorted_array.extend_from_slice(&left_array[i..]);
+    sorted_array.extend_from_slice(&right_array[j..]);
     sorted_array
 }
 
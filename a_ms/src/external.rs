@@ -0,0 +1,228 @@
+//! External-memory k-way merge sort, for files that don't fit in memory.
+//!
+//! The input is read in chunks of at most `memory_budget` bytes, each chunk is sorted with
+//! `merge_sort_by` and spilled to a temporary "run" file, and the runs are then merged
+//! with a binary heap (at most `merge_width` runs at a time).
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use a_hea::{build_max_heap_by, max_heapify_by};
+use anyhow::Error;
+
+use crate::merge_sort_by;
+
+/// Bookkeeping cost of each record held in memory, on top of its bytes
+const RECORD_OVERHEAD: usize = std::mem::size_of::<Vec<u8>>();
+
+/// Used to give each run file of this process a unique name
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// How records are laid out in the input and output files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// Records of exactly this many bytes, with no separator
+    FixedWidth(usize),
+    /// Newline-delimited text (the newline is not part of the record)
+    Lines,
+}
+
+impl RecordFormat {
+    /// Read the next record into `record`, returning false at the end of the input
+    fn read_record<R: BufRead>(&self, reader: &mut R, record: &mut Vec<u8>) -> io::Result<bool> {
+        record.clear();
+        match self {
+            RecordFormat::FixedWidth(width) => {
+                record.resize(*width, 0);
+                let mut filled = 0;
+                while filled < *width {
+                    match reader.read(&mut record[filled..])? {
+                        0 if filled == 0 => return Ok(false),
+                        0 => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                "Truncated fixed-width record",
+                            ))
+                        }
+                        n => filled += n,
+                    }
+                }
+                Ok(true)
+            }
+            RecordFormat::Lines => {
+                if reader.read_until(b'\n', record)? == 0 {
+                    return Ok(false);
+                }
+                if record.last() == Some(&b'\n') {
+                    record.pop();
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    fn write_record<W: Write>(&self, writer: &mut W, record: &[u8]) -> io::Result<()> {
+        writer.write_all(record)?;
+        if let RecordFormat::Lines = self {
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings for `external_sort`
+#[derive(Debug, Clone)]
+pub struct ExternalSortConfig {
+    /// Approximate number of bytes of records to hold in memory while building runs
+    pub memory_budget: usize,
+    /// Directory for the temporary run files
+    pub temp_dir: PathBuf,
+    /// Maximum number of runs merged at once (and so the number of files open at once)
+    pub merge_width: usize,
+}
+
+impl Default for ExternalSortConfig {
+    fn default() -> Self {
+        ExternalSortConfig {
+            memory_budget: 256 * 1024 * 1024,
+            temp_dir: std::env::temp_dir(),
+            merge_width: 64,
+        }
+    }
+}
+
+/// Run files on disk, removed when dropped
+struct Runs {
+    files: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn new_file(&mut self, temp_dir: &Path) -> PathBuf {
+        let file = temp_dir.join(format!(
+            "a_ms_run_{}_{}.tmp",
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        self.files.push(file.clone());
+        file
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for file in &self.files {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+/// Sort the records of `input_file` lexicographically by their bytes into `output_file`
+pub fn external_sort(
+    input_file: &Path,
+    output_file: &Path,
+    format: RecordFormat,
+    config: &ExternalSortConfig,
+) -> Result<(), Error> {
+    external_sort_by(input_file, output_file, format, config, |a, b| a.cmp(b))
+}
+
+/// Stable external merge sort of the records of `input_file` into `output_file`, using `compare`
+pub fn external_sort_by<F: FnMut(&[u8], &[u8]) -> Ordering>(
+    input_file: &Path,
+    output_file: &Path,
+    format: RecordFormat,
+    config: &ExternalSortConfig,
+    mut compare: F,
+) -> Result<(), Error> {
+    let mut runs = Runs { files: Vec::new() };
+    let mut run_files = write_sorted_runs(input_file, format, config, &mut runs, &mut compare)?;
+    let merge_width = config.merge_width.max(2);
+    while run_files.len() > merge_width {
+        let mut merged_files = Vec::with_capacity(run_files.len().div_ceil(merge_width));
+        for group in run_files.chunks(merge_width) {
+            let merged_file = runs.new_file(&config.temp_dir);
+            merge_runs(group, &merged_file, format, &mut compare)?;
+            merged_files.push(merged_file);
+        }
+        for file in run_files {
+            fs::remove_file(file)?;
+        }
+        run_files = merged_files;
+    }
+    merge_runs(&run_files, output_file, format, &mut compare)
+}
+
+/// Split the input into sorted runs of at most `config.memory_budget` bytes each
+fn write_sorted_runs<F: FnMut(&[u8], &[u8]) -> Ordering>(
+    input_file: &Path,
+    format: RecordFormat,
+    config: &ExternalSortConfig,
+    runs: &mut Runs,
+    compare: &mut F,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut reader = BufReader::new(File::open(input_file)?);
+    let mut run_files = Vec::new();
+    let mut chunk: Vec<Vec<u8>> = Vec::new();
+    let mut chunk_size = 0;
+    let mut record = Vec::new();
+    loop {
+        let has_record = format.read_record(&mut reader, &mut record)?;
+        if has_record {
+            chunk_size += record.len() + RECORD_OVERHEAD;
+            chunk.push(record.clone());
+        }
+        if (!has_record && !chunk.is_empty()) || chunk_size >= config.memory_budget {
+            merge_sort_by(&mut chunk, |a, b| compare(a, b));
+            let run_file = runs.new_file(&config.temp_dir);
+            let mut writer = BufWriter::new(File::create(&run_file)?);
+            for sorted_record in &chunk {
+                format.write_record(&mut writer, sorted_record)?;
+            }
+            writer.flush()?;
+            run_files.push(run_file);
+            chunk.clear();
+            chunk_size = 0;
+        }
+        if !has_record {
+            return Ok(run_files);
+        }
+    }
+}
+
+/// k-way merge of sorted run files into `output_file`.
+/// Ties are broken by run order, so merging runs written in input order is stable.
+fn merge_runs<F: FnMut(&[u8], &[u8]) -> Ordering>(
+    run_files: &[PathBuf],
+    output_file: &Path,
+    format: RecordFormat,
+    compare: &mut F,
+) -> Result<(), Error> {
+    let mut readers = run_files
+        .iter()
+        .map(|file| Ok(BufReader::new(File::open(file)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    // the heap keeps the smallest (record, run index) at the root
+    let mut heap_compare =
+        |a: &(Vec<u8>, usize), b: &(Vec<u8>, usize)| compare(&b.0, &a.0).then(b.1.cmp(&a.1));
+    let mut heap = Vec::with_capacity(readers.len());
+    for (run_index, reader) in readers.iter_mut().enumerate() {
+        let mut record = Vec::new();
+        if format.read_record(reader, &mut record)? {
+            heap.push((record, run_index));
+        }
+    }
+    build_max_heap_by(&mut heap, &mut heap_compare);
+    let mut writer = BufWriter::new(File::create(output_file)?);
+    while !heap.is_empty() {
+        format.write_record(&mut writer, &heap[0].0)?;
+        let run_index = heap[0].1;
+        if !format.read_record(&mut readers[run_index], &mut heap[0].0)? {
+            heap.swap_remove(0);
+        }
+        max_heapify_by(&mut heap, 0, &mut heap_compare);
+    }
+    writer.flush()?;
+    Ok(())
+}
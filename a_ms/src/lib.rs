@@ -1,6 +1,9 @@
+pub mod external;
+
 use anyhow::Error;
 
-use a_mer::merge;
+use a_mer::merge_by;
+use std::cmp::Ordering;
 use std::path::Path;
 
 /// Merge Sort
@@ -9,19 +12,35 @@ use std::path::Path;
 ///
 /// Return: A sorted array A[1..n].
 pub fn rosalind_ms(filename: &Path) -> Result<Vec<isize>, Error> {
-    let (_, array) = utility::io::read_isize_array(filename)?;
-    let sorted_array = merge_sort(&array);
-    println!("{}", utility::io::format_array(&sorted_array));
-    Ok(sorted_array)
+    let (_, mut array) = utility::io::read_isize_array(filename)?;
+    merge_sort(&mut array);
+    println!("{}", utility::io::format_array(&array));
+    Ok(array)
+}
+
+/// Stable merge sort
+pub fn merge_sort<T: Ord + Clone>(array: &mut [T]) {
+    merge_sort_by(array, T::cmp)
+}
+
+/// Stable merge sort with a comparator
+pub fn merge_sort_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], mut compare: F) {
+    merge_sort_recurse(array, &mut compare)
 }
 
-fn merge_sort<T: PartialOrd + PartialEq + Copy>(array: &[T]) -> Vec<T> {
+/// Stable merge sort by a key extracted from each element
+pub fn merge_sort_by_key<T: Clone, K: Ord, F: FnMut(&T) -> K>(array: &mut [T], mut key: F) {
+    merge_sort_by(array, |a, b| key(a).cmp(&key(b)))
+}
+
+fn merge_sort_recurse<T: Clone, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], compare: &mut F) {
     let length = array.len();
     if length > 1 {
         let mid = length / 2;
-        merge(&merge_sort(&array[..mid]), &merge_sort(&array[mid..]))
-    } else {
-        array.to_vec()
+        merge_sort_recurse(&mut array[..mid], compare);
+        merge_sort_recurse(&mut array[mid..], compare);
+        let merged = merge_by(&array[..mid], &array[mid..], |a, b| compare(a, b));
+        array.clone_from_slice(&merged);
     }
 }
 
@@ -36,4 +55,79 @@ mod tests {
         assert!((1..result.len()).all(|i| result[i - 1] <= result[i]));
         Ok(())
     }
+
+    #[test]
+    fn ms_stable() {
+        let mut array = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')];
+        merge_sort_by_key(&mut array, |(k, _)| *k);
+        assert_eq!(
+            array,
+            vec![(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]
+        );
+    }
+
+    #[test]
+    fn ms_external() -> Result<(), Error> {
+        let temp_dir = std::env::temp_dir().join(format!("a_ms_test_{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir)?;
+        let (input_file, output_file) = (temp_dir.join("input.txt"), temp_dir.join("output.txt"));
+        let mut lines: Vec<String> = (0..500).map(|i| ((i * 7919) % 1009).to_string()).collect();
+        std::fs::write(&input_file, lines.join("\n"))?;
+        let config = external::ExternalSortConfig {
+            memory_budget: 512,
+            temp_dir: temp_dir.clone(),
+            merge_width: 3,
+        };
+        external::external_sort_by(
+            &input_file,
+            &output_file,
+            external::RecordFormat::Lines,
+            &config,
+            |a, b| a.len().cmp(&b.len()).then(a.cmp(b)),
+        )?;
+        lines.sort_by_key(|line| (line.len(), line.clone()));
+        let output = utility::io::input_from_file(&output_file)?;
+        std::fs::remove_dir_all(&temp_dir)?;
+        assert_eq!(output.lines().collect::<Vec<_>>(), lines);
+        Ok(())
+    }
+
+    #[test]
+    fn ms_external_fixed_width() -> Result<(), Error> {
+        use std::convert::TryInto;
+
+        let temp_dir =
+            std::env::temp_dir().join(format!("a_ms_fixed_width_test_{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir)?;
+        let (input_file, output_file) = (temp_dir.join("input.bin"), temp_dir.join("output.bin"));
+        // big-endian numbers sort by their bytes, which include newlines
+        let mut numbers: Vec<u64> = (0..300u64)
+            .map(|i| (i * 0x0a0b_0c0d_0e0f) % 100_003)
+            .collect();
+        let bytes: Vec<u8> = numbers.iter().flat_map(|n| n.to_be_bytes()).collect();
+        std::fs::write(&input_file, &bytes)?;
+        // about 8 records per run, so around 40 runs merged 4 at a time
+        let config = external::ExternalSortConfig {
+            memory_budget: 256,
+            temp_dir: temp_dir.clone(),
+            merge_width: 4,
+        };
+        let format = external::RecordFormat::FixedWidth(8);
+        external::external_sort(&input_file, &output_file, format, &config)?;
+        let output = std::fs::read(&output_file)?;
+        // a trailing partial record is an error
+        std::fs::write(&input_file, &bytes[..bytes.len() - 3])?;
+        let truncated = external::external_sort(&input_file, &output_file, format, &config);
+        std::fs::remove_dir_all(&temp_dir)?;
+        numbers.sort_unstable();
+        assert_eq!(
+            output
+                .chunks(8)
+                .map(|record| u64::from_be_bytes(record.try_into().unwrap()))
+                .collect::<Vec<_>>(),
+            numbers
+        );
+        assert!(truncated.is_err());
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use std::cmp::Ordering;
 use std::path::Path;
 
 /// 3-Way Partition
@@ -9,30 +10,52 @@ use std::path::Path;
 /// Return: An array B[1..n] such that it is a permutation of A and there are indices 1≤q≤r≤n
 /// such that B[i]<A[1] for all 1≤i≤q−1, B[i]=A[1] for all q≤i≤r, and B[i]>A[1] for all r+1≤i≤n.
 pub fn rosalind_par3(filename: &Path) -> Result<Vec<isize>, Error> {
-    let (length, mut array) = utility::io::read_isize_array(filename)?;
+    let (_, mut array) = utility::io::read_isize_array(filename)?;
     let pivot = array[0];
-    partition(&mut array, length, pivot);
+    partition(&mut array, &pivot);
     println!("{}", utility::io::format_array(&array));
     Ok(array)
 }
 
-fn partition<T: PartialOrd + PartialEq + Copy>(array: &mut [T], length: usize, pivot: T) -> usize {
-    let (mut i, mut j, mut n) = (0, 0, length - 1);
-    loop {
-        if j > n {
-            return j;
-        }
-        if array[j] < pivot {
-            array.swap(i, j);
-            i += 1;
-            j += 1;
-        } else if array[j] > pivot {
-            array.swap(j, n);
-            n -= 1;
-        } else {
-            j += 1;
+/// 3-way (Dutch national flag) partition around `pivot`.
+///
+/// Returns `(q, r)` such that `array[..q] < pivot`, `array[q..r] == pivot` and `array[r..] > pivot`
+pub fn partition<T: Ord>(array: &mut [T], pivot: &T) -> (usize, usize) {
+    partition_by(array, pivot, T::cmp)
+}
+
+/// 3-way partition with a comparator
+pub fn partition_by<T, F: FnMut(&T, &T) -> Ordering>(
+    array: &mut [T],
+    pivot: &T,
+    mut compare: F,
+) -> (usize, usize) {
+    let (mut i, mut j, mut n) = (0, 0, array.len());
+    while j < n {
+        match compare(&array[j], pivot) {
+            Ordering::Less => {
+                array.swap(i, j);
+                i += 1;
+                j += 1;
+            }
+            Ordering::Greater => {
+                n -= 1;
+                array.swap(j, n);
+            }
+            Ordering::Equal => j += 1,
         }
     }
+    (i, n)
+}
+
+/// 3-way partition by a key extracted from each element
+pub fn partition_by_key<T, K: Ord, F: FnMut(&T) -> K>(
+    array: &mut [T],
+    pivot: &T,
+    mut key: F,
+) -> (usize, usize) {
+    let pivot_key = key(pivot);
+    partition_by(array, pivot, |a, _| key(a).cmp(&pivot_key))
 }
 
 #[cfg(test)]
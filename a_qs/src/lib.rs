@@ -1,7 +1,11 @@
 use anyhow::Error;
 use rand::{thread_rng, Rng};
 
-use a_med::partition;
+use a_hs::heap_sort_by;
+use a_ins::insertion_sort_by;
+use a_med::partition_by;
+use a_par3::partition_by as three_way_partition_by;
+use std::cmp::Ordering;
 use std::path::Path;
 use utility::io::Parseable;
 
@@ -19,19 +23,79 @@ pub fn rosalind_qs(filename: &Path) -> Result<Vec<isize>, Error> {
     Ok(array)
 }
 
-fn quicksort(array: &mut [isize]) {
-    let mut pivot_index = thread_rng().gen_range(0..array.len());
-    pivot_index = partition(array, 0, array.len() - 1, pivot_index);
-    if pivot_index == 0 {
-        pivot_index += 1;
+/// Slices at most this long are finished off with insertion sort in `introsort`
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+/// Randomized quicksort (not stable)
+pub fn quicksort<T: Ord>(array: &mut [T]) {
+    quicksort_by(array, T::cmp)
+}
+
+/// Randomized quicksort with a comparator
+pub fn quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], mut compare: F) {
+    quicksort_recurse(array, &mut compare, &mut thread_rng())
+}
+
+/// Randomized quicksort by a key extracted from each element
+pub fn quicksort_by_key<T, K: Ord, F: FnMut(&T) -> K>(array: &mut [T], mut key: F) {
+    quicksort_by(array, |a, b| key(a).cmp(&key(b)))
+}
+
+fn quicksort_recurse<T, F: FnMut(&T, &T) -> Ordering, R: Rng>(
+    array: &mut [T],
+    compare: &mut F,
+    rng: &mut R,
+) {
+    if array.len() <= 1 {
+        return;
     }
+    let pivot_index = rng.gen_range(0..array.len());
+    let pivot_index = partition_by(array, 0, array.len() - 1, pivot_index, |a, b| compare(a, b));
     let (left_array, right_array) = array.split_at_mut(pivot_index);
-    if left_array.len() > 1 {
-        quicksort(left_array);
+    quicksort_recurse(left_array, compare, rng);
+    quicksort_recurse(&mut right_array[1..], compare, rng);
+}
+
+/// Introsort: randomized quicksort with 3-way partitioning, switching to heap sort
+/// when the recursion gets too deep and to insertion sort for short slices (not stable)
+pub fn introsort<T: Ord>(array: &mut [T]) {
+    introsort_by(array, T::cmp)
+}
+
+/// Introsort with a comparator
+pub fn introsort_by<T, F: FnMut(&T, &T) -> Ordering>(array: &mut [T], mut compare: F) {
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    introsort_recurse(array, depth_limit, &mut compare, &mut thread_rng())
+}
+
+/// Introsort by a key extracted from each element
+pub fn introsort_by_key<T, K: Ord, F: FnMut(&T) -> K>(array: &mut [T], mut key: F) {
+    introsort_by(array, |a, b| key(a).cmp(&key(b)))
+}
+
+fn introsort_recurse<T, F: FnMut(&T, &T) -> Ordering, R: Rng>(
+    array: &mut [T],
+    depth_limit: usize,
+    compare: &mut F,
+    rng: &mut R,
+) {
+    if array.len() <= INSERTION_SORT_THRESHOLD {
+        insertion_sort_by(array, |a, b| compare(a, b));
+        return;
     }
-    if right_array.len() > 1 {
-        quicksort(right_array);
+    if depth_limit == 0 {
+        heap_sort_by(array, |a, b| compare(a, b));
+        return;
     }
+    let pivot_index = rng.gen_range(0..array.len());
+    array.swap(0, pivot_index);
+    let (pivot, rest) = array.split_first_mut().unwrap();
+    let (less_end, greater_start) = three_way_partition_by(rest, pivot, |a, b| compare(a, b));
+    // move the pivot from the front to the end of the smaller elements
+    array.swap(0, less_end);
+    let (left_array, right_array) = array.split_at_mut(greater_start + 1);
+    introsort_recurse(&mut left_array[..less_end], depth_limit - 1, compare, rng);
+    introsort_recurse(right_array, depth_limit - 1, compare, rng);
 }
 
 #[cfg(test)]
@@ -45,4 +109,19 @@ mod tests {
         assert!((1..array.len()).all(|i| array[i - 1] <= array[i]));
         Ok(())
     }
+
+    #[test]
+    fn qs_introsort() {
+        let mut array: Vec<isize> = (0..1000).map(|i| (i * 7919) % 101 - 50).collect();
+        let mut expected = array.clone();
+        expected.sort_unstable();
+        introsort(&mut array);
+        assert_eq!(array, expected);
+        let mut words = vec!["kiwi", "fig", "banana", "apple", "date"];
+        introsort_by_key(&mut words, |w| w.len());
+        assert!((1..words.len()).all(|i| words[i - 1].len() <= words[i].len()));
+        let mut array = vec![3, 1, 2];
+        quicksort_by(&mut array, |a, b| b.cmp(a));
+        assert_eq!(array, vec![3, 2, 1]);
+    }
 }
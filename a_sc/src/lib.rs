@@ -1,7 +1,6 @@
 use anyhow::Error;
-use itertools::Itertools;
 
-use a_ts::TopologicalSort;
+use a_scc::StronglyConnected;
 use std::path::Path;
 
 /// Semi-Connected Graph
@@ -19,7 +18,7 @@ pub fn rosalind_sc(filename: &Path) -> Result<Vec<isize>, Error> {
     let num_sections = lines.next().unwrap().parse::<usize>()?;
     let mut output = Vec::with_capacity(num_sections);
     for _ in 0..num_sections {
-        let graph = utility::graph::IntegerGraph::from_edge_list(&mut lines, true, true)?;
+        let graph = utility::graph::IntegerGraph::from_edge_list(&mut lines, true, false)?;
         if graph.is_semi_connected() {
             output.push(1);
         } else {
@@ -35,14 +34,7 @@ pub trait SemiConnectedCheck {
 }
 impl SemiConnectedCheck for utility::graph::IntegerGraph {
     fn is_semi_connected(&self) -> bool {
-        for (node_1, node_2) in self.get_topological_sort().into_iter().tuple_windows() {
-            if let Some(edges) = self.adjacency_list.get(&node_1) {
-                if !edges.contains(&node_2) {
-                    return false;
-                }
-            }
-        }
-        true
+        self.get_strongly_connected_components().is_semi_connected()
     }
 }
 
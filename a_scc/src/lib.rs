@@ -1,6 +1,9 @@
+use std::collections::{btree_map::BTreeMap, BTreeSet};
+
 use anyhow::Error;
 
 use std::path::Path;
+use utility::graph::IntegerGraph;
 
 /// Strongly Connected Components
 ///
@@ -13,17 +16,201 @@ pub fn rosalind_scc(filename: &Path) -> Result<usize, Error> {
         .split('\n')
         .filter(|s| !s.trim().is_empty())
         .map(|s| s.to_owned());
-    let mut graph = utility::graph::IntegerGraph::from_edge_list(&mut lines, true, false)?;
-    let graph_reverse = graph.get_reverse_graph(true);
-    let mut node_order = graph_reverse
-        .postvisit
-        .into_iter()
-        .enumerate()
-        .collect::<Vec<_>>();
-    node_order.sort_by(|a, b| b.1.cmp(&a.1));
-    graph.run_dfs_given_node_order(&node_order.into_iter().map(|(i, _)| i).collect::<Vec<_>>());
-    println!("{}", graph.num_connected_components);
-    Ok(graph.num_connected_components)
+    let graph = IntegerGraph::from_edge_list(&mut lines, true, false)?;
+    let num_components = graph.get_strongly_connected_components().num_components();
+    println!("{}", num_components);
+    Ok(num_components)
+}
+
+/// Strongly connected components of a directed graph, numbered in topological order
+/// of the condensation (i.e. no edge goes from a component to a lower-numbered one)
+#[derive(Debug, Clone)]
+pub struct StronglyConnectedComponents {
+    /// Component index of each node
+    pub node_to_component: BTreeMap<usize, usize>,
+    /// Nodes in each component
+    pub components: Vec<Vec<usize>>,
+    /// DAG with one node per component, and an edge between two components
+    /// wherever the original graph has an edge between their members
+    pub condensation: IntegerGraph,
+}
+
+impl StronglyConnectedComponents {
+    pub fn num_components(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn component(&self, node: usize) -> usize {
+        self.node_to_component[&node]
+    }
+
+    pub fn same_component(&self, node_1: usize, node_2: usize) -> bool {
+        self.component(node_1) == self.component(node_2)
+    }
+
+    /// A graph is semi-connected if for every pair of nodes there is a path from one to the other,
+    /// i.e. if the condensation has a Hamiltonian path through its topological order
+    pub fn is_semi_connected(&self) -> bool {
+        (1..self.num_components()).all(|component| {
+            self.condensation
+                .adjacency_list
+                .get(&(component - 1))
+                .is_some_and(|edge_list| edge_list.contains(&component))
+        })
+    }
+
+    /// Transitive closure of the condensation, for answering reachability queries on the original graph
+    pub fn get_reachability(&self) -> Reachability {
+        let num_components = self.num_components();
+        let num_words = num_components.div_ceil(64);
+        let mut reachable = vec![vec![0u64; num_words]; num_components];
+        for component in (0..num_components).rev() {
+            reachable[component][component / 64] |= 1 << (component % 64);
+            if let Some(edge_list) = self.condensation.adjacency_list.get(&component) {
+                for next_component in edge_list {
+                    // components are in topological order so next_component is already complete
+                    let (current, next) = reachable.split_at_mut(*next_component);
+                    for (word, next_word) in current[component].iter_mut().zip(&next[0]) {
+                        *word |= next_word;
+                    }
+                }
+            }
+        }
+        Reachability {
+            node_to_component: self.node_to_component.clone(),
+            reachable,
+        }
+    }
+}
+
+/// Reachability between nodes of a directed graph, stored as a bitset per strongly connected component
+#[derive(Debug, Clone)]
+pub struct Reachability {
+    node_to_component: BTreeMap<usize, usize>,
+    reachable: Vec<Vec<u64>>,
+}
+
+impl Reachability {
+    /// Is there a path from component_1 to component_2
+    pub fn component_can_reach(&self, component_1: usize, component_2: usize) -> bool {
+        self.reachable[component_1][component_2 / 64] & (1 << (component_2 % 64)) != 0
+    }
+
+    /// Is there a path from node_1 to node_2
+    pub fn can_reach(&self, node_1: usize, node_2: usize) -> bool {
+        self.component_can_reach(
+            self.node_to_component[&node_1],
+            self.node_to_component[&node_2],
+        )
+    }
+}
+
+pub trait StronglyConnected {
+    /// Iterative Tarjan's algorithm
+    fn get_strongly_connected_components(&self) -> StronglyConnectedComponents;
+}
+
+impl StronglyConnected for IntegerGraph {
+    fn get_strongly_connected_components(&self) -> StronglyConnectedComponents {
+        let successors: Vec<Vec<usize>> = (0..self.num_nodes)
+            .map(|node_index| {
+                self.adjacency_list
+                    .get(&self.nodes[node_index])
+                    .map(|edge_list| edge_list.iter().map(|n| self.node_to_index[n]).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let mut index = vec![None; self.num_nodes];
+        let mut lowlink = vec![0; self.num_nodes];
+        let mut on_stack = vec![false; self.num_nodes];
+        let mut stack = Vec::new();
+        let mut component_of = vec![0; self.num_nodes];
+        let mut num_components = 0;
+        let mut clock = 0;
+        // (node index, position of next edge to follow) for each node on the DFS path
+        let mut call_stack: Vec<(usize, usize)> = Vec::new();
+        for root in 0..self.num_nodes {
+            if index[root].is_some() {
+                continue;
+            }
+            index[root] = Some(clock);
+            lowlink[root] = clock;
+            clock += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            call_stack.push((root, 0));
+            while let Some(&(node, edge_position)) = call_stack.last() {
+                if edge_position < successors[node].len() {
+                    call_stack.last_mut().unwrap().1 += 1;
+                    let next_node = successors[node][edge_position];
+                    match index[next_node] {
+                        None => {
+                            index[next_node] = Some(clock);
+                            lowlink[next_node] = clock;
+                            clock += 1;
+                            stack.push(next_node);
+                            on_stack[next_node] = true;
+                            call_stack.push((next_node, 0));
+                        }
+                        Some(next_index) if on_stack[next_node] => {
+                            lowlink[node] = lowlink[node].min(next_index);
+                        }
+                        Some(_) => (),
+                    }
+                } else {
+                    call_stack.pop();
+                    if let Some(&(parent, _)) = call_stack.last() {
+                        lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                    }
+                    if Some(lowlink[node]) == index[node] {
+                        while let Some(member) = stack.pop() {
+                            on_stack[member] = false;
+                            component_of[member] = num_components;
+                            if member == node {
+                                break;
+                            }
+                        }
+                        num_components += 1;
+                    }
+                }
+            }
+        }
+        // Tarjan's algorithm finds components in reverse topological order
+        for component in component_of.iter_mut() {
+            *component = num_components - 1 - *component;
+        }
+        let mut components = vec![Vec::new(); num_components];
+        let mut condensation_edges = vec![BTreeSet::new(); num_components];
+        for node_index in 0..self.num_nodes {
+            let component = component_of[node_index];
+            components[component].push(self.nodes[node_index]);
+            for next_index in &successors[node_index] {
+                if component_of[*next_index] != component {
+                    condensation_edges[component].insert(component_of[*next_index]);
+                }
+            }
+        }
+        let condensation_adjacency_list = condensation_edges
+            .into_iter()
+            .enumerate()
+            .filter(|(_, edges)| !edges.is_empty())
+            .map(|(component, edges)| (component, edges.into_iter().collect()))
+            .collect();
+        StronglyConnectedComponents {
+            node_to_component: self
+                .nodes
+                .iter()
+                .zip(component_of)
+                .map(|(node, component)| (*node, component))
+                .collect(),
+            components,
+            condensation: IntegerGraph::new(
+                condensation_adjacency_list,
+                (0..num_components).collect(),
+                false,
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -37,4 +224,31 @@ mod tests {
         assert_eq!(rosalind_scc(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn scc_condensation() {
+        let adjacency_list = vec![
+            (1, vec![2]),
+            (2, vec![3]),
+            (3, vec![1, 4]),
+            (4, vec![5]),
+            (5, vec![4]),
+            (6, vec![5]),
+        ]
+        .into_iter()
+        .collect();
+        let graph = IntegerGraph::new(adjacency_list, (1..=6).collect(), false);
+        let scc = graph.get_strongly_connected_components();
+        assert_eq!(scc.num_components(), 3);
+        assert!(scc.same_component(1, 3));
+        assert!(scc.same_component(4, 5));
+        assert!(scc.component(1) < scc.component(4));
+        assert!(scc.component(6) < scc.component(4));
+        assert!(!scc.is_semi_connected());
+        let reachability = scc.get_reachability();
+        assert!(reachability.can_reach(2, 5));
+        assert!(reachability.can_reach(6, 4));
+        assert!(!reachability.can_reach(4, 1));
+        assert!(!reachability.can_reach(6, 1));
+    }
 }
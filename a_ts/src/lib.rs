@@ -1,4 +1,6 @@
 use anyhow::Error;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
 use std::path::Path;
 
 /// Given: A simple directed acyclic graph with n≤103 vertices in the edge list format.
@@ -16,15 +18,281 @@ pub fn rosalind_ts(filename: &Path) -> Result<Vec<usize>, Error> {
     Ok(output)
 }
 
+/// Tie-breaking rule for Kahn's algorithm when several nodes have no remaining incoming edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KahnOrder {
+    /// Nodes are taken in the order they become available
+    Fifo,
+    /// Lexicographically smallest topological ordering
+    Smallest,
+    /// Lexicographically largest topological ordering
+    Largest,
+}
+
 pub trait TopologicalSort {
+    /// DFS-based topological sort (by decreasing postvisit number)
     fn get_topological_sort(&self) -> Vec<usize>;
+    /// Number of incoming edges for each node index
+    fn get_in_degrees(&self) -> Vec<usize>;
+    /// Kahn's algorithm with the given tie-breaking rule.
+    ///
+    /// Returns `Err` with the nodes of a cycle (in edge order, starting from its smallest node)
+    /// if the graph is not a DAG
+    fn get_topological_sort_kahn(&self, order: KahnOrder) -> Result<Vec<usize>, Vec<usize>>;
+    /// Lazily iterate over every topological ordering, in lexicographic order of node indices
+    fn all_topological_sorts(&self) -> AllTopologicalSorts;
+    /// Number of topological orderings (0 if the graph has a cycle).
+    ///
+    /// Uses a DP over subsets of nodes, so returns `None` for graphs with more than
+    /// `MAX_COUNT_NODES` nodes
+    fn count_topological_sorts(&self) -> Option<u128>;
 }
+
+/// Maximum number of nodes for which `count_topological_sorts` is attempted
+/// (its table of 2^n counts then takes at most 16 MiB)
+pub const MAX_COUNT_NODES: usize = 20;
+
 impl TopologicalSort for utility::graph::IntegerGraph {
     fn get_topological_sort(&self) -> Vec<usize> {
         let mut postvisit = self.postvisit.iter().enumerate().collect::<Vec<_>>();
         postvisit.sort_by(|a, b| b.1.cmp(a.1));
         postvisit.into_iter().map(|(i, _)| self.nodes[i]).collect()
     }
+
+    fn get_in_degrees(&self) -> Vec<usize> {
+        let mut in_degrees = vec![0; self.num_nodes];
+        for edge_list in self.adjacency_list.values() {
+            for node in edge_list {
+                in_degrees[self.node_to_index[node]] += 1;
+            }
+        }
+        in_degrees
+    }
+
+    fn get_topological_sort_kahn(&self, order: KahnOrder) -> Result<Vec<usize>, Vec<usize>> {
+        let mut in_degrees = self.get_in_degrees();
+        let mut candidates = KahnCandidates::new(order);
+        for (node_index, in_degree) in in_degrees.iter().enumerate() {
+            if *in_degree == 0 {
+                candidates.push(self.nodes[node_index]);
+            }
+        }
+        let mut sorted = Vec::with_capacity(self.num_nodes);
+        while let Some(node) = candidates.pop() {
+            if let Some(edge_list) = self.adjacency_list.get(&node) {
+                for next_node in edge_list {
+                    let next_index = self.node_to_index[next_node];
+                    in_degrees[next_index] -= 1;
+                    if in_degrees[next_index] == 0 {
+                        candidates.push(*next_node);
+                    }
+                }
+            }
+            sorted.push(node);
+        }
+        if sorted.len() == self.num_nodes {
+            Ok(sorted)
+        } else {
+            Err(self.find_cycle(&in_degrees))
+        }
+    }
+
+    fn all_topological_sorts(&self) -> AllTopologicalSorts {
+        AllTopologicalSorts::new(self)
+    }
+
+    fn count_topological_sorts(&self) -> Option<u128> {
+        if self.num_nodes > MAX_COUNT_NODES {
+            return None;
+        }
+        let mut predecessors = vec![0usize; self.num_nodes];
+        for (node, edge_list) in &self.adjacency_list {
+            let node_index = self.node_to_index[node];
+            for next_node in edge_list {
+                predecessors[self.node_to_index[next_node]] |= 1 << node_index;
+            }
+        }
+        let mut counts = vec![0u128; 1 << self.num_nodes];
+        counts[0] = 1;
+        for mask in 0..counts.len() {
+            if counts[mask] == 0 {
+                continue;
+            }
+            for (node_index, predecessor_mask) in predecessors.iter().enumerate() {
+                if mask & (1 << node_index) == 0 && predecessor_mask & !mask == 0 {
+                    counts[mask | (1 << node_index)] += counts[mask];
+                }
+            }
+        }
+        Some(counts[counts.len() - 1])
+    }
+}
+
+trait FindCycle {
+    fn find_cycle(&self, in_degrees: &[usize]) -> Vec<usize>;
+}
+
+impl FindCycle for utility::graph::IntegerGraph {
+    /// Every node left with a non-zero in-degree after Kahn's algorithm has a predecessor
+    /// that was also left over, so walking predecessors must eventually revisit a node.
+    fn find_cycle(&self, in_degrees: &[usize]) -> Vec<usize> {
+        let mut predecessor = vec![None; self.num_nodes];
+        for (node, edge_list) in &self.adjacency_list {
+            let node_index = self.node_to_index[node];
+            if in_degrees[node_index] == 0 {
+                continue;
+            }
+            for next_node in edge_list {
+                let next_index = self.node_to_index[next_node];
+                if in_degrees[next_index] > 0 {
+                    predecessor[next_index] = Some(node_index);
+                }
+            }
+        }
+        let start = match in_degrees.iter().position(|d| *d > 0) {
+            Some(start) => start,
+            None => return Vec::new(),
+        };
+        let mut seen = vec![false; self.num_nodes];
+        let mut node_index = start;
+        while !seen[node_index] {
+            seen[node_index] = true;
+            node_index =
+                predecessor[node_index].expect("leftover node without leftover predecessor");
+        }
+        let mut cycle = vec![self.nodes[node_index]];
+        let mut current = predecessor[node_index].unwrap();
+        while current != node_index {
+            cycle.push(self.nodes[current]);
+            current = predecessor[current].unwrap();
+        }
+        cycle.reverse();
+        let min_position = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+        cycle.rotate_left(min_position);
+        cycle
+    }
+}
+
+enum KahnCandidates {
+    Fifo(VecDeque<usize>),
+    Smallest(BinaryHeap<Reverse<usize>>),
+    Largest(BinaryHeap<usize>),
+}
+
+impl KahnCandidates {
+    fn new(order: KahnOrder) -> Self {
+        match order {
+            KahnOrder::Fifo => KahnCandidates::Fifo(VecDeque::new()),
+            KahnOrder::Smallest => KahnCandidates::Smallest(BinaryHeap::new()),
+            KahnOrder::Largest => KahnCandidates::Largest(BinaryHeap::new()),
+        }
+    }
+
+    fn push(&mut self, node: usize) {
+        match self {
+            KahnCandidates::Fifo(queue) => queue.push_back(node),
+            KahnCandidates::Smallest(heap) => heap.push(Reverse(node)),
+            KahnCandidates::Largest(heap) => heap.push(node),
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        match self {
+            KahnCandidates::Fifo(queue) => queue.pop_front(),
+            KahnCandidates::Smallest(heap) => heap.pop().map(|Reverse(node)| node),
+            KahnCandidates::Largest(heap) => heap.pop(),
+        }
+    }
+}
+
+/// Iterator over all topological orderings of a graph, found by backtracking over
+/// the choice of zero in-degree node at each position
+pub struct AllTopologicalSorts {
+    nodes: Vec<usize>,
+    successors: Vec<Vec<usize>>,
+    in_degrees: Vec<usize>,
+    used: Vec<bool>,
+    order: Vec<usize>,
+    /// next node index to try at each depth
+    cursor: Vec<usize>,
+    done: bool,
+}
+
+impl AllTopologicalSorts {
+    fn new(graph: &utility::graph::IntegerGraph) -> Self {
+        let successors = (0..graph.num_nodes)
+            .map(|node_index| {
+                graph
+                    .adjacency_list
+                    .get(&graph.nodes[node_index])
+                    .map(|edge_list| edge_list.iter().map(|n| graph.node_to_index[n]).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+        AllTopologicalSorts {
+            nodes: graph.nodes.clone(),
+            successors,
+            in_degrees: graph.get_in_degrees(),
+            used: vec![false; graph.num_nodes],
+            order: Vec::with_capacity(graph.num_nodes),
+            cursor: vec![0; graph.num_nodes + 1],
+            done: false,
+        }
+    }
+
+    fn choose(&mut self, node_index: usize) {
+        self.used[node_index] = true;
+        for next_index in &self.successors[node_index] {
+            self.in_degrees[*next_index] -= 1;
+        }
+        self.order.push(node_index);
+        self.cursor[self.order.len()] = 0;
+    }
+
+    fn unchoose(&mut self) -> Option<usize> {
+        let node_index = self.order.pop()?;
+        self.used[node_index] = false;
+        for next_index in &self.successors[node_index] {
+            self.in_degrees[*next_index] += 1;
+        }
+        Some(node_index)
+    }
+}
+
+impl Iterator for AllTopologicalSorts {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.nodes.is_empty() {
+            self.done = true;
+            return Some(Vec::new());
+        }
+        loop {
+            let depth = self.order.len();
+            if depth == self.nodes.len() {
+                let ordering = self.order.iter().map(|i| self.nodes[*i]).collect();
+                self.unchoose();
+                return Some(ordering);
+            }
+            let next_index = (self.cursor[depth]..self.nodes.len())
+                .find(|i| !self.used[*i] && self.in_degrees[*i] == 0);
+            match next_index {
+                Some(node_index) => {
+                    self.cursor[depth] = node_index + 1;
+                    self.choose(node_index);
+                }
+                None => {
+                    if self.unchoose().is_none() {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -40,4 +308,41 @@ mod tests {
         assert_eq!(rosalind_ts(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn ts_kahn() {
+        let adjacency_list = vec![(1, vec![3]), (2, vec![3, 4]), (3, vec![5]), (4, vec![5])]
+            .into_iter()
+            .collect();
+        let graph = utility::graph::IntegerGraph::new(adjacency_list, (1..=5).collect(), true);
+        assert_eq!(
+            graph.get_topological_sort_kahn(KahnOrder::Smallest),
+            Ok(vec![1, 2, 3, 4, 5])
+        );
+        assert_eq!(
+            graph.get_topological_sort_kahn(KahnOrder::Largest),
+            Ok(vec![2, 4, 1, 3, 5])
+        );
+        let orderings: Vec<_> = graph.all_topological_sorts().collect();
+        assert_eq!(orderings.len(), 5);
+        assert_eq!(graph.count_topological_sorts(), Some(5));
+
+        let adjacency_list = vec![(1, vec![2]), (2, vec![3]), (3, vec![4]), (4, vec![2])]
+            .into_iter()
+            .collect();
+        let graph = utility::graph::IntegerGraph::new(adjacency_list, (1..=4).collect(), true);
+        assert_eq!(
+            graph.get_topological_sort_kahn(KahnOrder::Fifo),
+            Err(vec![2, 3, 4])
+        );
+        assert_eq!(graph.all_topological_sorts().next(), None);
+        assert_eq!(graph.count_topological_sorts(), Some(0));
+
+        let graph = utility::graph::IntegerGraph::new(
+            std::collections::BTreeMap::new(),
+            (1..=MAX_COUNT_NODES + 1).collect(),
+            true,
+        );
+        assert_eq!(graph.count_topological_sorts(), None);
+    }
 }
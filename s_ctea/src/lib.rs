@@ -0,0 +1,33 @@
+use anyhow::Error;
+
+use s_edit::get_edit_distances;
+use s_edta::count_optimal_alignments;
+use std::path::Path;
+
+/// Counting Optimal Alignments
+///
+/// Given: Two protein strings s and t in FASTA format, each of length at most 1000 aa.
+///
+/// Return: The total number of optimal alignments of s and t with respect to edit alignment
+/// score, modulo 134,217,727 (2^27-1).
+pub fn rosalind_ctea(filename: &Path) -> Result<u64, Error> {
+    let (headers, sequences) = utility::io::read_fasta_file_and_headers(filename)?;
+    let (string_1, string_2) = (&sequences[&headers[0]], &sequences[&headers[1]]);
+    let distances = get_edit_distances(string_1, string_2);
+    let result = count_optimal_alignments(string_1, string_2, &distances, 134_217_727);
+    println!("{}", result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctea() -> Result<(), Error> {
+        let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_ctea")?;
+        let output = utility::io::input_from_file(&output_file)?.parse::<u64>()?;
+        assert_eq!(rosalind_ctea(&input_file)?, output);
+        Ok(())
+    }
+}
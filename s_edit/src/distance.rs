@@ -0,0 +1,204 @@
+//! Edit distance without filling the whole dynamic programming matrix.
+//!
+//! Myers' bit-parallel algorithm packs the vertical differences of a column of the matrix into
+//! 64-bit words and updates a whole word per text character, in O(⌈m/64⌉·n).
+//! Ukkonen's banded algorithm only fills the cells within `max_distance` of the diagonal,
+//! in O(max_distance·n), which is enough to decide whether two strings are that close.
+const WORD_SIZE: usize = 64;
+
+/// Where a pattern matches best in a longer text, with free gaps at the ends of the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemiGlobalHit {
+    pub distance: usize,
+    /// `text[start..end]` is (one of) the closest substrings to the pattern
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Bit-parallel state of a pattern split into 64-character blocks
+struct BitVectors {
+    /// `equalities[c * blocks + b]`: positions of block b of the pattern holding c
+    equalities: Vec<u64>,
+    blocks: usize,
+    /// bit of the last pattern character in the last block
+    last_bit: u64,
+}
+
+impl BitVectors {
+    fn new(pattern: &[u8]) -> Self {
+        let blocks = pattern.len().div_ceil(WORD_SIZE).max(1);
+        let mut equalities = vec![0; 256 * blocks];
+        for (i, c) in pattern.iter().enumerate() {
+            equalities[*c as usize * blocks + i / WORD_SIZE] |= 1 << (i % WORD_SIZE);
+        }
+        BitVectors {
+            equalities,
+            blocks,
+            last_bit: 1 << ((pattern.len().max(1) - 1) % WORD_SIZE),
+        }
+    }
+
+    /// Score of the last pattern row at every column of the text, starting from the empty
+    /// prefix. With `global`, the first row grows by one per column (a full alignment of the
+    /// text), otherwise it is all zeros (the pattern may start anywhere in the text).
+    fn last_row(&self, pattern_length: usize, text: &[u8], global: bool) -> Vec<usize> {
+        let blocks = self.blocks;
+        // vertical differences of the current column: +1 (positive) or -1 (negative)
+        let mut positive = vec![!0u64; blocks];
+        let mut negative = vec![0u64; blocks];
+        let mut score = pattern_length;
+        let mut scores = Vec::with_capacity(text.len() + 1);
+        scores.push(score);
+        for c in text {
+            // horizontal difference entering the top of the next block
+            let mut carry: i8 = if global { 1 } else { 0 };
+            for block in 0..blocks {
+                let high_bit = if block == blocks - 1 {
+                    self.last_bit
+                } else {
+                    1 << (WORD_SIZE - 1)
+                };
+                let (pv, mv) = (positive[block], negative[block]);
+                let mut eq = self.equalities[*c as usize * blocks + block];
+                let xv = eq | mv;
+                if carry < 0 {
+                    eq |= 1;
+                }
+                let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+                let mut ph = mv | !(xh | pv);
+                let mut mh = pv & xh;
+                let carry_out = if ph & high_bit != 0 {
+                    1
+                } else if mh & high_bit != 0 {
+                    -1
+                } else {
+                    0
+                };
+                ph <<= 1;
+                mh <<= 1;
+                if carry < 0 {
+                    mh |= 1;
+                } else if carry > 0 {
+                    ph |= 1;
+                }
+                positive[block] = mh | !(xv | ph);
+                negative[block] = ph & xv;
+                carry = carry_out;
+            }
+            score = (score as isize + carry as isize) as usize;
+            scores.push(score);
+        }
+        scores
+    }
+}
+
+/// Edit distance with Myers' bit-parallel algorithm
+pub fn myers_edit_distance(string_1: &[u8], string_2: &[u8]) -> usize {
+    // the shorter string is the one packed into words
+    let (pattern, text) = if string_1.len() <= string_2.len() {
+        (string_1, string_2)
+    } else {
+        (string_2, string_1)
+    };
+    if pattern.is_empty() {
+        return text.len();
+    }
+    let bit_vectors = BitVectors::new(pattern);
+    *bit_vectors
+        .last_row(pattern.len(), text, true)
+        .last()
+        .unwrap()
+}
+
+/// Edit distance if it is at most `max_distance`, filling only the cells of the matrix within
+/// `max_distance` of the main diagonal (Ukkonen), None otherwise
+pub fn banded_edit_distance(
+    string_1: &[u8],
+    string_2: &[u8],
+    max_distance: usize,
+) -> Option<usize> {
+    let (m, n) = (string_1.len(), string_2.len());
+    if m.abs_diff(n) > max_distance {
+        return None;
+    }
+    let band = max_distance;
+    let outside = max_distance + 1;
+    // row i holds columns i - band ..= i + band, column j at index j + band - i
+    let width = 2 * band + 1;
+    let mut previous: Vec<usize> = (0..width)
+        .map(|index| match index.checked_sub(band) {
+            Some(j) if j <= n => j,
+            _ => outside,
+        })
+        .collect();
+    let mut current = vec![outside; width];
+    for i in 1..=m {
+        for index in 0..width {
+            let j = match (i + index).checked_sub(band) {
+                Some(j) if j <= n => j,
+                _ => {
+                    current[index] = outside;
+                    continue;
+                }
+            };
+            let mut distance = if j == 0 { i } else { outside };
+            if j > 0 {
+                // (i - 1, j - 1) has the same index in the previous row
+                let cost = (string_1[i - 1] != string_2[j - 1]) as usize;
+                distance = distance.min(previous[index] + cost);
+                if index > 0 {
+                    // (i, j - 1)
+                    distance = distance.min(current[index - 1] + 1);
+                }
+            }
+            if index + 1 < width {
+                // (i - 1, j)
+                distance = distance.min(previous[index + 1] + 1);
+            }
+            current[index] = distance.min(outside);
+        }
+        if current.iter().all(|distance| *distance > max_distance) {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let distance = previous[n + band - m];
+    if distance <= max_distance {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+/// Closest match of `pattern` anywhere in `text`: the edit distance between the pattern and
+/// the closest substring of the text (the one ending first, and then the shortest),
+/// found with Myers' algorithm in both directions
+pub fn semi_global_edit_distance(pattern: &[u8], text: &[u8]) -> SemiGlobalHit {
+    if pattern.is_empty() {
+        return SemiGlobalHit {
+            distance: 0,
+            start: 0,
+            end: 0,
+        };
+    }
+    let scores = BitVectors::new(pattern).last_row(pattern.len(), text, false);
+    let (end, distance) = scores
+        .iter()
+        .enumerate()
+        .min_by_key(|(end, distance)| (**distance, *end))
+        .map(|(end, distance)| (end, *distance))
+        .unwrap();
+    // reversed, the substrings ending at `end` become prefixes, aligned with a global first row
+    let reversed_pattern: Vec<_> = pattern.iter().rev().copied().collect();
+    let reversed_text: Vec<_> = text[..end].iter().rev().copied().collect();
+    let reversed_scores =
+        BitVectors::new(&reversed_pattern).last_row(pattern.len(), &reversed_text, true);
+    let length = (0..reversed_scores.len())
+        .find(|length| reversed_scores[*length] == distance)
+        .unwrap();
+    SemiGlobalHit {
+        distance,
+        start: end - length,
+        end,
+    }
+}
@@ -2,6 +2,12 @@ use anyhow::Error;
 use ndarray::Array2;
 use std::path::Path;
 
+pub use distance::{
+    banded_edit_distance, myers_edit_distance, semi_global_edit_distance, SemiGlobalHit,
+};
+
+pub mod distance;
+
 /// Edit Distance
 ///
 /// Given: Two protein strings s and t in FASTA format (each of length at most 1000 aa).
@@ -16,10 +22,12 @@ pub fn rosalind_edit(filename: &Path) -> Result<usize, Error> {
     Ok(result)
 }
 
+/// Edit distance with Myers' bit-parallel algorithm (see `get_edit_distances` for the full matrix)
 pub fn get_edit_distance(string_1: &str, string_2: &str) -> usize {
-    get_edit_distances(string_1, string_2)[(string_1.len(), string_2.len())]
+    myers_edit_distance(string_1.as_bytes(), string_2.as_bytes())
 }
 
+/// Full (m + 1) × (n + 1) matrix of edit distances between prefixes, for traceback
 pub fn get_edit_distances(string_1: &str, string_2: &str) -> Array2<usize> {
     let (string_1, string_2): (Vec<_>, Vec<_>) =
         (string_1.chars().collect(), string_2.chars().collect());
@@ -59,4 +67,97 @@ mod tests {
         );
         Ok(())
     }
+
+    /// A fresh string for each call, seeded from a counter
+    fn random_string(length: usize, alphabet: &[u8], seed: &mut u64) -> Vec<u8> {
+        *seed += 1;
+        utility::testing::random_sequence(length, alphabet, *seed).into_bytes()
+    }
+
+    #[test]
+    fn fast_edit_distances() {
+        let mut seed = 3;
+        for &(length_1, length_2) in &[
+            (0, 0),
+            (0, 5),
+            (7, 0),
+            (1, 1),
+            (10, 12),
+            (63, 64),
+            (64, 65),
+            (100, 70),
+            (130, 200),
+            (257, 250),
+        ] {
+            for alphabet in &[&b"AC"[..], b"ACGT", b"ACDEFGHIKLMNPQRSTVWY"] {
+                let string_1 = random_string(length_1, alphabet, &mut seed);
+                let mut string_2 = random_string(length_2, alphabet, &mut seed);
+                // a similar pair too, to test small distances
+                let mut similar = string_1.clone();
+                for i in (0..similar.len()).step_by(9) {
+                    similar[i] = alphabet[0];
+                }
+                similar.truncate(similar.len().saturating_sub(length_2 % 3));
+                for other in [&mut string_2, &mut similar] {
+                    let (a, b) = (
+                        std::str::from_utf8(&string_1).unwrap(),
+                        std::str::from_utf8(other).unwrap(),
+                    );
+                    let expected = get_edit_distances(a, b)[(a.len(), b.len())];
+                    assert_eq!(myers_edit_distance(&string_1, other), expected);
+                    assert_eq!(get_edit_distance(a, b), expected);
+                    for max_distance in [0, expected.saturating_sub(1), expected, expected + 3] {
+                        assert_eq!(
+                            banded_edit_distance(&string_1, other, max_distance),
+                            Some(expected).filter(|d| *d <= max_distance),
+                            "{} {} {}",
+                            a,
+                            b,
+                            max_distance
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn semi_global() {
+        let mut seed = 5;
+        for &(pattern_length, text_length) in &[(1, 10), (8, 30), (70, 300), (150, 400), (20, 5)] {
+            for alphabet in &[&b"ACGT"[..], b"AC"] {
+                let text = random_string(text_length, alphabet, &mut seed);
+                let mut pattern = random_string(pattern_length, alphabet, &mut seed);
+                if text_length > pattern_length {
+                    // mostly a substring of the text, with a few edits
+                    pattern = text[text_length / 3..text_length / 3 + pattern_length].to_vec();
+                    pattern[pattern_length / 2] = b'T';
+                    pattern.remove(0);
+                }
+                let hit = semi_global_edit_distance(&pattern, &text);
+                // dynamic programming with a first row of zeros, so that matches start anywhere
+                let mut row: Vec<usize> = vec![0; text.len() + 1];
+                for (i, p) in pattern.iter().enumerate() {
+                    let mut next = vec![i + 1; text.len() + 1];
+                    for j in 1..=text.len() {
+                        next[j] = (row[j - 1] + (*p != text[j - 1]) as usize)
+                            .min(row[j] + 1)
+                            .min(next[j - 1] + 1);
+                    }
+                    row = next;
+                }
+                let brute_force = row
+                    .iter()
+                    .enumerate()
+                    .map(|(end, distance)| (*distance, end))
+                    .min()
+                    .unwrap();
+                assert_eq!((hit.distance, hit.end), brute_force);
+                assert_eq!(
+                    myers_edit_distance(&pattern, &text[hit.start..hit.end]),
+                    hit.distance
+                );
+            }
+        }
+    }
 }
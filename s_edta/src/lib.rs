@@ -4,7 +4,6 @@ use ndarray::Array2;
 use s_edit::get_edit_distances;
 use std::path::Path;
 
-/// WIP
 /// Edit Distance Alignment
 ///
 /// Given: Two protein strings s and t in FASTA format (with each string having length at most 1000 aa).
@@ -12,50 +11,296 @@ use std::path::Path;
 /// Return: The edit distance dE(s,t) followed by two augmented strings s′ and t′
 /// representing an optimal alignment of s and t.
 pub fn rosalind_edta(filename: &Path) -> Result<(usize, String, String), Error> {
-    let fasta = utility::io::read_fasta_file(filename)?;
-    let sequences: Vec<String> = fasta.values().map(|x| x.to_owned()).collect();
-    let (string_1, string_2) = (&sequences[0], &sequences[1]);
+    let (headers, sequences) = utility::io::read_fasta_file_and_headers(filename)?;
+    let (string_1, string_2) = (&sequences[&headers[0]], &sequences[&headers[1]]);
     let distances = get_edit_distances(string_1, string_2);
-    let (aln_1, aln_2) = backtrack(string_1, string_2, &distances);
-    let edit_distance = distances[(string_1.len(), string_2.len())];
-    println!("{}\n{}\n{}", edit_distance, aln_1, aln_2);
-    Ok((edit_distance, aln_1, aln_2))
+    let alignment = edit_alignment(string_1, string_2, &distances);
+    let (aln_1, aln_2) = alignment.aligned_strings(string_1, string_2);
+    println!("{}\n{}\n{}", alignment.distance, aln_1, aln_2);
+    Ok((alignment.distance, aln_1, aln_2))
 }
 
-fn backtrack(string_1: &str, string_2: &str, distances: &Array2<usize>) -> (String, String) {
-    let (string_1, string_2): (Vec<_>, Vec<_>) =
-        (string_1.chars().collect(), string_2.chars().collect());
-    let (mut m, mut n) = (string_1.len(), string_2.len());
-    let (mut aln_1, mut aln_2) = (Vec::new(), Vec::new());
+/// Column of an alignment of string_1 (the reference) with string_2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditOperation {
+    Match,
+    Substitution,
+    /// character of string_2 against a gap
+    Insertion,
+    /// character of string_1 against a gap
+    Deletion,
+}
+
+impl EditOperation {
+    /// Extended CIGAR code
+    pub fn cigar_code(self) -> char {
+        match self {
+            EditOperation::Match => '=',
+            EditOperation::Substitution => 'X',
+            EditOperation::Insertion => 'I',
+            EditOperation::Deletion => 'D',
+        }
+    }
+
+    /// Number of characters consumed from (string_1, string_2)
+    fn steps(self) -> (usize, usize) {
+        match self {
+            EditOperation::Match | EditOperation::Substitution => (1, 1),
+            EditOperation::Insertion => (0, 1),
+            EditOperation::Deletion => (1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EditAlignment {
+    pub distance: usize,
+    /// Columns from left to right
+    pub operations: Vec<EditOperation>,
+}
+
+impl EditAlignment {
+    /// The two strings with '-' for gaps
+    pub fn aligned_strings(&self, string_1: &str, string_2: &str) -> (String, String) {
+        let (mut chars_1, mut chars_2) = (string_1.chars(), string_2.chars());
+        let (mut aln_1, mut aln_2) = (String::new(), String::new());
+        for operation in &self.operations {
+            let (step_1, step_2) = operation.steps();
+            aln_1.push(if step_1 == 1 {
+                chars_1.next().unwrap()
+            } else {
+                '-'
+            });
+            aln_2.push(if step_2 == 1 {
+                chars_2.next().unwrap()
+            } else {
+                '-'
+            });
+        }
+        (aln_1, aln_2)
+    }
+
+    /// Extended CIGAR string, e.g. 2=1D2=1X1I
+    pub fn cigar(&self) -> String {
+        let mut cigar = String::new();
+        let mut operations = self.operations.iter().peekable();
+        while let Some(operation) = operations.next() {
+            let mut length = 1;
+            while operations.next_if_eq(&operation).is_some() {
+                length += 1;
+            }
+            cigar.push_str(&format!("{}{}", length, operation.cigar_code()));
+        }
+        cigar
+    }
+}
+
+/// Traceback through the matrix of `s_edit::get_edit_distances`
+struct Traceback<'a> {
+    string_1: Vec<char>,
+    string_2: Vec<char>,
+    distances: &'a Array2<usize>,
+}
+
+impl<'a> Traceback<'a> {
+    fn new(string_1: &str, string_2: &str, distances: &'a Array2<usize>) -> Self {
+        Traceback {
+            string_1: string_1.chars().collect(),
+            string_2: string_2.chars().collect(),
+            distances,
+        }
+    }
 
-    while m != 0 && n != 0 {
-        let cost = if string_1[m - 1] == string_2[n - 1] {
-            0
+    /// Last columns of the optimal alignments of the prefixes of lengths i and j.
+    /// Ties are broken in this order: match or substitution, deletion, insertion.
+    fn last_operations(&self, i: usize, j: usize) -> impl Iterator<Item = EditOperation> + '_ {
+        let diagonal = if i > 0 && j > 0 {
+            Some(if self.string_1[i - 1] == self.string_2[j - 1] {
+                EditOperation::Match
+            } else {
+                EditOperation::Substitution
+            })
+        } else {
+            None
+        };
+        let deletion = if i > 0 {
+            Some(EditOperation::Deletion)
         } else {
-            1
+            None
         };
-        if distances[(m, n)] == distances[(m - 1, n - 1)] + cost {
-            aln_1.insert(0, string_1[m - 1]);
-            aln_2.insert(0, string_2[n - 1]);
-            m -= 1;
-            n -= 1;
-        } else if m > 0 && distances[(m, n)] == distances[(m - 1, n)] + 1 {
-            aln_1.insert(0, string_1[m - 1]);
-            aln_2.insert(0, '-');
-            m -= 1;
+        let insertion = if j > 0 {
+            Some(EditOperation::Insertion)
         } else {
-            aln_1.insert(0, '-');
-            aln_2.insert(0, string_2[n - 1]);
-            n -= 1;
+            None
+        };
+        diagonal
+            .into_iter()
+            .chain(deletion)
+            .chain(insertion)
+            .filter(move |operation| {
+                let (step_1, step_2) = operation.steps();
+                let cost = (*operation != EditOperation::Match) as usize;
+                self.distances[(i - step_1, j - step_2)] + cost == self.distances[(i, j)]
+            })
+    }
+}
+
+/// One optimal alignment, breaking ties deterministically (see `optimal_alignments`)
+pub fn edit_alignment(string_1: &str, string_2: &str, distances: &Array2<usize>) -> EditAlignment {
+    optimal_alignments(string_1, string_2, distances)
+        .next()
+        .unwrap()
+}
+
+/// Number of optimal alignments, i.e. of paths from (0, 0) to (m, n) using only moves that keep
+/// to the edit distance, modulo `modulus` (Rosalind CTEA). The modulus must be at most 2^63.
+pub fn count_optimal_alignments(
+    string_1: &str,
+    string_2: &str,
+    distances: &Array2<usize>,
+    modulus: u64,
+) -> u64 {
+    let traceback = Traceback::new(string_1, string_2, distances);
+    let (m, n) = (traceback.string_1.len(), traceback.string_2.len());
+    let mut previous: Vec<u64> = Vec::new();
+    let mut current: Vec<u64> = Vec::with_capacity(n + 1);
+    for i in 0..=m {
+        current.clear();
+        for j in 0..=n {
+            let count = if i == 0 && j == 0 {
+                1 % modulus
+            } else {
+                traceback
+                    .last_operations(i, j)
+                    .map(|operation| match operation.steps() {
+                        (1, 1) => previous[j - 1],
+                        (1, 0) => previous[j],
+                        _ => current[j - 1],
+                    })
+                    .fold(0, |total, count| (total + count) % modulus)
+            };
+            current.push(count);
         }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous.pop().unwrap()
+}
+
+/// Every optimal alignment, lazily. The first one prefers, from the end of the strings,
+/// a match or substitution over a deletion and a deletion over an insertion, and the rest
+/// follow in the same order of preference.
+pub fn optimal_alignments<'a>(
+    string_1: &str,
+    string_2: &str,
+    distances: &'a Array2<usize>,
+) -> OptimalAlignments<'a> {
+    let traceback = Traceback::new(string_1, string_2, distances);
+    let (m, n) = (traceback.string_1.len(), traceback.string_2.len());
+    let mut alignments = OptimalAlignments {
+        traceback,
+        distance: distances[(m, n)],
+        path: Vec::new(),
+        position: (m, n),
+        done: false,
+    };
+    alignments.descend();
+    alignments
+}
+
+/// Depth-first search of the traceback, backtracking to the most recent column with an
+/// alternative for each new alignment
+pub struct OptimalAlignments<'a> {
+    traceback: Traceback<'a>,
+    distance: usize,
+    /// columns chosen so far from the end, each with the untried alternatives at its cell
+    path: Vec<(EditOperation, Vec<EditOperation>)>,
+    /// cell reached by `path`
+    position: (usize, usize),
+    done: bool,
+}
+
+impl<'a> OptimalAlignments<'a> {
+    /// Follow the preferred columns from `position` back to (0, 0)
+    fn descend(&mut self) {
+        let (mut i, mut j) = self.position;
+        while (i, j) != (0, 0) {
+            let mut operations: Vec<_> = self.traceback.last_operations(i, j).collect();
+            operations.reverse();
+            let operation = operations.pop().unwrap();
+            let (step_1, step_2) = operation.steps();
+            i -= step_1;
+            j -= step_2;
+            self.path.push((operation, operations));
+        }
+        self.position = (0, 0);
+    }
+
+    /// Switch the deepest column with an untried alternative to it, false if there is none
+    fn backtrack(&mut self) -> bool {
+        while let Some((operation, mut alternatives)) = self.path.pop() {
+            let (step_1, step_2) = operation.steps();
+            let (i, j) = self.position;
+            self.position = (i + step_1, j + step_2);
+            if let Some(alternative) = alternatives.pop() {
+                let (step_1, step_2) = alternative.steps();
+                let (i, j) = self.position;
+                self.position = (i - step_1, j - step_2);
+                self.path.push((alternative, alternatives));
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<'a> Iterator for OptimalAlignments<'a> {
+    type Item = EditAlignment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let alignment = EditAlignment {
+            distance: self.distance,
+            operations: self
+                .path
+                .iter()
+                .rev()
+                .map(|(operation, _)| *operation)
+                .collect(),
+        };
+        if self.backtrack() {
+            self.descend();
+        } else {
+            self.done = true;
+        }
+        Some(alignment)
     }
-    (aln_1.into_iter().collect(), aln_2.into_iter().collect())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Whether two augmented strings align the strings with `distance` edits
+    fn is_alignment(
+        aln_1: &str,
+        aln_2: &str,
+        string_1: &str,
+        string_2: &str,
+        distance: usize,
+    ) -> bool {
+        aln_1.replace('-', "") == string_1
+            && aln_2.replace('-', "") == string_2
+            && aln_1.chars().count() == aln_2.chars().count()
+            && aln_1
+                .chars()
+                .zip(aln_2.chars())
+                .filter(|(a, b)| a != b)
+                .count()
+                == distance
+    }
+
     #[test]
     fn edta() -> Result<(), Error> {
         let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_edta")?;
@@ -66,8 +311,53 @@ mod tests {
             edit_distance,
             output_lines.next().unwrap().parse::<usize>()?
         );
-        assert_eq!(&aln_1, output_lines.next().unwrap());
-        assert_eq!(&aln_2, output_lines.next().unwrap());
+        // any optimal alignment of the input strings is accepted
+        let (headers, sequences) = utility::io::read_fasta_file_and_headers(&input_file)?;
+        assert!(is_alignment(
+            &aln_1,
+            &aln_2,
+            &sequences[&headers[0]],
+            &sequences[&headers[1]],
+            edit_distance
+        ));
         Ok(())
     }
+
+    #[test]
+    fn co_optimal_alignments() {
+        let (string_1, string_2) = ("PLEASANTLY", "MEANLY");
+        let distances = get_edit_distances(string_1, string_2);
+        let alignment = edit_alignment(string_1, string_2, &distances);
+        assert_eq!(alignment.distance, 5);
+        assert_eq!(
+            alignment.aligned_strings(string_1, string_2),
+            ("PLEASANTLY".into(), "-ME--AN-LY".into())
+        );
+        assert_eq!(alignment.cigar(), "1D1X1=2D2=1D2=");
+
+        let alignments: Vec<_> = optimal_alignments(string_1, string_2, &distances).collect();
+        assert_eq!(alignments[0], alignment);
+        assert_eq!(
+            alignments.len() as u64,
+            count_optimal_alignments(string_1, string_2, &distances, 1 << 63)
+        );
+        assert_eq!(alignments.len(), 4);
+        let distinct: std::collections::HashSet<_> = alignments.iter().collect();
+        assert_eq!(distinct.len(), alignments.len());
+        for alignment in &alignments {
+            let (aln_1, aln_2) = alignment.aligned_strings(string_1, string_2);
+            assert!(is_alignment(&aln_1, &aln_2, string_1, string_2, 5));
+        }
+
+        // empty strings and strings with many co-optimal alignments
+        let distances = get_edit_distances("", "ACG");
+        assert_eq!(edit_alignment("", "ACG", &distances).cigar(), "3I");
+        let distances = get_edit_distances("AAAA", "AA");
+        assert_eq!(
+            count_optimal_alignments("AAAA", "AA", &distances, 1 << 63),
+            6
+        );
+        assert_eq!(count_optimal_alignments("AAAA", "AA", &distances, 4), 2);
+        assert_eq!(optimal_alignments("AAAA", "AA", &distances).count(), 6);
+    }
 }
@@ -0,0 +1,416 @@
+//! k-mer counting for read sets and genomes that don't fit in memory as `String`-keyed maps.
+//!
+//! k-mers are packed into `Kmer`s and counted in a hash map. When the map outgrows
+//! `memory_budget`, it is sorted and spilled to a temporary partition file, and the partitions
+//! are merged with a heap at the end, adding up the counts of equal k-mers.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use a_hea::{build_max_heap_by, max_heapify_by};
+use anyhow::Error;
+
+use utility::errors::RosalindParseError;
+use utility::kmer::{Alphabet, Kmer};
+
+/// Approximate memory used by each entry of the count map
+const ENTRY_SIZE: usize = std::mem::size_of::<(Kmer, u64)>() + 8;
+
+/// Bytes per (k-mer, count) record in partition and count files
+const RECORD_SIZE: usize = 16;
+
+/// Start of the header of count files, followed by k and the strandedness as big-endian u32s
+const COUNTS_MAGIC: &[u8; 8] = b"KMERCNT1";
+
+/// Bytes in the header of count files
+const COUNTS_HEADER_SIZE: usize = 16;
+
+/// Used to give each partition file of this process a unique name
+static PARTITION_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Settings for `KmerCounter`
+#[derive(Debug, Clone)]
+pub struct KmerCounterConfig {
+    /// At most 32, so that k-mers fit in a u64
+    pub k: usize,
+    /// Count each k-mer together with its reverse complement, as reads come from either strand
+    pub canonical: bool,
+    /// k-mers seen fewer times are left out of `counts` (but not of the spectrum)
+    pub min_count: u64,
+    /// Approximate number of bytes the count map may use before it is spilled to disk
+    pub memory_budget: usize,
+    /// Directory for the temporary partition files
+    pub temp_dir: PathBuf,
+}
+
+impl Default for KmerCounterConfig {
+    fn default() -> Self {
+        KmerCounterConfig {
+            k: 21,
+            canonical: true,
+            min_count: 1,
+            memory_budget: 1024 * 1024 * 1024,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+/// Sorted partition files, removed when dropped
+struct Partitions {
+    files: Vec<PathBuf>,
+    /// files given by the caller, which are merged but not removed
+    external_files: Vec<PathBuf>,
+}
+
+impl Drop for Partitions {
+    fn drop(&mut self) {
+        for file in &self.files {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+fn write_record<W: Write>(writer: &mut W, kmer: Kmer, count: u64) -> io::Result<()> {
+    // big-endian, so that the bytes sort like the k-mers
+    writer.write_all(&kmer.value().to_be_bytes())?;
+    writer.write_all(&count.to_be_bytes())
+}
+
+fn read_record<R: Read>(reader: &mut R, k: usize) -> io::Result<Option<(Kmer, u64)>> {
+    let mut record = [0; RECORD_SIZE];
+    match reader.read_exact(&mut record) {
+        Ok(()) => {
+            let (value, count) = record.split_at(8);
+            Ok(Some((
+                Kmer::new(u64::from_be_bytes(value.try_into().unwrap()), k),
+                u64::from_be_bytes(count.try_into().unwrap()),
+            )))
+        }
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+fn write_counts_header<W: Write>(writer: &mut W, k: usize, canonical: bool) -> io::Result<()> {
+    writer.write_all(COUNTS_MAGIC)?;
+    writer.write_all(&(k as u32).to_be_bytes())?;
+    writer.write_all(&u32::from(canonical).to_be_bytes())
+}
+
+/// k and strandedness of a count file, or None if it doesn't start with a count file header
+fn read_counts_header<R: Read>(reader: &mut R) -> io::Result<Option<(usize, bool)>> {
+    let mut header = [0; COUNTS_HEADER_SIZE];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let (magic, fields) = header.split_at(COUNTS_MAGIC.len());
+    let (k, canonical) = fields.split_at(4);
+    let k = u32::from_be_bytes(k.try_into().unwrap()) as usize;
+    match (
+        magic == COUNTS_MAGIC,
+        u32::from_be_bytes(canonical.try_into().unwrap()),
+    ) {
+        (true, 0) => Ok(Some((k, false))),
+        (true, 1) => Ok(Some((k, true))),
+        _ => Ok(None),
+    }
+}
+
+pub struct KmerCounter {
+    config: KmerCounterConfig,
+    alphabet: Alphabet,
+    counts: HashMap<Kmer, u64>,
+    partitions: Partitions,
+}
+
+impl KmerCounter {
+    pub fn new(config: KmerCounterConfig) -> Self {
+        KmerCounter::with_alphabet(config, Alphabet::dna())
+    }
+
+    /// Counter of k-mers over another alphabet, which needs complements if `config.canonical`
+    pub fn with_alphabet(config: KmerCounterConfig, alphabet: Alphabet) -> Self {
+        assert!(
+            config.k > 0 && config.k <= alphabet.max_k::<u64>(),
+            "k-mer too long for a u64"
+        );
+        KmerCounter {
+            config,
+            alphabet,
+            counts: HashMap::new(),
+            partitions: Partitions {
+                files: Vec::new(),
+                external_files: Vec::new(),
+            },
+        }
+    }
+
+    /// Count the k-mers of each line of a sequence, carrying k-mers across lines.
+    /// k-mers with symbols outside the alphabet (e.g. N) are skipped.
+    fn add_lines<'a>(&mut self, lines: impl Iterator<Item = &'a [u8]>) -> Result<(), Error> {
+        let mut kmer = Kmer::new(0, self.config.k);
+        let mut valid_length = 0;
+        for line in lines {
+            for symbol in line {
+                match self.alphabet.encode(*symbol) {
+                    Some(code) => {
+                        kmer = kmer.push(code, &self.alphabet);
+                        valid_length += 1;
+                        if valid_length >= self.config.k {
+                            let kmer = if self.config.canonical {
+                                kmer.canonical(&self.alphabet)
+                            } else {
+                                kmer
+                            };
+                            *self.counts.entry(kmer).or_insert(0) += 1;
+                        }
+                    }
+                    None => valid_length = 0,
+                }
+            }
+            if self.counts.len() * ENTRY_SIZE >= self.config.memory_budget {
+                self.spill()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `count` occurrences of a k-mer
+    pub fn add_kmer(&mut self, kmer: Kmer, count: u64) -> Result<(), Error> {
+        let kmer = if self.config.canonical {
+            kmer.canonical(&self.alphabet)
+        } else {
+            kmer
+        };
+        *self.counts.entry(kmer).or_insert(0) += count;
+        if self.counts.len() * ENTRY_SIZE >= self.config.memory_budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Number of partition files spilled to disk so far
+    pub fn num_partitions(&self) -> usize {
+        self.partitions.files.len()
+    }
+
+    pub fn add_sequence(&mut self, sequence: &[u8]) -> Result<(), Error> {
+        self.add_lines(std::iter::once(sequence))
+    }
+
+    /// Count the k-mers of every sequence of a FASTA or FASTQ file, read one record at a time
+    pub fn add_file(&mut self, filename: &Path) -> Result<(), Error> {
+        let mut reader = BufReader::new(File::open(filename)?);
+        let mut line = Vec::new();
+        let mut sequence_lines: Vec<Vec<u8>> = Vec::new();
+        let mut is_fastq = None;
+        // number of FASTQ quality characters still to skip, which may start with '@'
+        let mut qualities_left: usize = 0;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return self.add_lines(sequence_lines.iter().map(|line| line.as_slice()));
+            }
+            while line.last().is_some_and(|c| c.is_ascii_whitespace()) {
+                line.pop();
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let is_fastq = *is_fastq.get_or_insert(line[0] == b'@');
+            if qualities_left > 0 {
+                qualities_left = qualities_left.saturating_sub(line.len());
+            } else if line[0] == if is_fastq { b'@' } else { b'>' } {
+                self.add_lines(sequence_lines.iter().map(|line| line.as_slice()))?;
+                sequence_lines.clear();
+            } else if is_fastq && line[0] == b'+' {
+                qualities_left = sequence_lines.iter().map(Vec::len).sum();
+            } else {
+                sequence_lines.push(line.clone());
+            }
+        }
+    }
+
+    /// Merge in the counts of a file written by `write_counts`, which must have been counted
+    /// with the same k and strandedness
+    pub fn add_counts_file(&mut self, filename: &Path) -> Result<(), Error> {
+        let mut reader = BufReader::new(File::open(filename)?);
+        let error = |message: String| {
+            RosalindParseError::InputFormatError(format!("{}: {}", filename.display(), message))
+        };
+        let (k, canonical) = read_counts_header(&mut reader)?
+            .ok_or_else(|| error(String::from("not a k-mer count file")))?;
+        if (k, canonical) != (self.config.k, self.config.canonical) {
+            return Err(error(format!(
+                "counted with k = {} and canonical = {}, not k = {} and canonical = {}",
+                k, canonical, self.config.k, self.config.canonical
+            ))
+            .into());
+        }
+        let length = fs::metadata(filename)?.len() as usize;
+        if !(length - COUNTS_HEADER_SIZE).is_multiple_of(RECORD_SIZE) {
+            return Err(error(String::from("truncated count record")).into());
+        }
+        self.partitions.external_files.push(filename.to_path_buf());
+        Ok(())
+    }
+
+    /// Write the in-memory counts to a sorted partition file
+    fn spill(&mut self) -> Result<(), Error> {
+        let mut counts: Vec<_> = self.counts.drain().collect();
+        counts.sort_unstable();
+        let file = self.config.temp_dir.join(format!(
+            "s_kmer_partition_{}_{}.tmp",
+            std::process::id(),
+            PARTITION_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        self.partitions.files.push(file.clone());
+        let mut writer = BufWriter::new(File::create(&file)?);
+        for (kmer, count) in counts {
+            write_record(&mut writer, kmer, count)?;
+        }
+        writer.flush()?;
+        self.counts.shrink_to_fit();
+        Ok(())
+    }
+
+    /// Visit every k-mer with its total count, in lexicographic order
+    pub fn for_each_count<F: FnMut(Kmer, u64)>(mut self, mut visit: F) -> Result<(), Error> {
+        if self.partitions.files.is_empty() && self.partitions.external_files.is_empty() {
+            let mut counts: Vec<_> = self.counts.drain().collect();
+            counts.sort_unstable();
+            for (kmer, count) in counts {
+                visit(kmer, count);
+            }
+            return Ok(());
+        }
+        if !self.counts.is_empty() {
+            self.spill()?;
+        }
+        let k = self.config.k;
+        let mut readers = self
+            .partitions
+            .files
+            .iter()
+            .map(|file| Ok(BufReader::new(File::open(file)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        for file in &self.partitions.external_files {
+            let mut reader = BufReader::new(File::open(file)?);
+            // checked by add_counts_file
+            read_counts_header(&mut reader)?;
+            readers.push(reader);
+        }
+        // the heap keeps the smallest k-mer at the root
+        let mut heap_compare =
+            |a: &(Kmer, u64, usize), b: &(Kmer, u64, usize)| -> Ordering { b.0.cmp(&a.0) };
+        let mut heap = Vec::with_capacity(readers.len());
+        for (index, reader) in readers.iter_mut().enumerate() {
+            if let Some((kmer, count)) = read_record(reader, k)? {
+                heap.push((kmer, count, index));
+            }
+        }
+        build_max_heap_by(&mut heap, &mut heap_compare);
+        let mut current: Option<(Kmer, u64)> = None;
+        while !heap.is_empty() {
+            let (kmer, count, index) = heap[0];
+            current = match current {
+                Some((current_kmer, total)) if current_kmer == kmer => Some((kmer, total + count)),
+                Some((current_kmer, total)) => {
+                    visit(current_kmer, total);
+                    Some((kmer, count))
+                }
+                None => Some((kmer, count)),
+            };
+            match read_record(&mut readers[index], k)? {
+                Some((kmer, count)) => heap[0] = (kmer, count, index),
+                None => {
+                    heap.swap_remove(0);
+                }
+            }
+            max_heapify_by(&mut heap, 0, &mut heap_compare);
+        }
+        if let Some((kmer, total)) = current {
+            visit(kmer, total);
+        }
+        Ok(())
+    }
+
+    /// Sorted k-mers seen at least `min_count` times, with their counts
+    pub fn counts(self) -> Result<Vec<(Kmer, u64)>, Error> {
+        let min_count = self.config.min_count;
+        let mut counts = Vec::new();
+        self.for_each_count(|kmer, count| {
+            if count >= min_count {
+                counts.push((kmer, count));
+            }
+        })?;
+        Ok(counts)
+    }
+
+    /// Write the sorted k-mers seen at least `min_count` times, with their counts,
+    /// in the binary format read by `add_counts_file`, after a header recording k and the
+    /// strandedness
+    pub fn write_counts(self, filename: &Path) -> Result<(), Error> {
+        let min_count = self.config.min_count;
+        let mut writer = BufWriter::new(File::create(filename)?);
+        write_counts_header(&mut writer, self.config.k, self.config.canonical)?;
+        let mut result = Ok(());
+        self.for_each_count(|kmer, count| {
+            if count >= min_count && result.is_ok() {
+                result = write_record(&mut writer, kmer, count);
+            }
+        })?;
+        result?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Histogram of k-mer multiplicities (including those under `min_count`)
+    pub fn spectrum(self) -> Result<KmerSpectrum, Error> {
+        let mut histogram = vec![0];
+        self.for_each_count(|_, count| {
+            let count = count as usize;
+            if histogram.len() <= count {
+                histogram.resize(count + 1, 0);
+            }
+            histogram[count] += 1;
+        })?;
+        Ok(KmerSpectrum { histogram })
+    }
+}
+
+/// k-mer spectrum: `histogram[i]` is the number of distinct k-mers seen exactly i times
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KmerSpectrum {
+    pub histogram: Vec<u64>,
+}
+
+impl KmerSpectrum {
+    /// Multiplicity where the histogram first starts rising, separating k-mers with sequencing
+    /// errors (seen few times) from those of the genome
+    pub fn error_cutoff(&self) -> Option<usize> {
+        (1..self.histogram.len().saturating_sub(1))
+            .find(|i| self.histogram[i + 1] > self.histogram[*i])
+    }
+
+    /// Most common multiplicity above the error cutoff, i.e. the k-mer coverage
+    pub fn peak(&self) -> Option<usize> {
+        let cutoff = self.error_cutoff()?;
+        (cutoff..self.histogram.len()).max_by_key(|i| (self.histogram[*i], std::cmp::Reverse(*i)))
+    }
+
+    /// Genome size as the number of k-mers above the error cutoff divided by the coverage
+    pub fn estimate_genome_size(&self) -> Option<f64> {
+        let (cutoff, peak) = (self.error_cutoff()?, self.peak()?);
+        let total: u64 = (cutoff..self.histogram.len())
+            .map(|i| i as u64 * self.histogram[i])
+            .sum();
+        Some(total as f64 / peak as f64)
+    }
+}
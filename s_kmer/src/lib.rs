@@ -1,9 +1,9 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
 
-use s_lexf::enumerate_lex;
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmers};
+
+pub mod counter;
 
 /// k-Mer Composition
 ///
@@ -11,20 +11,16 @@ use std::path::Path;
 ///
 /// Return: The 4-mer composition of s.
 pub fn rosalind_kmer(filename: &Path) -> Result<Vec<Vec<usize>>, Error> {
-    let alphabets = vec!['A', 'C', 'G', 'T'];
+    let alphabet = Alphabet::dna();
     let dna = utility::io::read_fasta_file(filename)?;
-    let kmer_indices: HashMap<String, usize> = enumerate_lex(&alphabets, 4)
-        .into_iter()
-        .enumerate()
-        .map(|(i, k)| (k, i))
-        .collect();
     let mut counts: Vec<Vec<_>> = Vec::with_capacity(dna.len());
-    for (i, (_, sequence)) in dna.into_iter().enumerate() {
-        counts.push((0..kmer_indices.len()).map(|_| 0).collect());
-        for kmer in utility::string::kmerize(&sequence, 4) {
-            counts[i][kmer_indices[&kmer]] += 1;
+    for (_, sequence) in dna {
+        let mut composition = vec![0; alphabet.size().pow(4)];
+        for (_, kmer) in Kmers::<u64>::new(sequence.as_bytes(), 4, &alphabet) {
+            composition[kmer.rank(&alphabet) as usize] += 1;
         }
-        println!("{}", utility::io::format_array(&counts[i]));
+        println!("{}", utility::io::format_array(&composition));
+        counts.push(composition);
     }
     Ok(counts)
 }
@@ -40,9 +36,123 @@ mod tests {
         let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_kmer")?;
         let output = utility::io::input_from_file(&output_file)?
             .split('\n')
-            .map(|line| usize::parse_line(line))
+            .map(usize::parse_line)
             .collect::<Result<Vec<Vec<_>>, _>>()?;
         assert_eq!(rosalind_kmer(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn kmer_counter() -> Result<(), Error> {
+        use crate::counter::{KmerCounter, KmerCounterConfig};
+        use utility::kmer::Kmer;
+
+        let temp_dir = std::env::temp_dir().join(format!("s_kmer_test_{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir)?;
+        let dna = Alphabet::dna();
+        // a pseudo-random genome read at 20x coverage by error-free overlapping reads
+        let genome = utility::testing::random_sequence(400, b"ACGT", 7).into_bytes();
+        let mut fastq = String::new();
+        for start in (0..genome.len() - 50).step_by(5) {
+            let read = std::str::from_utf8(&genome[start..start + 50]).unwrap();
+            // qualities starting with '@' must not be read as headers
+            fastq.push_str(&format!(
+                "@read{}\n{}\n+\n@{}\n",
+                start,
+                read,
+                "I".repeat(49)
+            ));
+        }
+        let fastq_file = temp_dir.join("reads.fastq");
+        std::fs::write(&fastq_file, &fastq)?;
+        let fasta_file = temp_dir.join("genome.fasta");
+        std::fs::write(
+            &fasta_file,
+            format!(
+                ">genome\n{}\n{}\n>n\nNNNN\n",
+                std::str::from_utf8(&genome[..200]).unwrap(),
+                std::str::from_utf8(&genome[200..]).unwrap()
+            ),
+        )?;
+
+        let config = KmerCounterConfig {
+            k: 11,
+            canonical: false,
+            min_count: 1,
+            memory_budget: 1 << 30,
+            temp_dir: temp_dir.clone(),
+        };
+        let mut expected: std::collections::HashMap<Kmer, u64> = std::collections::HashMap::new();
+        for (_, kmer) in Kmers::<u64>::new(&genome, 11, &dna) {
+            *expected.entry(kmer).or_insert(0) += 1;
+        }
+        let mut in_memory = KmerCounter::new(config.clone());
+        in_memory.add_file(&fasta_file)?;
+        let mut expected: Vec<_> = expected.into_iter().collect();
+        expected.sort_unstable();
+        assert_eq!(in_memory.counts()?, expected);
+
+        // spilling and merging count files gives the same counts as counting in memory
+        let mut spilling = KmerCounter::new(KmerCounterConfig {
+            memory_budget: 2000,
+            min_count: 2,
+            canonical: true,
+            ..config.clone()
+        });
+        let mut unspilled = KmerCounter::new(KmerCounterConfig {
+            min_count: 2,
+            canonical: true,
+            ..config.clone()
+        });
+        let counts_file = temp_dir.join("genome.counts");
+        let mut genome_counter = KmerCounter::new(KmerCounterConfig {
+            canonical: true,
+            ..config.clone()
+        });
+        genome_counter.add_file(&fasta_file)?;
+        genome_counter.write_counts(&counts_file)?;
+        spilling.add_file(&fastq_file)?;
+        spilling.add_counts_file(&counts_file)?;
+        unspilled.add_file(&fastq_file)?;
+        unspilled.add_file(&fasta_file)?;
+        let counts = spilling.counts()?;
+        assert_eq!(counts, unspilled.counts()?);
+        assert!(counts
+            .iter()
+            .all(|(kmer, count)| *count >= 2 && *kmer == kmer.canonical(&dna)));
+
+        // count files are only merged into counters with the same k and strandedness
+        assert!(KmerCounter::new(config.clone())
+            .add_counts_file(&counts_file)
+            .is_err());
+        assert!(KmerCounter::new(KmerCounterConfig {
+            k: 12,
+            canonical: true,
+            ..config.clone()
+        })
+        .add_counts_file(&counts_file)
+        .is_err());
+        assert!(KmerCounter::new(config.clone())
+            .add_counts_file(&fasta_file)
+            .is_err());
+        let mut truncated = std::fs::read(&counts_file)?;
+        truncated.pop();
+        let truncated_file = temp_dir.join("truncated.counts");
+        std::fs::write(&truncated_file, &truncated)?;
+        assert!(KmerCounter::new(KmerCounterConfig {
+            canonical: true,
+            ..config.clone()
+        })
+        .add_counts_file(&truncated_file)
+        .is_err());
+
+        let mut reads = KmerCounter::new(config);
+        reads.add_file(&fastq_file)?;
+        let spectrum = reads.spectrum()?;
+        let genome_size = spectrum.estimate_genome_size().unwrap();
+        std::fs::remove_dir_all(&temp_dir)?;
+        assert_eq!(spectrum.peak(), Some(8));
+        assert!((genome_size - 400.).abs() < 80., "{}", genome_size);
+        Ok(())
+    }
 }
@@ -2,35 +2,41 @@ use anyhow::Error;
 
 use std::path::Path;
 
+pub use matcher::{Horspool, Kmp, MatchStream, Matcher, RabinKarp, ZAlgorithm};
+
+pub mod matcher;
+
 /// Speeding Up Motif Finding
 ///
 /// Given: A DNA string s (of length at most 100 kbp) in FASTA format.
 ///
 /// Return: The failure array of s.
-pub fn rosalind_kmp(filename: &Path) -> Result<Vec<Vec<isize>>, Error> {
+pub fn rosalind_kmp(filename: &Path) -> Result<Vec<Vec<usize>>, Error> {
     let dna = utility::io::read_fasta_file(filename)?;
     let mut counts: Vec<Vec<_>> = Vec::with_capacity(dna.len());
     for (_, sequence) in dna {
-        let array = compute_failure_array(&sequence);
+        let array = compute_failure_array(sequence.as_bytes());
         println!("{}", utility::io::format_array(&array));
         counts.push(array);
     }
     Ok(counts)
 }
 
-fn compute_failure_array(string: &str) -> Vec<isize> {
-    let n = string.len();
-    let characters: Vec<_> = string.chars().collect();
-    let mut failure_array: Vec<isize> = (0..=n).map(|_| -1).collect();
-    let mut j = -1isize;
-    for i in 1..=n {
-        while (j >= 0) && (characters[i - 1] != characters[(j as usize)]) {
-            j = failure_array[j as usize];
+/// Failure array of a string: for each prefix, the length of its longest proper suffix
+/// that is also a prefix of the string
+pub fn compute_failure_array(string: &[u8]) -> Vec<usize> {
+    let mut failure_array = vec![0; string.len()];
+    let mut j = 0;
+    for i in 1..string.len() {
+        while j > 0 && string[i] != string[j] {
+            j = failure_array[j - 1];
+        }
+        if string[i] == string[j] {
+            j += 1;
         }
-        j += 1;
         failure_array[i] = j;
     }
-    failure_array[1..].to_vec()
+    failure_array
 }
 
 #[cfg(test)]
@@ -44,9 +50,51 @@ mod tests {
         let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_kmp")?;
         let output = utility::io::input_from_file(&output_file)?
             .split('\n')
-            .map(|line| isize::parse_line(line))
+            .map(usize::parse_line)
             .collect::<Result<Vec<Vec<_>>, _>>()?;
         assert_eq!(rosalind_kmp(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn matchers() {
+        let text = b"GATATATGCATATACTTATATATAT";
+        for pattern in &[
+            &b"ATAT"[..],
+            b"A",
+            b"T",
+            b"GATATATGCATATACTTATATATAT",
+            b"CG",
+            b"",
+        ] {
+            let expected: Vec<_> = (0..text.len())
+                .filter(|i| !pattern.is_empty() && text[*i..].starts_with(pattern))
+                .collect();
+            let matchers: Vec<Box<dyn Matcher>> = vec![
+                Box::new(Kmp::new(pattern)),
+                Box::new(ZAlgorithm::new(pattern)),
+                Box::new(Horspool::new(pattern)),
+                Box::new(RabinKarp::new(pattern)),
+            ];
+            for matcher in &matchers {
+                assert_eq!(matcher.find_all(text), expected);
+            }
+            let non_overlapping = matchers[0].find_non_overlapping(text);
+            for chunk_size in 1..6 {
+                let kmp = Kmp::new(pattern);
+                for (overlapping, expected) in &[(true, &expected), (false, &non_overlapping)] {
+                    let mut stream = kmp.stream(*overlapping);
+                    let streamed: Vec<_> = text
+                        .chunks(chunk_size)
+                        .flat_map(|chunk| stream.feed(chunk))
+                        .collect();
+                    assert_eq!(&streamed, *expected);
+                }
+            }
+        }
+        assert_eq!(
+            Kmp::new(b"ATAT").find_non_overlapping(text),
+            vec![1, 9, 17, 21]
+        );
+    }
 }
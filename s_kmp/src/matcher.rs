@@ -0,0 +1,320 @@
+//! Exact single-pattern matching over bytes.
+//!
+//! Every algorithm implements `Matcher`, which finds overlapping or non-overlapping occurrences
+//! in a whole text, or incrementally in a text arriving in chunks through `MatchStream`.
+use crate::compute_failure_array;
+
+pub trait Matcher {
+    fn pattern(&self) -> &[u8];
+
+    /// Starting positions of all (possibly overlapping) occurrences of the pattern
+    fn find_all(&self, text: &[u8]) -> Vec<usize>;
+
+    /// Starting positions of occurrences that don't overlap, taken greedily from the left
+    fn find_non_overlapping(&self, text: &[u8]) -> Vec<usize> {
+        let length = self.pattern().len();
+        let mut next_allowed = 0;
+        self.find_all(text)
+            .into_iter()
+            .filter(|position| {
+                let allowed = *position >= next_allowed;
+                if allowed {
+                    next_allowed = position + length;
+                }
+                allowed
+            })
+            .collect()
+    }
+
+    /// Number of (possibly overlapping) occurrences
+    fn count(&self, text: &[u8]) -> usize {
+        self.find_all(text).len()
+    }
+
+    /// Matcher for a text arriving in chunks
+    fn stream(&self, overlapping: bool) -> MatchStream<'_, Self>
+    where
+        Self: Sized,
+    {
+        MatchStream {
+            matcher: self,
+            overlapping,
+            carry: Vec::new(),
+            offset: 0,
+            next_allowed: 0,
+        }
+    }
+}
+
+/// Keeps the last |pattern| - 1 bytes of each chunk so that occurrences spanning chunks are found
+pub struct MatchStream<'a, M: Matcher> {
+    matcher: &'a M,
+    overlapping: bool,
+    carry: Vec<u8>,
+    /// position in the whole text of the start of `carry`
+    offset: usize,
+    next_allowed: usize,
+}
+
+impl<'a, M: Matcher> MatchStream<'a, M> {
+    /// Scan the next chunk, returning the starting positions (in the whole text)
+    /// of the occurrences ending in it
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<usize> {
+        let length = self.matcher.pattern().len();
+        self.carry.extend_from_slice(chunk);
+        let mut positions: Vec<_> = self
+            .matcher
+            .find_all(&self.carry)
+            .into_iter()
+            .map(|position| position + self.offset)
+            .collect();
+        if !self.overlapping {
+            let next_allowed = &mut self.next_allowed;
+            positions.retain(|position| {
+                let allowed = *position >= *next_allowed;
+                if allowed {
+                    *next_allowed = position + length;
+                }
+                allowed
+            });
+        }
+        let keep = length.saturating_sub(1).min(self.carry.len());
+        let drop = self.carry.len() - keep;
+        self.carry.drain(..drop);
+        self.offset += drop;
+        positions
+    }
+}
+
+/// Knuth-Morris-Pratt: O(n + m) using the failure array of the pattern
+pub struct Kmp {
+    pattern: Vec<u8>,
+    failure_array: Vec<usize>,
+}
+
+impl Kmp {
+    pub fn new(pattern: &[u8]) -> Self {
+        Kmp {
+            pattern: pattern.to_vec(),
+            failure_array: compute_failure_array(pattern),
+        }
+    }
+}
+
+impl Matcher for Kmp {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_all(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = Vec::new();
+        if self.pattern.is_empty() {
+            return positions;
+        }
+        let mut matched = 0;
+        for (i, c) in text.iter().enumerate() {
+            while matched > 0 && self.pattern[matched] != *c {
+                matched = self.failure_array[matched - 1];
+            }
+            if self.pattern[matched] == *c {
+                matched += 1;
+            }
+            if matched == self.pattern.len() {
+                positions.push(i + 1 - matched);
+                matched = self.failure_array[matched - 1];
+            }
+        }
+        positions
+    }
+}
+
+/// Z-array: `z[i]` is the length of the longest common prefix of `string` and `string[i..]`
+/// (`z[0]` is the length of the string)
+pub fn z_array(string: &[u8]) -> Vec<usize> {
+    let mut z = vec![0; string.len()];
+    if string.is_empty() {
+        return z;
+    }
+    z[0] = string.len();
+    // [left, right) is the rightmost window found so far that matches a prefix
+    let (mut left, mut right) = (0, 0);
+    for i in 1..string.len() {
+        let mut length = if i < right {
+            z[i - left].min(right - i)
+        } else {
+            0
+        };
+        while i + length < string.len() && string[length] == string[i + length] {
+            length += 1;
+        }
+        if i + length > right {
+            left = i;
+            right = i + length;
+        }
+        z[i] = length;
+    }
+    z
+}
+
+/// Z-algorithm: the pattern's Z-array lets each text position reuse the comparisons of the
+/// rightmost window matching a prefix of the pattern, in O(n + m)
+pub struct ZAlgorithm {
+    pattern: Vec<u8>,
+    z_array: Vec<usize>,
+}
+
+impl ZAlgorithm {
+    pub fn new(pattern: &[u8]) -> Self {
+        ZAlgorithm {
+            pattern: pattern.to_vec(),
+            z_array: z_array(pattern),
+        }
+    }
+}
+
+impl Matcher for ZAlgorithm {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_all(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = Vec::new();
+        let m = self.pattern.len();
+        if m == 0 {
+            return positions;
+        }
+        let (mut left, mut right) = (0, 0);
+        for i in 0..text.len() {
+            let mut length = if i < right {
+                self.z_array[i - left].min(right - i)
+            } else {
+                0
+            };
+            if i >= right || self.z_array[i - left] >= right - i {
+                while length < m
+                    && i + length < text.len()
+                    && self.pattern[length] == text[i + length]
+                {
+                    length += 1;
+                }
+                left = i;
+                right = i + length;
+            }
+            if length == m {
+                positions.push(i);
+            }
+        }
+        positions
+    }
+}
+
+/// Boyer-Moore-Horspool: compares the window right to left and shifts by the bad-character rule
+/// for its last byte. Sublinear on average for long patterns over large alphabets.
+pub struct Horspool {
+    pattern: Vec<u8>,
+    shifts: [usize; 256],
+}
+
+impl Horspool {
+    pub fn new(pattern: &[u8]) -> Self {
+        let mut shifts = [pattern.len().max(1); 256];
+        if let Some((_, init)) = pattern.split_last() {
+            for (i, c) in init.iter().enumerate() {
+                shifts[*c as usize] = pattern.len() - 1 - i;
+            }
+        }
+        Horspool {
+            pattern: pattern.to_vec(),
+            shifts,
+        }
+    }
+}
+
+impl Matcher for Horspool {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_all(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = Vec::new();
+        let m = self.pattern.len();
+        if m == 0 {
+            return positions;
+        }
+        let mut start = 0;
+        while start + m <= text.len() {
+            let window = &text[start..start + m];
+            if window
+                .iter()
+                .rev()
+                .zip(self.pattern.iter().rev())
+                .all(|(a, b)| a == b)
+            {
+                positions.push(start);
+            }
+            start += self.shifts[window[m - 1] as usize];
+        }
+        positions
+    }
+}
+
+/// Rabin-Karp: compares a rolling polynomial hash of each window with the pattern's,
+/// checking the bytes only when the hashes agree
+pub struct RabinKarp {
+    pattern: Vec<u8>,
+    pattern_hash: u64,
+    /// BASE^(m - 1), to remove the outgoing byte from the hash
+    leading_power: u64,
+}
+
+impl RabinKarp {
+    const BASE: u64 = 257;
+    /// Mersenne prime 2^61 - 1
+    const MODULUS: u64 = (1 << 61) - 1;
+
+    pub fn new(pattern: &[u8]) -> Self {
+        let leading_power =
+            (1..pattern.len()).fold(1, |power, _| Self::multiply(power, Self::BASE));
+        RabinKarp {
+            pattern: pattern.to_vec(),
+            pattern_hash: Self::hash(pattern),
+            leading_power,
+        }
+    }
+
+    fn multiply(a: u64, b: u64) -> u64 {
+        ((a as u128 * b as u128) % Self::MODULUS as u128) as u64
+    }
+
+    fn hash(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0, |hash, c| {
+            (Self::multiply(hash, Self::BASE) + *c as u64) % Self::MODULUS
+        })
+    }
+}
+
+impl Matcher for RabinKarp {
+    fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    fn find_all(&self, text: &[u8]) -> Vec<usize> {
+        let mut positions = Vec::new();
+        let m = self.pattern.len();
+        if m == 0 || text.len() < m {
+            return positions;
+        }
+        let mut hash = Self::hash(&text[..m]);
+        for start in 0..=(text.len() - m) {
+            if hash == self.pattern_hash && text[start..start + m] == self.pattern[..] {
+                positions.push(start);
+            }
+            if start + m < text.len() {
+                let outgoing = Self::multiply(text[start] as u64, self.leading_power);
+                hash = (hash + Self::MODULUS - outgoing) % Self::MODULUS;
+                hash = (Self::multiply(hash, Self::BASE) + text[start + m] as u64) % Self::MODULUS;
+            }
+        }
+        positions
+    }
+}
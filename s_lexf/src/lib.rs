@@ -1,9 +1,8 @@
-use std::char;
-use std::collections::HashMap;
 use std::path::Path;
 
 use anyhow::Error;
-use radix::RadixNum;
+
+use utility::enumeration::{Enumeration, Words};
 
 /// Enumerating k-mers Lexicographically
 ///
@@ -18,55 +17,21 @@ pub fn rosalind_lexf(filename: &Path) -> Result<Vec<String>, Error> {
         .map(|a| a.chars().next().unwrap())
         .collect();
     let length = parts[1].parse::<usize>()?;
-    let output = enumerate_lex(&alphabets, length);
-    for line in &output {
+    let mut output = Vec::new();
+    for line in enumerate_lex(&alphabets, length) {
         println!("{}", line);
+        output.push(line);
     }
     Ok(output)
 }
 
-/// Definitely a hack: uses base-10 conversion to convert between decimal and alphabet length
-pub fn enumerate_lex_2(alphabets: Vec<char>, length: usize) -> impl Iterator<Item = String> {
-    let num_alphabets = alphabets.len();
-    let alphabet_map: HashMap<char, char> = alphabets
-        .into_iter()
-        .enumerate()
-        .map(|(n, c)| {
-            (
-                char::from_digit(n as u32, num_alphabets as u32)
-                    .unwrap()
-                    .to_ascii_uppercase(),
-                c,
-            )
-        })
-        .collect();
-    let num_strings = num_alphabets.pow(length as u32);
-    (0..num_strings).map(move |i| {
-        let i_digits = RadixNum::from(i)
-            .with_radix(num_alphabets)
-            .unwrap()
-            .digits()
-            .collect::<Vec<_>>();
-        (0..(length - i_digits.len()))
-            .map(|_| alphabet_map[&'0'])
-            .collect::<String>()
-            + &i_digits
-                .into_iter()
-                .map(|n| alphabet_map[&n])
-                .collect::<String>()
-    })
-}
-
-pub fn enumerate_lex(alphabet: &[char], length: usize) -> Vec<String> {
-    if length == 1 {
-        alphabet.iter().map(|i| i.to_string()).collect()
-    } else {
-        let strings = enumerate_lex(alphabet, length - 1);
-        alphabet
-            .iter()
-            .flat_map(|c| strings.iter().map(move |rest| format!("{}{}", c, rest)))
-            .collect()
-    }
+/// Strings of length `length` over the ordered alphabet, lazily in lexicographic order
+pub fn enumerate_lex(alphabet: &[char], length: usize) -> impl Iterator<Item = String> {
+    let words = Words::new(alphabet.to_vec(), length);
+    let size = words.size();
+    words
+        .into_iter_range(0..size)
+        .map(|word| word.into_iter().collect())
 }
 
 #[cfg(test)]
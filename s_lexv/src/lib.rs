@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
-use itertools::Itertools;
 
 use std::path::Path;
+use utility::enumeration::{Enumeration, VariableLengthWords};
 
 /// Ordering Strings of Varying Length Lexicographically
 ///
@@ -20,20 +18,25 @@ pub fn rosalind_lexv(filename: &Path) -> Result<Vec<String>, Error> {
         .split(' ')
         .map(|a| a.chars().next().unwrap())
         .collect::<Vec<_>>();
-    let alphabet_indices: HashMap<_, _> =
-        alphabet.iter().enumerate().map(|(i, c)| (c, i)).collect();
     let length = parts[1].parse::<usize>()?;
-    let mut strings = cartesian_product_repeat(&alphabet, length);
-    strings.sort_by_key(|k| k.chars().map(|c| alphabet_indices[&c]).collect::<Vec<_>>());
-    println!("{}", strings.join("\n"));
+    let mut strings = Vec::new();
+    for string in enumerate_lex_variable_length(&alphabet, length) {
+        println!("{}", string);
+        strings.push(string);
+    }
     Ok(strings)
 }
 
-fn cartesian_product_repeat(alphabet: &[char], length: usize) -> Vec<String> {
-    (1..=length)
-        .flat_map(|l| (0..l).map(|_| alphabet.iter()).multi_cartesian_product())
-        .map(|x| x.into_iter().collect())
-        .collect()
+/// Strings of length at most `length` over the ordered alphabet, lazily in lexicographic order
+pub fn enumerate_lex_variable_length(
+    alphabet: &[char],
+    length: usize,
+) -> impl Iterator<Item = String> {
+    let words = VariableLengthWords::new(alphabet.to_vec(), length);
+    let size = words.size();
+    words
+        .into_iter_range(0..size)
+        .map(|word| word.into_iter().collect())
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use a_med::order_statistic_tree::OrderStatisticTree;
 use std::path::Path;
 use utility::io::Parseable;
 
@@ -11,10 +12,9 @@ use utility::io::Parseable;
 pub fn rosalind_lgis(filename: &Path) -> Result<(Vec<usize>, Vec<usize>), Error> {
     let input = utility::io::input_from_file(filename)?;
     let parts: Vec<_> = input.split('\n').collect();
-    let length = parts[0].parse::<usize>()?;
     let sequence = usize::parse_line(parts[1])?;
-    let inc_subs = longest_subsequence(length, &sequence, |x, y| x > y);
-    let dec_subs = longest_subsequence(length, &sequence, |x, y| x < y);
+    let inc_subs = longest_subsequence(&sequence, |x| x as isize);
+    let dec_subs = longest_subsequence(&sequence, |x| -(x as isize));
     println!(
         "{}\n{}",
         utility::io::format_array(&inc_subs),
@@ -23,39 +23,37 @@ pub fn rosalind_lgis(filename: &Path) -> Result<(Vec<usize>, Vec<usize>), Error>
     Ok((inc_subs, dec_subs))
 }
 
-/// Find the longest subsequence in a sequence of given length according to a given ordering function
-fn longest_subsequence(
-    length: usize,
-    sequence: &[usize],
-    ordering: fn(usize, usize) -> bool,
-) -> Vec<usize> {
-    let mut sub_length = 0usize;
-    let mut ls_predecessors = Vec::new();
-    let mut ls_ends = (0..=length).map(|_| 0).collect::<Vec<_>>();
-    for i in 0..length {
-        let mut low = 1.;
-        let mut high = sub_length as f64;
-        while low <= high {
-            let mid = ((low + high) / 2.).ceil() as usize;
-            if ordering(sequence[i], sequence[ls_ends[mid]]) {
-                low = (mid + 1) as f64;
-            } else {
-                high = (mid - 1) as f64;
-            }
-        }
-        let new_length = low as usize;
-        ls_predecessors.push(ls_ends[new_length - 1]);
-        ls_ends[new_length] = i;
-        if new_length > sub_length {
-            sub_length = new_length;
+/// Find the longest subsequence of a sequence that is strictly increasing under a given key.
+///
+/// Patience sorting: `tails` holds, for each length, the (key, index) of the smallest
+/// possible last element of a subsequence of that length
+fn longest_subsequence(sequence: &[usize], key: fn(usize) -> isize) -> Vec<usize> {
+    let mut tails = OrderStatisticTree::new();
+    let mut predecessors = Vec::with_capacity(sequence.len());
+    for (i, value) in sequence.iter().enumerate() {
+        let value_key = key(*value);
+        let position = tails.rank(&(value_key, 0));
+        predecessors.push(if position > 0 {
+            tails.select(position - 1).map(|(_, index)| *index)
+        } else {
+            None
+        });
+        if let Some(replaced) = tails.select(position).copied() {
+            tails.remove(&replaced);
         }
+        tails.insert((value_key, i));
     }
-    let mut subsequence = (0..sub_length as usize).map(|_| 0).collect::<Vec<_>>();
-    let mut k = ls_ends[sub_length];
-    for i in (0..sub_length).rev() {
-        subsequence[i] = sequence[k];
-        k = ls_predecessors[k];
+    let mut subsequence = Vec::with_capacity(tails.len());
+    let mut current = tails
+        .len()
+        .checked_sub(1)
+        .and_then(|last| tails.select(last))
+        .map(|(_, index)| *index);
+    while let Some(index) = current {
+        subsequence.push(sequence[index]);
+        current = predecessors[index];
     }
+    subsequence.reverse();
     subsequence
 }
 
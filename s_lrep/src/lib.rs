@@ -1,82 +1,98 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
-use petgraph::visit::EdgeRef;
-use petgraph::Directed;
-use petgraph::Direction::{Incoming, Outgoing};
-use petgraph::Graph;
 
-use s_ctbl::traverse;
 use std::path::Path;
+use t_ba9c::{GeneralizedSuffixTree, Repeat};
 
-/// W.I.P
-fn make_suffix_tree(
-    lines: &[&str],
-) -> Result<Graph<String, (usize, usize), Directed, usize>, Error> {
-    let mut tree: Graph<String, (usize, usize), Directed, usize> = Graph::default();
-    let mut node_to_index = HashMap::new();
-    let (mut index_1, mut index_2);
-    for line in lines {
-        let parts: Vec<_> = line.split(' ').collect();
-        let (node_1, node_2, start, length) = (
-            parts[0],
-            parts[1],
-            parts[2].parse::<usize>()?,
-            parts[3].parse::<usize>()?,
-        );
-        index_1 = *node_to_index
-            .entry(node_1)
-            .or_insert_with(|| tree.add_node(node_1.to_owned()));
-        index_2 = *node_to_index
-            .entry(node_2)
-            .or_insert_with(|| tree.add_node(node_2.to_owned()));
-        tree.add_edge(index_1, index_2, (start, length));
-    }
-    Ok(tree)
-}
-
-pub fn rosalind_lrep(filename: &Path) -> Result<(), Error> {
+/// Finding the Longest Multiple Repeat
+///
+/// Given: A DNA string s (of length at most 20 kbp) with $ appended, a positive integer k,
+/// and a list of edges defining the suffix tree of s.
+///
+/// Return: The longest substring of s that occurs at least k times in s.
+/// (If multiple solutions exist, you may return any single solution.)
+pub fn rosalind_lrep(filename: &Path) -> Result<String, Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = contents.split('\n').collect();
-    let (dna, k) = (lines[0], lines[1].parse::<usize>()?);
-    let dna: Vec<_> = dna.chars().collect();
-    let tree = make_suffix_tree(&lines[2..])?;
-    let mut substring = (None, 0);
-    for node in tree.node_indices() {
-        let num_leaves = traverse(&tree, node)
-            .into_iter()
-            .filter(|n| tree.edges_directed(*n, Outgoing).count() == 0)
-            .count();
-        if num_leaves >= k {
-            let (mut start, mut length) = (None, 0);
-            let mut edges = tree.edges_directed(node, Incoming);
-            while let Some(edge) = edges.next() {
-                start = Some(tree[edge.id()].0);
-                length += tree[edge.id()].1;
-                edges = tree.edges_directed(edge.source(), Incoming);
-            }
-            if length > substring.1 {
-                substring = (start, length);
-                if let Some(start) = start {
-                    println!(
-                        "{} {} {}",
-                        tree.node_weight(node).unwrap(),
-                        num_leaves,
-                        &dna[(start - 1)..(start - 1 + length)]
-                            .iter()
-                            .collect::<String>()
-                    );
-                }
-            }
-        }
+    let (dna, k) = (lines[0].trim_end_matches('$'), lines[1].parse::<usize>()?);
+    // the suffix tree is built from the string rather than read from the given edges
+    let suffix_tree = GeneralizedSuffixTree::new(&[dna]);
+    let repeat = String::from_utf8(
+        suffix_tree
+            .longest_k_fold_repeat(k)
+            .unwrap_or_default()
+            .to_vec(),
+    )?;
+    println!("{}", repeat);
+    Ok(repeat)
+}
+
+/// Convert repeats in a single string to the repeated substring and its 0-based positions
+fn to_positions(repeats: Vec<Repeat<'_>>) -> Vec<(String, Vec<usize>)> {
+    repeats
+        .into_iter()
+        .map(|repeat| {
+            (
+                String::from_utf8_lossy(repeat.string).into_owned(),
+                repeat
+                    .positions
+                    .into_iter()
+                    .map(|(_, offset)| offset)
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// Repeats of at least `min_length` occurring at least `min_count` times which can't be extended
+/// in either direction without losing an occurrence, longest first
+pub fn maximal_repeats(
+    dna: &str,
+    min_length: usize,
+    min_count: usize,
+) -> Vec<(String, Vec<usize>)> {
+    to_positions(GeneralizedSuffixTree::new(&[dna]).maximal_repeats(min_length, min_count))
+}
+
+/// Maximal repeats of at least `min_length` occurring at least `min_count` times which aren't
+/// contained in another repeat occurring that often, longest first
+pub fn supermaximal_repeats(
+    dna: &str,
+    min_length: usize,
+    min_count: usize,
+) -> Vec<(String, Vec<usize>)> {
+    to_positions(GeneralizedSuffixTree::new(&[dna]).supermaximal_repeats(min_length, min_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lrep() -> Result<(), Error> {
+        let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_lrep")?;
+        let output = utility::io::input_from_file(&output_file)?;
+        assert_eq!(rosalind_lrep(&input_file)?, output.trim());
+        Ok(())
     }
-    if let (Some(start), length) = substring {
-        println!(
-            "{}",
-            &dna[(start - 1)..(start - 1 + length)]
-                .iter()
-                .collect::<String>()
+
+    #[test]
+    fn maximal_and_supermaximal_repeats() {
+        let dna = "XABCYABCWABCYZ";
+        let pair = |string: &str, positions: Vec<usize>| (string.to_owned(), positions);
+        assert_eq!(
+            maximal_repeats(dna, 1, 2),
+            vec![pair("ABCY", vec![1, 9]), pair("ABC", vec![1, 5, 9])]
+        );
+        assert_eq!(maximal_repeats(dna, 1, 3), vec![pair("ABC", vec![1, 5, 9])]);
+        assert_eq!(
+            supermaximal_repeats(dna, 1, 2),
+            vec![pair("ABCY", vec![1, 9])]
+        );
+        assert!(supermaximal_repeats(dna, 5, 2).is_empty());
+        // ABCY occurs twice, so doesn't count against ABC among repeats occurring three times
+        assert_eq!(
+            supermaximal_repeats(dna, 1, 3),
+            vec![pair("ABC", vec![1, 5, 9])]
         );
     }
-    Ok(())
 }
@@ -2,7 +2,10 @@ use std::collections::HashMap;
 use std::path::Path;
 
 use anyhow::Error;
-use regex::Regex;
+
+use crate::prosite::PrositePattern;
+
+pub mod prosite;
 
 const UNIPROT_URL: &str = "http://www.uniprot.org/uniprot/";
 
@@ -13,7 +16,7 @@ const UNIPROT_URL: &str = "http://www.uniprot.org/uniprot/";
 /// Return: For each protein possessing the N-glycosylation motif, output its given access ID
 /// followed by a list of locations in the protein string where the motif can be found.
 pub async fn rosalind_mprt(filename: &Path) -> Result<HashMap<String, Vec<usize>>, Error> {
-    let motif = Regex::new("N[^P][ST][^P]")?;
+    let motif: PrositePattern = "N-{P}-[ST]-{P}".parse()?;
     let input = utility::io::input_from_file(filename)?;
     let uniprot_ids = input.split('\n').collect::<Vec<&str>>();
     let mut sequences = Vec::with_capacity(uniprot_ids.len());
@@ -23,7 +26,11 @@ pub async fn rosalind_mprt(filename: &Path) -> Result<HashMap<String, Vec<usize>
     let output = sequences
         .into_iter()
         .filter_map(|(uniprot_id, sequence)| {
-            let indices = find_all(&motif, &sequence);
+            let indices: Vec<_> = motif
+                .find_all(sequence.as_bytes())
+                .into_iter()
+                .map(|hit| hit.start + 1)
+                .collect();
             if !indices.is_empty() {
                 Some((uniprot_id, indices))
             } else {
@@ -56,23 +63,6 @@ async fn get_sequence_from_uniprot(uniprot_id: &str) -> Result<String, Error> {
         .join(""))
 }
 
-/// Overlapping regex matcher. Returns all (1-indexed) positions where regex is found.
-fn find_all(motif: &Regex, sequence: &str) -> Vec<usize> {
-    let mut indices = Vec::new();
-    let mut subsequence;
-    let mut start_index = 0;
-    loop {
-        subsequence = &sequence[start_index..];
-        match motif.find(subsequence) {
-            Some(mat) => {
-                start_index += mat.start() + 1;
-                indices.push(start_index);
-            }
-            None => return indices,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -94,4 +84,60 @@ mod tests {
         assert_eq!(rosalind_mprt(&input_file).await?, output);
         Ok(())
     }
+
+    #[test]
+    fn prosite() -> Result<(), Error> {
+        let hit = |start, end| prosite::Hit { start, end };
+        let glycosylation = prosite::common_motif("PS00001").unwrap().compile();
+        assert_eq!(
+            glycosylation.find_all(b"NNSTNPSTNNTS"),
+            vec![hit(0, 4), hit(1, 5), hit(8, 12)]
+        );
+        let pattern: PrositePattern = "<A-x(1,2)-[ST](2)-{V}-[G>].".parse()?;
+        assert_eq!(pattern.find_all(b"ACSTQG"), vec![hit(0, 6)]);
+        assert_eq!(pattern.find_all(b"ACDSTQ"), vec![hit(0, 6)]);
+        assert_eq!(pattern.find_all(b"AACSTQG"), vec![hit(0, 7)]);
+        assert!(pattern.find_all(b"ACSTVG").is_empty());
+        assert!(pattern.find_all(b"CACSTQG").is_empty());
+        let er_target = prosite::common_motif("PS00014").unwrap().compile();
+        assert_eq!(er_target.find_all(b"MKDELKDEL"), vec![hit(5, 9)]);
+        for bad in &["", "A-", "A-x(3,2)", "A-[BC", "A-(2)", "A-[G>]-C", "a-C"] {
+            assert!(bad.parse::<PrositePattern>().is_err(), "{}", bad);
+        }
+        for motif in prosite::COMMON_MOTIFS {
+            motif.compile();
+        }
+        assert!(prosite::common_motif("PS99999").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn scan_fasta() -> Result<(), Error> {
+        let hit = |start, end| prosite::Hit { start, end };
+        let fasta_file =
+            std::env::temp_dir().join(format!("s_mprt_test_{}.fasta", std::process::id()));
+        std::fs::write(
+            &fasta_file,
+            ">P1\nMNGTANPSQ\nKDEL\n>P2\nMRGDA\n>P3\nMAAAA\n",
+        )?;
+        let patterns: Vec<_> = ["PS00001", "PS00014", "PS00016"]
+            .iter()
+            .map(|accession| {
+                let motif = prosite::common_motif(accession).unwrap();
+                (motif.name, motif.compile())
+            })
+            .collect();
+        let hits = prosite::scan_fasta(&fasta_file, &patterns);
+        std::fs::remove_file(&fasta_file)?;
+        let found = |id: &str, name: &str, hit| (id.to_owned(), name.to_owned(), hit);
+        assert_eq!(
+            hits?,
+            vec![
+                found("P1", "ASN_GLYCOSYLATION", hit(1, 5)),
+                found("P1", "ER_TARGET", hit(9, 13)),
+                found("P2", "RGD", hit(1, 4)),
+            ]
+        );
+        Ok(())
+    }
 }
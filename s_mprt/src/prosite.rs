@@ -0,0 +1,302 @@
+//! PROSITE patterns, e.g. `<A-x-[ST](2)-x(0,1)-{V}-[G>].`
+//!
+//! Elements are separated by '-' and are either a residue, `x` (any residue), `[..]` (any of the
+//! listed residues) or `{..}` (any residue but those listed), optionally followed by a repetition
+//! `(n)` or `(n,m)`. A leading `<` anchors the pattern to the N-terminus and a trailing `>` to the
+//! C-terminus; `>` inside the brackets of the last element (or `<` in the first) means the element
+//! may also match the end (or start) of the sequence.
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Error;
+
+use utility::errors::RosalindParseError;
+
+/// Bitmask of the 26 letters
+const ANY_RESIDUE: u32 = (1 << 26) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Element {
+    residues: u32,
+    min: usize,
+    max: usize,
+    /// also matches (as an empty string) the start of the sequence if first
+    /// or its end if last
+    or_terminus: bool,
+}
+
+impl Element {
+    fn matches(&self, residue: u8) -> bool {
+        residue.is_ascii_alphabetic()
+            && self.residues & (1 << (residue.to_ascii_uppercase() - b'A')) != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrositePattern {
+    elements: Vec<Element>,
+    n_terminal: bool,
+    c_terminal: bool,
+}
+
+/// Occurrence of a pattern at `start..end` (0-indexed) in a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hit {
+    pub start: usize,
+    pub end: usize,
+}
+
+fn format_error(pattern: &str, message: &str) -> RosalindParseError {
+    RosalindParseError::InputFormatError(format!("PROSITE pattern {}: {}", pattern, message))
+}
+
+fn residue_mask(residues: &str, pattern: &str) -> Result<u32, RosalindParseError> {
+    residues.bytes().try_fold(0, |mask, residue| {
+        if residue.is_ascii_uppercase() {
+            Ok(mask | 1 << (residue - b'A'))
+        } else {
+            Err(format_error(
+                pattern,
+                &format!("unexpected character {}", residue as char),
+            ))
+        }
+    })
+}
+
+impl FromStr for PrositePattern {
+    type Err = RosalindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut body = s.trim().trim_end_matches('.');
+        let n_terminal = body.starts_with('<');
+        if n_terminal {
+            body = &body[1..];
+        }
+        // a '>' inside the last element's brackets is followed by ']'
+        let c_terminal = body.ends_with('>');
+        if c_terminal {
+            body = &body[..body.len() - 1];
+        }
+        if body.is_empty() {
+            return Err(format_error(s, "no elements"));
+        }
+        let tokens: Vec<_> = body.split('-').collect();
+        let mut elements = Vec::with_capacity(tokens.len());
+        for (index, token) in tokens.iter().enumerate() {
+            let (residues, repetition) = match token.find('(') {
+                Some(open) => {
+                    let repetition = token[open + 1..]
+                        .strip_suffix(')')
+                        .ok_or_else(|| format_error(s, "unclosed repetition"))?;
+                    (&token[..open], Some(repetition))
+                }
+                None => (*token, None),
+            };
+            let mut or_terminus = false;
+            let residues = if residues == "x" {
+                ANY_RESIDUE
+            } else if let Some(inner) = residues.strip_prefix('[').and_then(|r| r.strip_suffix(']'))
+            {
+                let terminus = if index == 0 {
+                    Some('<')
+                } else if index == tokens.len() - 1 {
+                    Some('>')
+                } else {
+                    None
+                };
+                match terminus {
+                    Some(terminus) if inner.contains(terminus) => {
+                        or_terminus = true;
+                        residue_mask(&inner.replace(terminus, ""), s)?
+                    }
+                    _ => residue_mask(inner, s)?,
+                }
+            } else if let Some(inner) = residues.strip_prefix('{').and_then(|r| r.strip_suffix('}'))
+            {
+                ANY_RESIDUE & !residue_mask(inner, s)?
+            } else if residues.len() == 1 {
+                residue_mask(residues, s)?
+            } else {
+                return Err(format_error(s, &format!("bad element {}", token)));
+            };
+            let (min, max) = match repetition {
+                None => (1, 1),
+                Some(repetition) => {
+                    let mut bounds = repetition.split(',').map(|n| n.trim().parse::<usize>());
+                    let min = bounds.next().unwrap()?;
+                    let max = bounds.next().transpose()?.unwrap_or(min);
+                    if bounds.next().is_some() || min > max || max == 0 {
+                        return Err(format_error(s, &format!("bad repetition ({})", repetition)));
+                    }
+                    (min, max)
+                }
+            };
+            if or_terminus && (min, max) != (1, 1) {
+                return Err(format_error(s, "repeated terminus element"));
+            }
+            elements.push(Element {
+                residues,
+                min,
+                max,
+                or_terminus,
+            });
+        }
+        Ok(PrositePattern {
+            elements,
+            n_terminal,
+            c_terminal,
+        })
+    }
+}
+
+impl PrositePattern {
+    /// End of the longest match starting at `start`, if any.
+    /// Tracks the set of positions each prefix of the pattern can end at,
+    /// so variable repetitions never cause backtracking.
+    fn match_at(&self, sequence: &[u8], start: usize) -> Option<usize> {
+        let last = self.elements.len() - 1;
+        let mut ends = vec![start];
+        for (index, element) in self.elements.iter().enumerate() {
+            let mut next_ends = Vec::new();
+            for &position in &ends {
+                if element.or_terminus
+                    && ((index == 0 && position == 0)
+                        || (index == last && position == sequence.len()))
+                {
+                    next_ends.push(position);
+                }
+                let run = sequence[position..]
+                    .iter()
+                    .take(element.max)
+                    .take_while(|residue| element.matches(**residue))
+                    .count();
+                if run >= element.min {
+                    next_ends.extend((element.min..=run).map(|length| position + length));
+                }
+            }
+            next_ends.sort_unstable();
+            next_ends.dedup();
+            if next_ends.is_empty() {
+                return None;
+            }
+            ends = next_ends;
+        }
+        if self.c_terminal {
+            ends.retain(|end| *end == sequence.len());
+        }
+        ends.last().copied()
+    }
+
+    /// All (possibly overlapping) hits in a protein sequence: one per start position,
+    /// with the longest match from there
+    pub fn find_all(&self, sequence: &[u8]) -> Vec<Hit> {
+        let last_start = if self.n_terminal { 0 } else { sequence.len() };
+        (0..=last_start)
+            .filter_map(|start| self.match_at(sequence, start).map(|end| Hit { start, end }))
+            .filter(|hit| hit.end > hit.start)
+            .collect()
+    }
+}
+
+/// A named PROSITE motif
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Motif {
+    pub accession: &'static str,
+    pub name: &'static str,
+    pub pattern: &'static str,
+}
+
+impl Motif {
+    pub fn compile(&self) -> PrositePattern {
+        self.pattern.parse().expect("bundled motifs are valid")
+    }
+}
+
+/// Common short motifs from PROSITE
+pub const COMMON_MOTIFS: &[Motif] = &[
+    Motif {
+        accession: "PS00001",
+        name: "ASN_GLYCOSYLATION",
+        pattern: "N-{P}-[ST]-{P}.",
+    },
+    Motif {
+        accession: "PS00004",
+        name: "CAMP_PHOSPHO_SITE",
+        pattern: "[RK](2)-x-[ST].",
+    },
+    Motif {
+        accession: "PS00005",
+        name: "PKC_PHOSPHO_SITE",
+        pattern: "[ST]-x-[RK].",
+    },
+    Motif {
+        accession: "PS00006",
+        name: "CK2_PHOSPHO_SITE",
+        pattern: "[ST]-x(2)-[DE].",
+    },
+    Motif {
+        accession: "PS00007",
+        name: "TYR_PHOSPHO_SITE",
+        pattern: "[RK]-x(2,3)-[DE]-x(2,3)-Y.",
+    },
+    Motif {
+        accession: "PS00008",
+        name: "MYRISTYL",
+        pattern: "G-{EDRKHPFYW}-x(2)-[STAGCN]-{P}.",
+    },
+    Motif {
+        accession: "PS00009",
+        name: "AMIDATION",
+        pattern: "x-G-[RK]-[RK].",
+    },
+    Motif {
+        accession: "PS00014",
+        name: "ER_TARGET",
+        pattern: "[KRHQSA]-[DENQ]-E-L>.",
+    },
+    Motif {
+        accession: "PS00016",
+        name: "RGD",
+        pattern: "R-G-D.",
+    },
+    Motif {
+        accession: "PS00017",
+        name: "ATP_GTP_A",
+        pattern: "[AG]-x(4)-G-K-[ST].",
+    },
+    Motif {
+        accession: "PS00028",
+        name: "ZINC_FINGER_C2H2_1",
+        pattern: "C-x(2,4)-C-x(3)-[LIVMFYWC]-x(8)-H-x(3,5)-H.",
+    },
+    Motif {
+        accession: "PS00029",
+        name: "LEUCINE_ZIPPER",
+        pattern: "L-x(6)-L-x(6)-L-x(6)-L.",
+    },
+];
+
+/// The bundled motif with a PROSITE accession, e.g. "PS00001"
+pub fn common_motif(accession: &str) -> Option<&'static Motif> {
+    COMMON_MOTIFS
+        .iter()
+        .find(|motif| motif.accession == accession)
+}
+
+/// Hits of each pattern (with its name) in each protein of a FASTA file,
+/// as (sequence ID, pattern name, hit), sorted
+pub fn scan_fasta(
+    filename: &Path,
+    patterns: &[(&str, PrositePattern)],
+) -> Result<Vec<(String, String, Hit)>, Error> {
+    let mut hits = Vec::new();
+    for (id, sequence) in utility::io::read_fasta_file(filename)? {
+        for (name, pattern) in patterns {
+            for hit in pattern.find_all(sequence.as_bytes()) {
+                hits.push((id.clone(), name.to_string(), hit));
+            }
+        }
+    }
+    hits.sort();
+    Ok(hits)
+}
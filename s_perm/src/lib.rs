@@ -1,6 +1,7 @@
 use anyhow::Error;
 
 use std::path::Path;
+use utility::enumeration::HeapPermutations;
 
 /// Enumerating Gene Orders
 ///
@@ -13,44 +14,27 @@ pub fn rosalind_perm(filename: &Path) -> Result<(usize, Vec<Vec<usize>>), Error>
     let n = input.parse::<usize>()?;
     let total: usize = (1..=n).product();
     let mut output = Vec::with_capacity(total);
-    let mut array = (1..=n).collect::<Vec<_>>();
     println!("{}", total);
-    for permutation in get_permutations(&mut array) {
+    for permutation in get_permutations((1..=n).collect()) {
         println!("{}", utility::io::format_array(&permutation));
         output.push(permutation);
     }
     Ok((total, output))
 }
 
-/// Heap's algorithm
-pub fn get_permutations<T: PartialOrd + Clone>(array: &mut Vec<T>) -> Vec<Vec<T>> {
-    let length = array.len();
-    let mut permutations = Vec::new();
-    let mut c = vec![0; length];
-    permutations.push(array.clone());
-    let mut i = 0;
-    while i < length {
-        if c[i] < i {
-            if i % 2 == 0 {
-                array.swap(0, i);
-            } else {
-                array.swap(c[i], i);
-            }
-            permutations.push(array.clone());
-            c[i] += 1;
-            i = 0;
-        } else {
-            c[i] = 0;
-            i += 1;
-        }
-    }
-    permutations
+/// Every permutation of the items, lazily, with Heap's algorithm
+pub fn get_permutations<T: Clone>(array: Vec<T>) -> HeapPermutations<T> {
+    HeapPermutations::new(array)
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
+    use utility::enumeration::{
+        Enumeration, PartialPermutations, Permutations, SignedPermutations, Subsets,
+        VariableLengthWords, Words,
+    };
     use utility::io::Parseable;
 
     use super::*;
@@ -72,4 +56,107 @@ mod tests {
         );
         Ok(())
     }
+
+    /// Objects in order, each unranking its rank and ranking to it, and shards that
+    /// concatenate to the whole enumeration
+    fn check_enumeration<E: Enumeration>(enumeration: &E, expected: &[E::Item])
+    where
+        E::Item: PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(enumeration.size(), expected.len() as u128);
+        assert_eq!(enumeration.iter().collect::<Vec<_>>(), expected);
+        for (rank, item) in expected.iter().enumerate() {
+            assert_eq!(enumeration.unrank(rank as u128), *item);
+            assert_eq!(enumeration.rank(item), Some(rank as u128));
+        }
+        for shards in 1..5 {
+            let sharded: Vec<_> = (0..shards)
+                .flat_map(|index| enumeration.shard(index, shards))
+                .collect();
+            assert_eq!(sharded, expected);
+        }
+        assert_eq!(
+            enumeration.iter_range(2..4).collect::<Vec<_>>(),
+            expected[2.min(expected.len())..4.min(expected.len())]
+        );
+    }
+
+    #[test]
+    fn enumerations() {
+        let symbols = vec!['D', 'N', 'A'];
+        let index = |c: &char| symbols.iter().position(|s| s == c).unwrap();
+        let by_symbol_order =
+            |a: &Vec<char>, b: &Vec<char>| a.iter().map(index).cmp(b.iter().map(index));
+
+        let mut words: Vec<_> = (0..27)
+            .map(|i| vec![symbols[i / 9], symbols[i / 3 % 3], symbols[i % 3]])
+            .collect();
+        words.sort_by(by_symbol_order);
+        check_enumeration(&Words::new(symbols.clone(), 3), &words);
+        assert_eq!(
+            Words::new(symbols.clone(), 3).rank(&vec!['D', 'X', 'A']),
+            None
+        );
+
+        let mut variable_length: Vec<_> = (1..=3)
+            .flat_map(|length| {
+                Words::new(symbols.clone(), length)
+                    .iter()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        variable_length.sort_by(by_symbol_order);
+        check_enumeration(
+            &VariableLengthWords::new(symbols.clone(), 3),
+            &variable_length,
+        );
+
+        let mut partial_permutations: Vec<_> = Words::new((1..=5).collect(), 3)
+            .iter()
+            .filter(|word| word[0] != word[1] && word[0] != word[2] && word[1] != word[2])
+            .collect();
+        partial_permutations.sort();
+        check_enumeration(&PartialPermutations::new(5, 3), &partial_permutations);
+
+        let mut permutations: Vec<_> = get_permutations((1..=5).collect()).collect();
+        assert_eq!(permutations.len(), 120);
+        permutations.sort();
+        check_enumeration(&Permutations::new(5), &permutations);
+        check_enumeration(&Permutations::new(0), &[vec![]]);
+
+        let signs: Vec<_> = Words::new(vec![1, -1], 3).iter().collect();
+        let signed_permutations: Vec<Vec<i64>> = Permutations::new(3)
+            .iter()
+            .flat_map(|permutation| {
+                signs.iter().map(move |signs| {
+                    permutation
+                        .iter()
+                        .zip(signs)
+                        .map(|(x, sign)| *x as i64 * sign)
+                        .collect()
+                })
+            })
+            .collect();
+        check_enumeration(&SignedPermutations::new(3), &signed_permutations);
+
+        let subsets: Vec<Vec<usize>> = (0..16)
+            .map(|mask: usize| (1..=4).filter(|i| mask >> (i - 1) & 1 == 1).collect())
+            .collect();
+        check_enumeration(&Subsets::new(4), &subsets);
+        assert_eq!(Subsets::new(4).rank(&vec![3, 1]), None);
+
+        // ranks beyond u64
+        let words = Words::new(vec![0u8, 1], 100);
+        let rank = (1u128 << 99) + 12345;
+        assert_eq!(words.rank(&words.unrank(rank)), Some(rank));
+        let permutations = Permutations::new(30);
+        let last = permutations.unrank(permutations.size() - 1);
+        assert_eq!(last, (1..=30).rev().collect::<Vec<_>>());
+        assert_eq!(
+            permutations.shard(3, 4).next(),
+            Some(
+                permutations.unrank(permutations.size() / 4 * 3 + permutations.size() % 4 * 3 / 4)
+            )
+        );
+    }
 }
@@ -2,6 +2,7 @@ use anyhow::Error;
 use num::bigint::BigUint;
 
 use std::path::Path;
+use utility::enumeration::{Enumeration, PartialPermutations};
 use utility::io::Parseable;
 
 /// Partial Permutations
@@ -12,12 +13,23 @@ use utility::io::Parseable;
 pub fn rosalind_pper(filename: &Path) -> Result<BigUint, Error> {
     let contents = u64::parse_line(&utility::io::input_from_file(filename)?)?;
     let (n, k) = (contents[0], contents[1]);
-    let result = (utility::math::ncr(n, k) * utility::math::factorial(k as usize))
-        % BigUint::from(1_000_000u64);
+    let result = count_partial_permutations(n, k) % BigUint::from(1_000_000u64);
     println!("{}", result);
     Ok(result)
 }
 
+/// P(n, k), exactly, as it overflows a u128 for n = 100 and k >= 20
+pub fn count_partial_permutations(n: u64, k: u64) -> BigUint {
+    utility::math::ncr(n, k) * utility::math::factorial(k as usize)
+}
+
+/// Ordered arrangements of k numbers from 1..=n, lazily in lexicographic order
+pub fn enumerate_partial_permutations(n: usize, k: usize) -> impl Iterator<Item = Vec<usize>> {
+    let partial_permutations = PartialPermutations::new(n, k);
+    let size = partial_permutations.size();
+    partial_permutations.into_iter_range(0..size)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -29,4 +41,33 @@ mod tests {
         assert_eq!(rosalind_pper(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn count_pper() {
+        let million = BigUint::from(1_000_000u64);
+        assert_eq!(
+            count_partial_permutations(100, 10) % &million,
+            BigUint::from(472_000u64)
+        );
+        let large = count_partial_permutations(100, 40);
+        assert_eq!(large, (61..=100u64).map(BigUint::from).product());
+        assert!(large > BigUint::from(u128::MAX));
+        assert_eq!(large % million, BigUint::from(0u64));
+    }
+
+    #[test]
+    fn enumerate_pper() {
+        let partial_permutations: Vec<_> = enumerate_partial_permutations(3, 2).collect();
+        assert_eq!(
+            partial_permutations,
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 1],
+                vec![2, 3],
+                vec![3, 1],
+                vec![3, 2]
+            ]
+        );
+    }
 }
@@ -1,9 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use anyhow::Error;
 
 use std::path::Path;
 
+use crate::repeats::{find_palindromes, PalindromeParams};
+
+pub mod repeats;
+
 /// Locating Restriction Sites
 ///
 /// Given: A DNA string of length at most 1 kbp in FASTA format.
@@ -11,27 +15,25 @@ use std::path::Path;
 /// Return: The position and length of every reverse palindrome in the string having length
 /// between 4 and 12. You may return these pairs in any order.
 pub fn rosalind_revp(filename: &Path) -> Result<HashSet<(usize, usize)>, Error> {
-    let nucleotide_map: HashMap<_, _> = "ATCG".chars().zip("TAGC".chars()).collect();
     let fasta = utility::io::read_fasta_file(filename)?;
     let dna = fasta
         .values()
         .next()
-        .ok_or(utility::errors::RosalindOutputError::NoneError)?
-        .chars()
-        .collect::<Vec<_>>();
-    let mut output = HashSet::new();
-    for i in 0..dna.len() {
-        for length in 4..=12 {
-            if i + length > dna.len() {
-                continue;
-            }
-            if dna[i] == nucleotide_map[&dna[i + length - 1]]
-                && is_reverse_palindrome(&dna[(i + 1)..(i + length - 1)], &nucleotide_map)
-            {
-                output.insert((i + 1, length));
-            }
-        }
-    }
+        .ok_or(utility::errors::RosalindOutputError::NoneError)?;
+    let params = PalindromeParams {
+        min_arm_length: 2,
+        max_arm_length: Some(6),
+        ..Default::default()
+    };
+    // every palindrome nested inside a maximal one around the same center is also one
+    let output: HashSet<_> = find_palindromes(dna.as_bytes(), &params)
+        .into_iter()
+        .flat_map(|palindrome| {
+            let center = palindrome.start + palindrome.arm_length;
+            (params.min_arm_length..=palindrome.arm_length)
+                .map(move |arm_length| (center - arm_length + 1, 2 * arm_length))
+        })
+        .collect();
     println!(
         "{}",
         output
@@ -43,19 +45,6 @@ pub fn rosalind_revp(filename: &Path) -> Result<HashSet<(usize, usize)>, Error>
     Ok(output)
 }
 
-/// Checks if a dna string is a reverse palindrome
-fn is_reverse_palindrome(dna: &[char], nucleotide_map: &HashMap<char, char>) -> bool {
-    if dna.len() % 2 != 0 {
-        return false;
-    }
-    for i in 0..(dna.len() / 2) {
-        if dna[i] != nucleotide_map[&dna[dna.len() - i - 1]] {
-            return false;
-        }
-    }
-    true
-}
-
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -77,4 +66,157 @@ mod tests {
         assert_eq!(rosalind_revp(&input_file)?, output);
         Ok(())
     }
+
+    #[test]
+    fn repeats() {
+        use crate::repeats::*;
+
+        // GAATTC hairpin with a 3-base loop and one mismatched pair
+        let hairpin = b"TTGAGTCAAAGACTGAA";
+        let params = PalindromeParams {
+            min_arm_length: 4,
+            max_mismatches: 1,
+            max_spacer: 3,
+            ..Default::default()
+        };
+        assert!(find_palindromes(hairpin, &params).contains(&Palindrome {
+            start: 0,
+            end: 17,
+            arm_length: 7,
+            spacer: 3,
+            mismatches: 1,
+        }));
+        assert!(find_palindromes(
+            hairpin,
+            &PalindromeParams {
+                max_mismatches: 0,
+                ..params
+            }
+        )
+        .iter()
+        .all(|p| p.mismatches == 0 && p.arm_length < 7));
+
+        let microsatellite = b"GGCACACACACACAGTCACACTTT";
+        let repeats = find_tandem_repeats(
+            microsatellite,
+            &TandemRepeatParams {
+                min_period: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(repeats.len(), 1);
+        assert_eq!(
+            (repeats[0].start, repeats[0].end, repeats[0].unit.as_str()),
+            (2, 14, "CA")
+        );
+        assert_eq!(repeats[0].copies, 6.);
+        let impure = find_tandem_repeats(
+            b"ACGACGACGTCGACGACG",
+            &TandemRepeatParams {
+                min_purity: 0.8,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            (impure[0].start, impure[0].end, impure[0].period),
+            (0, 18, 3)
+        );
+        assert!(impure[0].purity < 1.);
+
+        let dna = "AAGGCTTACCTTTTGGTAAGCCAAGGCTTACC";
+        let direct = find_direct_repeats(dna, 8);
+        assert_eq!(
+            direct,
+            vec![RepeatPair {
+                kind: RepeatKind::Direct,
+                first: 0,
+                second: 22,
+                length: 10,
+            }]
+        );
+        let inverted = find_inverted_repeats(dna, 8);
+        assert!(inverted.contains(&RepeatPair {
+            kind: RepeatKind::Inverted,
+            first: 2,
+            second: 14,
+            length: 8,
+        }));
+        let gff = format_gff("seq", &[&direct[0], &repeats[0]]);
+        assert_eq!(
+            gff.lines().nth(1),
+            Some("seq\trosalind\tdirect_repeat\t1\t32\t.\t.\t.\tfirst=1;second=23;length=10")
+        );
+    }
+
+    #[test]
+    fn repeat_pairs() {
+        use crate::repeats::*;
+
+        // every pair of the three copies of ACGT, not just those adjacent in the suffix array
+        let direct = find_direct_repeats("ACGTTACGTAACGTC", 4);
+        let spans: Vec<_> = direct
+            .iter()
+            .map(|pair| (pair.first, pair.second, pair.length))
+            .collect();
+        assert_eq!(spans, vec![(0, 5, 4), (0, 10, 4), (5, 10, 4)]);
+
+        for seed in 0..20 {
+            let dna = utility::testing::random_sequence(40 + seed as usize, b"ACGT", seed);
+            let text = dna.as_bytes();
+            let mut expected = Vec::new();
+            for first in 0..text.len() {
+                for second in first + 1..text.len() {
+                    let length = text[first..]
+                        .iter()
+                        .zip(&text[second..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    if length >= 3 && (first == 0 || text[first - 1] != text[second - 1]) {
+                        expected.push((first, second, length));
+                    }
+                }
+            }
+            let found: Vec<_> = find_direct_repeats(&dna, 3)
+                .into_iter()
+                .map(|pair| (pair.first, pair.second, pair.length))
+                .collect();
+            assert_eq!(found, expected, "{}", dna);
+        }
+    }
+
+    #[test]
+    fn annotate_fasta() -> Result<(), Error> {
+        use crate::repeats::*;
+
+        let fasta_file =
+            std::env::temp_dir().join(format!("s_revp_test_{}.fasta", std::process::id()));
+        std::fs::write(&fasta_file, ">seq2\nACGTTACG\nTAACGTC\n>seq1\nTTTTTTTT\n")?;
+        let gff = annotate_fasta(
+            &fasta_file,
+            &PalindromeParams {
+                min_arm_length: 3,
+                ..Default::default()
+            },
+            &TandemRepeatParams::default(),
+            4,
+        );
+        std::fs::remove_file(&fasta_file)?;
+        // sequences in order of ID, features in order of position
+        let expected = [
+            "##gff-version 3",
+            "seq1\trosalind\ttandem_repeat\t1\t8\t1.000\t.\t.\tperiod=1;unit=T;copies=8.0",
+            "seq1\trosalind\tdirect_repeat\t1\t8\t.\t.\t.\tfirst=1;second=2;length=7",
+            "seq1\trosalind\tdirect_repeat\t1\t8\t.\t.\t.\tfirst=1;second=3;length=6",
+            "seq1\trosalind\tdirect_repeat\t1\t8\t.\t.\t.\tfirst=1;second=4;length=5",
+            "seq1\trosalind\tdirect_repeat\t1\t8\t.\t.\t.\tfirst=1;second=5;length=4",
+            "seq2\trosalind\tdirect_repeat\t1\t9\t.\t.\t.\tfirst=1;second=6;length=4",
+            "seq2\trosalind\tinverted_repeat\t1\t9\t.\t.\t.\tfirst=1;second=6;length=4",
+            "seq2\trosalind\tinverted_repeat\t1\t14\t.\t.\t.\tarm_length=7;spacer=0;mismatches=0",
+            "seq2\trosalind\tdirect_repeat\t1\t14\t.\t.\t.\tfirst=1;second=11;length=4",
+            "seq2\trosalind\tdirect_repeat\t6\t14\t.\t.\t.\tfirst=6;second=11;length=4",
+            "seq2\trosalind\tinverted_repeat\t6\t14\t.\t.\t.\tfirst=6;second=11;length=4",
+        ];
+        assert_eq!(gff?.lines().collect::<Vec<_>>(), expected);
+        Ok(())
+    }
 }
@@ -0,0 +1,489 @@
+//! Repeats in a DNA string: reverse-complement palindromes (optionally with mismatches and a
+//! hairpin spacer), tandem repeats, and pairs of direct or inverted repeats.
+//! All positions are 0-based, end-exclusive; `to_gff` gives 1-based GFF3 lines for annotation.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::Error;
+
+use t_ba9g::SuffixArray;
+
+fn complement(nucleotide: u8) -> u8 {
+    match nucleotide.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        // never pairs with anything
+        _ => 0,
+    }
+}
+
+fn is_pair(a: u8, b: u8) -> bool {
+    complement(a) != 0 && complement(a) == b.to_ascii_uppercase()
+}
+
+/// A repeat that can be written as a GFF3 feature
+pub trait GffFeature {
+    /// 0-based start and end-exclusive end
+    fn span(&self) -> (usize, usize);
+    /// Sequence Ontology term
+    fn feature_type(&self) -> &'static str;
+    fn score(&self) -> Option<f64> {
+        None
+    }
+    fn attributes(&self) -> Vec<(&'static str, String)>;
+
+    fn to_gff(&self, sequence_id: &str) -> String {
+        let (start, end) = self.span();
+        let attributes: Vec<_> = self
+            .attributes()
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        format!(
+            "{}\trosalind\t{}\t{}\t{}\t{}\t.\t.\t{}",
+            sequence_id,
+            self.feature_type(),
+            start + 1,
+            end,
+            self.score()
+                .map_or(String::from("."), |s| format!("{:.3}", s)),
+            attributes.join(";")
+        )
+    }
+}
+
+/// GFF3 document with one line per feature
+pub fn format_gff(sequence_id: &str, features: &[&dyn GffFeature]) -> String {
+    let mut gff = String::from("##gff-version 3\n");
+    for feature in features {
+        writeln!(gff, "{}", feature.to_gff(sequence_id)).unwrap();
+    }
+    gff
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PalindromeParams {
+    /// shortest arm, i.e. half of the length of a palindrome without spacer
+    pub min_arm_length: usize,
+    pub max_arm_length: Option<usize>,
+    pub max_mismatches: usize,
+    /// longest unpaired loop between the arms
+    pub max_spacer: usize,
+}
+
+impl Default for PalindromeParams {
+    fn default() -> Self {
+        PalindromeParams {
+            min_arm_length: 2,
+            max_arm_length: None,
+            max_mismatches: 0,
+            max_spacer: 0,
+        }
+    }
+}
+
+/// `dna[start..start + arm_length]` pairs with the reverse of
+/// `dna[end - arm_length..end]`, with `spacer` unpaired bases between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Palindrome {
+    pub start: usize,
+    pub end: usize,
+    pub arm_length: usize,
+    pub spacer: usize,
+    pub mismatches: usize,
+}
+
+impl GffFeature for Palindrome {
+    fn span(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    fn feature_type(&self) -> &'static str {
+        "inverted_repeat"
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("arm_length", self.arm_length.to_string()),
+            ("spacer", self.spacer.to_string()),
+            ("mismatches", self.mismatches.to_string()),
+        ]
+    }
+}
+
+/// Maximal reverse-complement palindromes: for each center and spacer length, the longest arms
+/// (up to the maximum) with at most `max_mismatches` unpaired bases, ending on a pair.
+/// Takes O(n · spacers · arm length).
+pub fn find_palindromes(dna: &[u8], params: &PalindromeParams) -> Vec<Palindrome> {
+    let max_arm_length = params.max_arm_length.unwrap_or(dna.len());
+    let mut palindromes = Vec::new();
+    for spacer in 0..=params.max_spacer {
+        // the left arm ends at `center`, the right one starts at `center + spacer`
+        for center in 1..dna.len() {
+            if center + spacer >= dna.len() {
+                break;
+            }
+            let (mut arm_length, mut mismatches) = (0, 0);
+            let (mut best_arm_length, mut best_mismatches) = (0, 0);
+            while arm_length < max_arm_length
+                && arm_length < center
+                && center + spacer + arm_length < dna.len()
+            {
+                let (left, right) = (
+                    dna[center - arm_length - 1],
+                    dna[center + spacer + arm_length],
+                );
+                arm_length += 1;
+                if is_pair(left, right) {
+                    best_arm_length = arm_length;
+                    best_mismatches = mismatches;
+                } else {
+                    mismatches += 1;
+                    if mismatches > params.max_mismatches {
+                        break;
+                    }
+                }
+            }
+            // a palindrome with spacer is only reported if the spacer can't pair up
+            let closed = spacer < 2 || !is_pair(dna[center], dna[center + spacer - 1]);
+            if best_arm_length >= params.min_arm_length.max(1) && closed {
+                palindromes.push(Palindrome {
+                    start: center - best_arm_length,
+                    end: center + spacer + best_arm_length,
+                    arm_length: best_arm_length,
+                    spacer,
+                    mismatches: best_mismatches,
+                });
+            }
+        }
+    }
+    palindromes.sort_unstable();
+    palindromes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TandemRepeatParams {
+    pub min_period: usize,
+    pub max_period: usize,
+    /// fewest copies of the unit, counting a partial last copy as a fraction
+    pub min_copies: f64,
+    /// smallest fraction of bases equal to the base one period before them
+    pub min_purity: f64,
+}
+
+impl Default for TandemRepeatParams {
+    fn default() -> Self {
+        TandemRepeatParams {
+            min_period: 1,
+            max_period: 6,
+            min_copies: 3.,
+            min_purity: 0.9,
+        }
+    }
+}
+
+/// Microsatellite `dna[start..end]` made of copies of `unit`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TandemRepeat {
+    pub start: usize,
+    pub end: usize,
+    pub period: usize,
+    pub unit: String,
+    pub copies: f64,
+    pub purity: f64,
+}
+
+impl GffFeature for TandemRepeat {
+    fn span(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    fn feature_type(&self) -> &'static str {
+        "tandem_repeat"
+    }
+
+    fn score(&self) -> Option<f64> {
+        Some(self.purity)
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("period", self.period.to_string()),
+            ("unit", self.unit.clone()),
+            ("copies", format!("{:.1}", self.copies)),
+        ]
+    }
+}
+
+/// Whether `unit` is a power of a shorter string
+fn is_periodic(unit: &[u8]) -> bool {
+    (1..unit.len())
+        .filter(|period| unit.len().is_multiple_of(*period))
+        .any(|period| (period..unit.len()).all(|i| unit[i] == unit[i - period]))
+}
+
+/// Maximal tandem repeats whose unit isn't itself periodic. For each period p, runs of positions
+/// i with dna[i] == dna[i + p] are joined across mismatches while the purity allows it.
+pub fn find_tandem_repeats(dna: &[u8], params: &TandemRepeatParams) -> Vec<TandemRepeat> {
+    let mut repeats = Vec::new();
+    for period in params.min_period.max(1)..=params.max_period {
+        if period >= dna.len() {
+            break;
+        }
+        let comparisons = dna.len() - period;
+        let matches: Vec<_> = (0..comparisons)
+            .map(|i| dna[i].eq_ignore_ascii_case(&dna[i + period]))
+            .collect();
+        // runs of matching comparisons as [start, end)
+        let mut runs = Vec::new();
+        let mut i = 0;
+        while i < comparisons {
+            if matches[i] {
+                let start = i;
+                while i < comparisons && matches[i] {
+                    i += 1;
+                }
+                runs.push((start, i));
+            }
+            i += 1;
+        }
+        let mut run_index = 0;
+        while run_index < runs.len() {
+            let (start, mut end) = runs[run_index];
+            let mut matched = end - start;
+            run_index += 1;
+            while let Some((next_start, next_end)) = runs.get(run_index) {
+                let merged = matched + next_end - next_start;
+                if (merged as f64) < params.min_purity * (next_end - start) as f64 {
+                    break;
+                }
+                matched = merged;
+                end = *next_end;
+                run_index += 1;
+            }
+            let length = end - start + period;
+            let unit = &dna[start..start + period];
+            let copies = length as f64 / period as f64;
+            let purity = matched as f64 / (end - start) as f64;
+            if copies >= params.min_copies && purity >= params.min_purity && !is_periodic(unit) {
+                repeats.push(TandemRepeat {
+                    start,
+                    end: start + length,
+                    period,
+                    unit: String::from_utf8_lossy(unit).to_ascii_uppercase(),
+                    copies,
+                    purity,
+                });
+            }
+        }
+    }
+    repeats.sort_by_key(|repeat| (repeat.start, repeat.period));
+    repeats
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RepeatKind {
+    /// the second copy reads the same as the first
+    Direct,
+    /// the second copy is the reverse complement of the first
+    Inverted,
+}
+
+/// Two copies `dna[first..first + length]` and `dna[second..second + length]`, with `first <= second`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RepeatPair {
+    pub kind: RepeatKind,
+    pub first: usize,
+    pub second: usize,
+    pub length: usize,
+}
+
+impl GffFeature for RepeatPair {
+    fn span(&self) -> (usize, usize) {
+        (self.first, self.second + self.length)
+    }
+
+    fn feature_type(&self) -> &'static str {
+        match self.kind {
+            RepeatKind::Direct => "direct_repeat",
+            RepeatKind::Inverted => "inverted_repeat",
+        }
+    }
+
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("first", (self.first + 1).to_string()),
+            ("second", (self.second + 1).to_string()),
+            ("length", self.length.to_string()),
+        ]
+    }
+}
+
+/// Suffixes below a node of the LCP interval tree, grouped by the character before them
+/// (None for the suffix at position 0)
+#[derive(Debug, Default)]
+struct LeftGroups {
+    groups: BTreeMap<Option<u8>, Vec<usize>>,
+    count: usize,
+}
+
+impl LeftGroups {
+    fn leaf(text: &[u8], position: usize) -> Self {
+        let mut groups = BTreeMap::new();
+        groups.insert(position.checked_sub(1).map(|p| text[p]), vec![position]);
+        LeftGroups { groups, count: 1 }
+    }
+
+    /// Join the suffixes of another child of a node whose suffixes share a prefix of `length`,
+    /// first pairing them up with this node's suffixes when the preceding characters differ
+    fn merge(
+        &mut self,
+        mut other: LeftGroups,
+        length: usize,
+        min_length: usize,
+        pairs: &mut Vec<(usize, usize, usize)>,
+    ) {
+        // the smaller side is moved, so each suffix is moved O(log n) times
+        if self.count < other.count {
+            std::mem::swap(self, &mut other);
+        }
+        if length >= min_length {
+            for (left, positions) in &other.groups {
+                for (other_left, other_positions) in &self.groups {
+                    if left.is_none() || left != other_left {
+                        for a in positions {
+                            for b in other_positions {
+                                pairs.push((*a.min(b), *a.max(b), length));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.count += other.count;
+        for (left, positions) in other.groups {
+            self.groups.entry(left).or_default().extend(positions);
+        }
+    }
+}
+
+/// Every maximal pair of repeats of at least `min_length`, as (first, second, length):
+/// the suffixes at first and second share exactly `length` characters (so the pair is
+/// right-maximal) and are preceded by different characters (left-maximal). Each pair is
+/// reported at the LCP interval of its common prefix, in O(n log n + pairs) time.
+fn maximal_pairs(suffix_array: &SuffixArray, min_length: usize) -> Vec<(usize, usize, usize)> {
+    let text = suffix_array.text.as_bytes();
+    let n = text.len();
+    let min_length = min_length.max(1);
+    let mut pairs = Vec::new();
+    if n == 0 {
+        return pairs;
+    }
+    // open LCP intervals with the suffixes of their children so far
+    let mut stack: Vec<(usize, LeftGroups)> = vec![(0, LeftGroups::default())];
+    // the leaf or closed interval just before the current rank
+    let mut child = LeftGroups::leaf(text, suffix_array.suffix_array[0]);
+    for rank in 1..=n {
+        let lcp = if rank < n {
+            suffix_array.lcp_array[rank]
+        } else {
+            0
+        };
+        while stack.last().unwrap().0 > lcp {
+            let (length, mut groups) = stack.pop().unwrap();
+            groups.merge(std::mem::take(&mut child), length, min_length, &mut pairs);
+            child = groups;
+        }
+        let (length, groups) = stack.last_mut().unwrap();
+        if *length < lcp {
+            stack.push((lcp, std::mem::take(&mut child)));
+        } else {
+            groups.merge(std::mem::take(&mut child), *length, min_length, &mut pairs);
+        }
+        if rank < n {
+            child = LeftGroups::leaf(text, suffix_array.suffix_array[rank]);
+        }
+    }
+    pairs
+}
+
+/// Maximal pairs of direct repeats of at least `min_length`: every pair of copies that can't
+/// be extended on either side, so a repeat with k copies gives k(k - 1) / 2 pairs
+pub fn find_direct_repeats(dna: &str, min_length: usize) -> Vec<RepeatPair> {
+    let suffix_array = SuffixArray::construct(&dna.to_ascii_uppercase());
+    let mut pairs: Vec<_> = maximal_pairs(&suffix_array, min_length)
+        .into_iter()
+        .map(|(first, second, length)| RepeatPair {
+            kind: RepeatKind::Direct,
+            first,
+            second,
+            length,
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Maximal pairs of inverted repeats of at least `min_length`, found as direct repeats between
+/// the string and its reverse complement
+pub fn find_inverted_repeats(dna: &str, min_length: usize) -> Vec<RepeatPair> {
+    let n = dna.len();
+    let text = format!(
+        "{}#{}",
+        dna.to_ascii_uppercase(),
+        s_revc::reverse_complement(dna)
+    );
+    let suffix_array = SuffixArray::construct(&text);
+    let mut pairs: Vec<_> = maximal_pairs(&suffix_array, min_length)
+        .into_iter()
+        .filter(|(first, second, _)| *first < n && *second > n)
+        .map(|(first, second, length)| {
+            // position in the reverse complement to the forward strand
+            let second = 2 * n + 1 - second - length;
+            RepeatPair {
+                kind: RepeatKind::Inverted,
+                first: first.min(second),
+                second: first.max(second),
+                length,
+            }
+        })
+        // a string that is its own reverse complement is a palindrome, not a pair
+        .filter(|pair| pair.first != pair.second)
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+/// GFF3 annotation of the palindromes, tandem repeats and repeat pairs (of at least
+/// `min_repeat_length`) in each sequence of a FASTA file
+pub fn annotate_fasta(
+    filename: &Path,
+    palindrome_params: &PalindromeParams,
+    tandem_repeat_params: &TandemRepeatParams,
+    min_repeat_length: usize,
+) -> Result<String, Error> {
+    let mut sequences: Vec<_> = utility::io::read_fasta_file(filename)?
+        .into_iter()
+        .collect();
+    sequences.sort();
+    let mut gff = String::from("##gff-version 3\n");
+    for (sequence_id, dna) in sequences {
+        let palindromes = find_palindromes(dna.as_bytes(), palindrome_params);
+        let tandem_repeats = find_tandem_repeats(dna.as_bytes(), tandem_repeat_params);
+        let mut pairs = find_direct_repeats(&dna, min_repeat_length);
+        pairs.extend(find_inverted_repeats(&dna, min_repeat_length));
+        let mut features: Vec<&dyn GffFeature> = Vec::new();
+        features.extend(palindromes.iter().map(|p| p as &dyn GffFeature));
+        features.extend(tandem_repeats.iter().map(|r| r as &dyn GffFeature));
+        features.extend(pairs.iter().map(|p| p as &dyn GffFeature));
+        features.sort_by_key(|feature| feature.span());
+        for feature in features {
+            writeln!(gff, "{}", feature.to_gff(&sequence_id)).unwrap();
+        }
+    }
+    Ok(gff)
+}
@@ -1,10 +1,9 @@
 use std::collections::HashSet;
 
 use anyhow::Error;
-use itertools::Itertools;
 
-use s_perm::get_permutations;
 use std::path::Path;
+use utility::enumeration::{Enumeration, SignedPermutations};
 
 /// Enumerating Oriented Gene Orderings
 ///
@@ -14,20 +13,15 @@ use std::path::Path;
 /// followed by a list of all such permutations (you may list the signed permutations in any order).
 pub fn rosalind_sign(filename: &Path) -> Result<HashSet<Vec<i64>>, Error> {
     let length = utility::io::input_from_file(filename)?.parse::<usize>()?;
-    let mut array = (1i64..=length as i64).collect::<Vec<_>>();
-    let permutations = get_permutations(&mut array);
-    let signs = vec![-1i64, 1];
-    let number = (1usize..=length).product::<usize>() * 2usize.pow(length as u32);
+    let signed_permutations = SignedPermutations::new(length);
+    let number = signed_permutations.size() as usize;
     println!("{}", number);
-    let mut signed_permutations = HashSet::with_capacity(number);
-    for permutation in permutations {
-        for sign in (0..length).map(|_| signs.iter()).multi_cartesian_product() {
-            let permutation: Vec<_> = permutation.iter().zip(sign).map(|(x, y)| x * y).collect();
-            println!("{}", utility::io::format_array(&permutation));
-            signed_permutations.insert(permutation);
-        }
+    let mut output = HashSet::with_capacity(number);
+    for permutation in signed_permutations.iter() {
+        println!("{}", utility::io::format_array(&permutation));
+        output.insert(permutation);
     }
-    Ok(signed_permutations)
+    Ok(output)
 }
 
 #[cfg(test)]
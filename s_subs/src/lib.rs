@@ -2,6 +2,8 @@ use anyhow::Error;
 
 use std::path::Path;
 
+use s_kmp::{Kmp, Matcher};
+
 /// Finding a motif in DNA
 ///
 /// Given: Two DNA strings s and t (each of length at most 1 kbp).
@@ -16,7 +18,8 @@ pub fn rosalind_subs(filename: &Path) -> Result<Vec<usize>, Error> {
     let motif = dna_motif.get(1).ok_or_else(|| {
         utility::errors::RosalindParseError::InputFormatError(String::from("Missing motif"))
     })?;
-    let output = utility::string::find_motifs(motif, dna)
+    let output = Kmp::new(motif.trim().as_bytes())
+        .find_all(dna.trim().as_bytes())
         .into_iter()
         .map(|x| x + 1)
         .collect::<Vec<usize>>();
@@ -0,0 +1,211 @@
+//! Aho-Corasick automaton: a trie with failure links (to the longest proper suffix of a node's
+//! string that is also in the trie) and output links (to the nearest such suffix ending a pattern),
+//! which finds every occurrence of every pattern in one pass over the text.
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::{Trie, TrieNode};
+
+const ROOT: usize = 0;
+
+/// Occurrence of the pattern with value `value` at `start..end` in the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Match<'a, T> {
+    pub value: &'a T,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct AhoCorasick<T, U: Eq + Hash> {
+    goto: Vec<HashMap<U, usize>>,
+    failure: Vec<usize>,
+    /// nearest node along the failure links whose string is a pattern
+    output: Vec<Option<usize>>,
+    values: Vec<Option<T>>,
+    depths: Vec<usize>,
+}
+
+impl<T: Eq + Clone, U: Eq + Hash + Clone> AhoCorasick<T, U> {
+    /// Flatten the trie in breadth-first order and add failure and output links
+    pub fn new(trie: &Trie<T, U>) -> Self {
+        let mut automaton = AhoCorasick {
+            goto: vec![HashMap::new()],
+            failure: vec![ROOT],
+            output: vec![None],
+            values: vec![trie.root.value.clone()],
+            depths: vec![0],
+        };
+        let mut queue: VecDeque<(&TrieNode<T, U>, usize)> = VecDeque::new();
+        queue.push_back((&trie.root, ROOT));
+        while let Some((trie_node, node)) = queue.pop_front() {
+            for (c, trie_child) in &trie_node.children {
+                let child = automaton.goto.len();
+                automaton.goto.push(HashMap::new());
+                automaton.values.push(trie_child.value.clone());
+                automaton.depths.push(automaton.depths[node] + 1);
+                automaton.goto[node].insert(c.clone(), child);
+                // parents are dequeued before children, so their failure links are already set
+                let failure = if node == ROOT {
+                    ROOT
+                } else {
+                    automaton.next_state(automaton.failure[node], c)
+                };
+                automaton.failure.push(failure);
+                automaton
+                    .output
+                    .push(if automaton.values[failure].is_some() {
+                        Some(failure)
+                    } else {
+                        automaton.output[failure]
+                    });
+                queue.push_back((trie_child, child));
+            }
+        }
+        automaton
+    }
+
+    /// Number of states, including the root
+    pub fn num_states(&self) -> usize {
+        self.goto.len()
+    }
+
+    /// Follow failure links until a transition on `c` exists
+    fn next_state(&self, state: usize, c: &U) -> usize {
+        let mut state = state;
+        loop {
+            if let Some(next) = self.goto[state].get(c) {
+                return *next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.failure[state];
+        }
+    }
+
+    /// Patterns ending in `state`, reported as ending at `end`
+    fn matches_at<'a>(&'a self, state: usize, end: usize, matches: &mut Vec<Match<'a, T>>) {
+        let mut node = Some(state);
+        while let Some(current) = node {
+            if let Some(value) = &self.values[current] {
+                matches.push(Match {
+                    value,
+                    start: end - self.depths[current],
+                    end,
+                });
+            }
+            node = self.output[current];
+        }
+    }
+
+    /// Every (possibly overlapping) occurrence of every pattern, ordered by end position
+    pub fn find_all(&self, text: &[U]) -> Vec<Match<'_, T>> {
+        let mut stream = self.stream();
+        stream.feed(text)
+    }
+
+    /// Matcher for text arriving in chunks, which also finds occurrences spanning chunks
+    pub fn stream(&self) -> AhoCorasickStream<'_, T, U> {
+        AhoCorasickStream {
+            automaton: self,
+            state: ROOT,
+            offset: 0,
+        }
+    }
+}
+
+pub struct AhoCorasickStream<'a, T, U: Eq + Hash> {
+    automaton: &'a AhoCorasick<T, U>,
+    state: usize,
+    /// number of symbols consumed so far
+    offset: usize,
+}
+
+impl<'a, T: Eq + Clone, U: Eq + Hash + Clone> AhoCorasickStream<'a, T, U> {
+    /// Scan the next chunk, returning matches ending in it (positions are from the start of the stream)
+    pub fn feed(&mut self, chunk: &[U]) -> Vec<Match<'a, T>> {
+        let mut matches = Vec::new();
+        for c in chunk {
+            self.state = self.automaton.next_state(self.state, c);
+            self.offset += 1;
+            self.automaton
+                .matches_at(self.state, self.offset, &mut matches);
+        }
+        matches
+    }
+
+    /// Start over as if at the beginning of a new text
+    pub fn reset(&mut self) {
+        self.state = ROOT;
+        self.offset = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+/// Occurrence of a DNA pattern on either strand, in forward-strand coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DnaMatch {
+    pub pattern_index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+}
+
+/// Matches many DNA patterns (primers, adapters, restriction sites, ...) at once, optionally also
+/// finding their reverse complements. Reverse complementary palindromes are reported once,
+/// on the forward strand.
+pub struct DnaPatternMatcher {
+    automaton: AhoCorasick<Vec<(usize, Strand)>, char>,
+}
+
+impl DnaPatternMatcher {
+    pub fn new(patterns: &[&str], both_strands: bool) -> Self {
+        let mut trie: Trie<Vec<(usize, Strand)>, char> = Trie::new();
+        let mut add = |pattern: &str, value| {
+            let pattern: Vec<_> = pattern.chars().collect();
+            let mut values = trie.find(&pattern).unwrap_or_default();
+            values.push(value);
+            trie.insert(&pattern, values);
+        };
+        for (pattern_index, pattern) in patterns.iter().enumerate() {
+            add(pattern, (pattern_index, Strand::Forward));
+            let reverse_complement = s_revc::reverse_complement(pattern);
+            if both_strands && reverse_complement != *pattern {
+                add(&reverse_complement, (pattern_index, Strand::Reverse));
+            }
+        }
+        DnaPatternMatcher {
+            automaton: AhoCorasick::new(&trie),
+        }
+    }
+
+    pub fn find_all(&self, text: &str) -> Vec<DnaMatch> {
+        let mut stream = self.automaton.stream();
+        self.resolve(stream.feed(&text.chars().collect::<Vec<_>>()))
+    }
+
+    pub fn stream(&self) -> AhoCorasickStream<'_, Vec<(usize, Strand)>, char> {
+        self.automaton.stream()
+    }
+
+    /// Convert the matches of a stream from `stream` into pattern indices and strands
+    pub fn resolve(&self, matches: Vec<Match<'_, Vec<(usize, Strand)>>>) -> Vec<DnaMatch> {
+        matches
+            .into_iter()
+            .flat_map(|m| {
+                m.value.iter().map(move |(pattern_index, strand)| DnaMatch {
+                    pattern_index: *pattern_index,
+                    start: m.start,
+                    end: m.end,
+                    strand: *strand,
+                })
+            })
+            .collect()
+    }
+}
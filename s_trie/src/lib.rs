@@ -4,6 +4,8 @@ use std::path::Path;
 
 use anyhow::Error;
 
+pub mod aho_corasick;
+
 /// Introduction to Pattern Matching
 ///
 /// Given: A list of at most 100 DNA strings of length at most 100 bp, none of which is a prefix of another.
@@ -64,18 +66,13 @@ impl<T: Eq + Clone, U: Eq + Hash + Clone> Trie<T, U> {
         }
     }
 
-    #[allow(dead_code)]
-    fn find(&self, query: &[U]) -> Option<T> {
-        let node = query
-            .iter()
-            .fold(Some(&self.root), |current_node, c| match current_node {
-                Some(n) => n.children.get(c),
-                None => None,
-            });
-        match node {
-            Some(n) => n.value.clone(),
-            None => None,
+    /// Value stored for `query`, if it was inserted
+    pub fn find(&self, query: &[U]) -> Option<T> {
+        let mut node = &self.root;
+        for c in query {
+            node = node.children.get(c)?;
         }
+        node.value.clone()
     }
 
     pub fn insert(&mut self, data: &[U], value: T) {
@@ -134,4 +131,58 @@ mod tests {
             .all(|((i, j, c), y)| &format!("{} {} {}", i, j, c) == y.trim()));
         Ok(())
     }
+
+    #[test]
+    fn aho_corasick() {
+        use crate::aho_corasick::{AhoCorasick, DnaPatternMatcher, Strand};
+
+        let patterns = ["he", "she", "his", "hers", "s"];
+        let mut trie = Trie::<usize, char>::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            trie.insert(&pattern.chars().collect::<Vec<_>>(), i);
+        }
+        let automaton = AhoCorasick::new(&trie);
+        let text: Vec<_> = "ushershishe".chars().collect();
+        let mut found: Vec<_> = automaton
+            .find_all(&text)
+            .into_iter()
+            .map(|m| (m.start, *m.value))
+            .collect();
+        found.sort_unstable();
+        let mut expected = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            let pattern: Vec<_> = pattern.chars().collect();
+            for start in 0..=(text.len() - pattern.len()) {
+                if text[start..].starts_with(&pattern) {
+                    expected.push((start, i));
+                }
+            }
+        }
+        expected.sort_unstable();
+        assert_eq!(found, expected);
+
+        // chunks give the same matches as the whole text
+        let mut stream = automaton.stream();
+        let mut chunked: Vec<_> = text
+            .chunks(3)
+            .flat_map(|chunk| stream.feed(chunk))
+            .map(|m| (m.start, *m.value))
+            .collect();
+        chunked.sort_unstable();
+        assert_eq!(chunked, expected);
+
+        let matcher = DnaPatternMatcher::new(&["GAATTC", "ACCT"], true);
+        let matches = matcher.find_all("TTGAATTCAGGTAACCT");
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.pattern_index, m.start, m.strand))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 2, Strand::Forward),
+                (1, 8, Strand::Reverse),
+                (1, 13, Strand::Forward)
+            ]
+        );
+    }
 }
@@ -2,6 +2,8 @@ use anyhow::Error;
 
 use std::path::Path;
 
+use s_kmp::{Kmp, Matcher};
+
 pub fn rosalind_ba1a(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
@@ -10,14 +12,7 @@ pub fn rosalind_ba1a(filename: &Path) -> Result<(), Error> {
     Ok(())
 }
 
+/// Number of (possibly overlapping) occurrences of `pattern` in `text`
 pub fn pattern_count(text: &str, pattern: &str) -> usize {
-    let mut count = 0;
-    let text: Vec<_> = text.chars().collect();
-    let pattern: Vec<_> = pattern.chars().collect();
-    for i in 0..=(text.len() - pattern.len()) {
-        if text[i..(i + pattern.len())] == pattern[..] {
-            count += 1;
-        }
-    }
-    count
+    Kmp::new(pattern.as_bytes()).count(text.as_bytes())
 }
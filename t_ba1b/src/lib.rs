@@ -3,39 +3,50 @@ use std::path::Path;
 
 use anyhow::Error;
 
+use utility::kmer::{Alphabet, Kmer, Kmers};
+
 pub fn rosalind_ba1b(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let (text, k) = (lines[0], lines[1].parse::<usize>()?);
-    let counts_tuple = get_sorted_kmer_counts(text, k);
+    let alphabet = Alphabet::dna();
+    let counts_tuple = get_sorted_kmer_counts(text, k, &alphabet);
     println!(
         "{}",
-        utility::io::format_array(&get_most_frequent_kmers(&counts_tuple))
+        utility::io::format_array(
+            &get_most_frequent_kmers(&counts_tuple)
+                .into_iter()
+                .map(|kmer| kmer.decode(&alphabet))
+                .collect::<Vec<_>>()
+        )
     );
     Ok(())
 }
 
-pub fn get_sorted_kmer_counts(text: &str, k: usize) -> Vec<(String, usize)> {
-    let text: Vec<_> = text.chars().collect();
+/// Counts of the k-mers in `text`, most frequent first (ties in lexicographic order)
+pub fn get_sorted_kmer_counts(text: &str, k: usize, alphabet: &Alphabet) -> Vec<(Kmer, usize)> {
     let mut counts = HashMap::new();
-    for i in 0..=(text.len() - k) {
-        *counts
-            .entry(text[i..(i + k)].iter().collect::<String>())
-            .or_insert(0usize) += 1;
+    for (_, kmer) in Kmers::new(text.as_bytes(), k, alphabet) {
+        *counts.entry(kmer).or_insert(0usize) += 1;
     }
+    sort_kmer_counts(counts)
+}
+
+/// Most frequent first, ties in lexicographic order
+pub fn sort_kmer_counts(counts: HashMap<Kmer, usize>) -> Vec<(Kmer, usize)> {
     let mut counts_tuple = counts.into_iter().collect::<Vec<_>>();
-    counts_tuple.sort_by(|a, b| b.1.cmp(&a.1));
+    counts_tuple.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
     counts_tuple
 }
 
-pub fn get_most_frequent_kmers(counts_tuple: &[(String, usize)]) -> Vec<String> {
+pub fn get_most_frequent_kmers(counts_tuple: &[(Kmer, usize)]) -> Vec<Kmer> {
     let max_count = counts_tuple[0].1;
     let mut max_kmers = Vec::new();
     for tuple in counts_tuple {
         if tuple.1 != max_count {
             break;
         }
-        max_kmers.push(tuple.0.clone())
+        max_kmers.push(tuple.0)
     }
     max_kmers
 }
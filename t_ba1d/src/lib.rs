@@ -2,6 +2,8 @@ use anyhow::Error;
 
 use std::path::Path;
 
+use s_kmp::{Kmp, Matcher};
+
 pub fn rosalind_ba1d(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
@@ -13,13 +15,5 @@ pub fn rosalind_ba1d(filename: &Path) -> Result<(), Error> {
 }
 
 fn find_pattern(text: &str, pattern: &str) -> Vec<usize> {
-    let mut indices = Vec::new();
-    let text: Vec<_> = text.chars().collect();
-    let pattern: Vec<_> = pattern.chars().collect();
-    for i in 0..=(text.len() - pattern.len()) {
-        if text[i..(i + pattern.len())] == pattern[..] {
-            indices.push(i);
-        }
-    }
-    indices
+    Kmp::new(pattern.as_bytes()).find_all(text.as_bytes())
 }
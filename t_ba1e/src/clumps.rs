@@ -0,0 +1,257 @@
+//! One-pass scan of a genome for k-mer clumps, GC content and GC skew.
+//!
+//! The window slides one nucleotide at a time: the k-mer leaving it is uncounted and the one
+//! entering it is counted, so a genome of length n is scanned in O(n) rather than O(n·L).
+use std::collections::{HashMap, VecDeque};
+
+use t_ba1f::SkewTracker;
+use utility::kmer::{Alphabet, Kmer};
+
+/// A k-mer occurring at least `min_count` times in every window of a run of consecutive windows.
+/// `start..end` (0-indexed) is the union of those windows and `count` the most occurrences
+/// in any one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Clump {
+    pub kmer: Kmer,
+    pub start: usize,
+    pub end: usize,
+    pub count: usize,
+}
+
+/// Finds (L, t)-clumps in a genome read one nucleotide at a time
+pub struct ClumpFinder {
+    alphabet: Alphabet,
+    k: usize,
+    window_length: usize,
+    min_count: usize,
+    canonical: bool,
+    /// k-mers starting in the current window, None where they have an invalid symbol
+    window_kmers: VecDeque<Option<Kmer>>,
+    counts: HashMap<Kmer, usize>,
+    /// start of the first window and highest count of each clump still growing
+    open_clumps: HashMap<Kmer, (usize, usize)>,
+    current: Kmer,
+    valid_length: usize,
+    /// number of nucleotides read
+    length: usize,
+}
+
+impl ClumpFinder {
+    /// With `canonical`, a k-mer and its reverse complement are counted together
+    /// (and reported as the smaller of the two)
+    pub fn new(
+        k: usize,
+        window_length: usize,
+        min_count: usize,
+        canonical: bool,
+        alphabet: &Alphabet,
+    ) -> Self {
+        assert!(
+            k > 0 && k <= window_length && k <= alphabet.max_k::<u64>(),
+            "k must be positive, at most the window length and fit in a k-mer"
+        );
+        ClumpFinder {
+            alphabet: alphabet.clone(),
+            k,
+            window_length,
+            min_count: min_count.max(1),
+            canonical,
+            window_kmers: VecDeque::with_capacity(window_length - k + 2),
+            counts: HashMap::new(),
+            open_clumps: HashMap::new(),
+            current: Kmer::new(0, k),
+            valid_length: 0,
+            length: 0,
+        }
+    }
+
+    /// Read the next nucleotide, returning the clumps that ended with the previous window
+    pub fn push(&mut self, nucleotide: u8) -> Vec<Clump> {
+        let mut closed = Vec::new();
+        let kmer = match self.alphabet.encode(nucleotide) {
+            Some(code) => {
+                self.current = self.current.push(code, &self.alphabet);
+                self.valid_length += 1;
+                if self.valid_length >= self.k {
+                    Some(if self.canonical {
+                        self.current.canonical(&self.alphabet)
+                    } else {
+                        self.current
+                    })
+                } else {
+                    None
+                }
+            }
+            None => {
+                self.valid_length = 0;
+                None
+            }
+        };
+        self.length += 1;
+        if self.length < self.k {
+            return closed;
+        }
+        // the window now ends at the nucleotide just read
+        let window_start = self.length.saturating_sub(self.window_length);
+        let leaving = if self.window_kmers.len() == self.window_length - self.k + 1 {
+            self.window_kmers.pop_front().flatten()
+        } else {
+            None
+        };
+        self.window_kmers.push_back(kmer);
+        if leaving == kmer {
+            // the counts are unchanged, and any clump of the k-mer carries on
+            return closed;
+        }
+        if let Some(leaving) = leaving {
+            let count = self.counts.get_mut(&leaving).unwrap();
+            *count -= 1;
+            let count = *count;
+            if count == 0 {
+                self.counts.remove(&leaving);
+            }
+            if count + 1 == self.min_count {
+                let (start, highest) = self.open_clumps.remove(&leaving).unwrap();
+                closed.push(Clump {
+                    kmer: leaving,
+                    start,
+                    end: self.length - 1,
+                    count: highest,
+                });
+            }
+        }
+        if let Some(kmer) = kmer {
+            let count = self.counts.entry(kmer).or_insert(0);
+            *count += 1;
+            if *count >= self.min_count {
+                let clump = self.open_clumps.entry(kmer).or_insert((window_start, 0));
+                clump.1 = clump.1.max(*count);
+            }
+        }
+        closed
+    }
+
+    /// Clumps still growing when the genome ends, none if it is shorter than a window
+    pub fn finish(self) -> Vec<Clump> {
+        if self.length < self.window_length {
+            return Vec::new();
+        }
+        let end = self.length;
+        self.open_clumps
+            .into_iter()
+            .map(|(kmer, (start, count))| Clump {
+                kmer,
+                start,
+                end,
+                count,
+            })
+            .collect()
+    }
+}
+
+/// All clumps of a genome, sorted by position
+pub fn find_clumps(
+    genome: &[u8],
+    k: usize,
+    window_length: usize,
+    min_count: usize,
+    canonical: bool,
+    alphabet: &Alphabet,
+) -> Vec<Clump> {
+    let mut finder = ClumpFinder::new(k, window_length, min_count, canonical, alphabet);
+    let mut clumps = Vec::new();
+    for nucleotide in genome {
+        clumps.extend(finder.push(*nucleotide));
+    }
+    clumps.extend(finder.finish());
+    clumps.sort_unstable_by_key(|clump| (clump.start, clump.end, clump.kmer));
+    clumps
+}
+
+/// Settings for `scan_genome`
+#[derive(Debug, Clone)]
+pub struct ScanParams {
+    pub k: usize,
+    /// Length L of the windows searched for clumps and measured for GC content
+    pub window_length: usize,
+    /// Occurrences t of a k-mer in a window that make a clump
+    pub min_count: usize,
+    pub canonical: bool,
+    /// GC content is reported for every `gc_step`-th window
+    pub gc_step: usize,
+}
+
+/// Everything `scan_genome` finds in its single pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenomeScan {
+    /// Sorted by position
+    pub clumps: Vec<Clump>,
+    /// (window start, fraction of G and C among the A, C, G and T of the window)
+    pub gc_content: Vec<(usize, f64)>,
+    /// G - C skew of every prefix, from the empty one to the whole genome
+    pub skew: Vec<isize>,
+    /// Prefix lengths with the lowest skew, where the replication origin is likely to be
+    pub skew_minima: Vec<usize>,
+}
+
+impl GenomeScan {
+    /// Clumps in a window of `radius` nucleotides on either side of a skew minimum
+    pub fn clumps_near_origin(&self, radius: usize) -> Vec<Clump> {
+        self.clumps
+            .iter()
+            .filter(|clump| {
+                self.skew_minima.iter().any(|minimum| {
+                    clump.start <= minimum + radius && minimum.saturating_sub(radius) < clump.end
+                })
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Clumps, windowed GC content and cumulative GC skew of a genome, in one pass
+pub fn scan_genome(genome: &[u8], params: &ScanParams, alphabet: &Alphabet) -> GenomeScan {
+    let mut finder = ClumpFinder::new(
+        params.k,
+        params.window_length,
+        params.min_count,
+        params.canonical,
+        alphabet,
+    );
+    let mut skew_tracker = SkewTracker::new();
+    let mut clumps = Vec::new();
+    let mut gc_content = Vec::new();
+    let mut skew = Vec::with_capacity(genome.len() + 1);
+    skew.push(0);
+    // (is valid, is G or C) of each nucleotide of the window
+    let mut window = VecDeque::with_capacity(params.window_length + 1);
+    let (mut valid, mut gc) = (0, 0);
+    for (i, nucleotide) in genome.iter().enumerate() {
+        clumps.extend(finder.push(*nucleotide));
+        skew.push(skew_tracker.push(*nucleotide));
+        let upper = nucleotide.to_ascii_uppercase();
+        let entering = (b"ACGT".contains(&upper), upper == b'G' || upper == b'C');
+        valid += entering.0 as usize;
+        gc += entering.1 as usize;
+        window.push_back(entering);
+        if window.len() > params.window_length {
+            let (was_valid, was_gc) = window.pop_front().unwrap();
+            valid -= was_valid as usize;
+            gc -= was_gc as usize;
+        }
+        if window.len() == params.window_length {
+            let start = i + 1 - params.window_length;
+            if start.is_multiple_of(params.gc_step.max(1)) && valid > 0 {
+                gc_content.push((start, gc as f64 / valid as f64));
+            }
+        }
+    }
+    clumps.extend(finder.finish());
+    clumps.sort_unstable_by_key(|clump| (clump.start, clump.end, clump.kmer));
+    GenomeScan {
+        clumps,
+        gc_content,
+        skew,
+        skew_minima: skew_tracker.minima().to_vec(),
+    }
+}
@@ -3,37 +3,167 @@ use std::collections::HashSet;
 use anyhow::Error;
 
 use std::path::Path;
-use t_ba1b::get_sorted_kmer_counts;
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer};
+
+use crate::clumps::find_clumps;
+
+pub mod clumps;
 
 pub fn rosalind_ba1e(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let numbers = usize::parse_line(lines[1])?;
     let (k, l, t) = (numbers[0], numbers[1], numbers[2]);
+    let alphabet = Alphabet::dna();
     println!(
         "{}",
         utility::io::format_array(
-            &find_l_t_clumps(lines[0], k, l, t)
+            &find_l_t_clumps(lines[0], k, l, t, &alphabet)
                 .into_iter()
+                .map(|kmer| kmer.decode(&alphabet))
                 .collect::<Vec<_>>(),
         )
     );
     Ok(())
 }
 
-fn find_l_t_clumps(text: &str, k: usize, l: usize, t: usize) -> HashSet<String> {
-    let mut clump_kmers = HashSet::new();
-    let text: Vec<_> = text.chars().collect();
-    for i in 0..(text.len() - l) {
-        let genome = text[i..(i + l)].iter().collect::<String>();
-        let counts_tuple = get_sorted_kmer_counts(&genome, k);
-        for tuple in counts_tuple {
-            if tuple.1 < t {
-                break;
+fn find_l_t_clumps(text: &str, k: usize, l: usize, t: usize, alphabet: &Alphabet) -> HashSet<Kmer> {
+    find_clumps(text.trim().as_bytes(), k, l, t, false, alphabet)
+        .into_iter()
+        .map(|clump| clump.kmer)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clumps::{scan_genome, ScanParams};
+    use super::*;
+    use crate::clumps::Clump;
+    use std::collections::HashMap;
+    use utility::kmer::Kmers;
+
+    #[test]
+    fn clumps() {
+        let alphabet = Alphabet::dna();
+        // the sample dataset of the problem
+        let text = "CGGACTCGACAGATGTGAAGAACGACAATGTGAAGACTCGACACGACAGAGTGAAGAGAAGAGGAAACATTGTAA";
+        let mut clump_kmers: Vec<_> = find_l_t_clumps(text, 5, 50, 4, &alphabet)
+            .into_iter()
+            .map(|kmer| kmer.decode(&alphabet))
+            .collect();
+        clump_kmers.sort();
+        assert_eq!(clump_kmers, vec!["CGACA", "GAAGA"]);
+
+        // every window checked by counting its k-mers from scratch
+        for &(length, k, window_length, min_count, canonical) in &[
+            (300, 2, 20, 3, false),
+            (300, 3, 30, 2, true),
+            (40, 3, 50, 1, false),
+            (50, 4, 50, 1, true),
+        ] {
+            let genome =
+                utility::testing::random_sequence(length, b"ACGTN", length as u64 + k as u64)
+                    .into_bytes();
+            let windows = (0..(genome.len() + 1).saturating_sub(window_length))
+                .map(|start| {
+                    let mut counts = HashMap::new();
+                    for (_, kmer) in
+                        Kmers::<u64>::new(&genome[start..start + window_length], k, &alphabet)
+                    {
+                        let kmer = if canonical {
+                            kmer.canonical(&alphabet)
+                        } else {
+                            kmer
+                        };
+                        *counts.entry(kmer).or_insert(0) += 1;
+                    }
+                    counts
+                })
+                .collect::<Vec<_>>();
+            let clumps = find_clumps(&genome, k, window_length, min_count, canonical, &alphabet);
+            let mut expected = Vec::new();
+            for (start, counts) in windows.iter().enumerate() {
+                for (kmer, count) in counts {
+                    if *count >= min_count
+                        && (start == 0 || windows[start - 1].get(kmer).unwrap_or(&0) < &min_count)
+                    {
+                        let last = (start..windows.len())
+                            .take_while(|s| windows[*s].get(kmer).unwrap_or(&0) >= &min_count)
+                            .last()
+                            .unwrap();
+                        let highest = (start..=last).map(|s| windows[s][kmer]).max().unwrap();
+                        expected.push(Clump {
+                            kmer: *kmer,
+                            start,
+                            end: last + window_length,
+                            count: highest,
+                        });
+                    }
+                }
             }
-            clump_kmers.insert(tuple.0);
+            expected.sort_unstable_by_key(|clump| (clump.start, clump.end, clump.kmer));
+            assert_eq!(clumps, expected);
         }
     }
-    clump_kmers
+
+    #[test]
+    fn genome_scan() {
+        let alphabet = Alphabet::dna();
+        let genome = b"GGGGGCCCCCCCCCCATGATGATGATGATGGGGGNGGGGG";
+        let params = ScanParams {
+            k: 3,
+            window_length: 10,
+            min_count: 3,
+            canonical: false,
+            gc_step: 5,
+        };
+        let scan = scan_genome(genome, &params, &alphabet);
+        assert_eq!(scan.skew, t_ba1f::cumulative_skew(genome));
+        assert_eq!(scan.skew_minima, vec![15, 16, 17]);
+        assert_eq!(
+            scan.gc_content,
+            vec![
+                (0, 1.0),
+                (5, 1.0),
+                (10, 0.6),
+                (15, 0.3),
+                (20, 0.4),
+                (25, 6. / 9.),
+                (30, 1.0)
+            ]
+        );
+        let atg = Kmer::encode(b"ATG", &alphabet).unwrap();
+        let near_origin = scan.clumps_near_origin(5);
+        assert!(near_origin.iter().any(|clump| clump.kmer == atg));
+        assert!(near_origin.iter().all(|clump| clump.start <= 20));
+        let clumps: Vec<_> = scan
+            .clumps
+            .iter()
+            .map(|clump| {
+                (
+                    clump.kmer.decode(&alphabet),
+                    clump.start,
+                    clump.end,
+                    clump.count,
+                )
+            })
+            .collect();
+        // the ATG repeats give overlapping clumps of each of their rotations
+        assert_eq!(
+            clumps,
+            vec![
+                ("GGG".to_string(), 0, 10, 3),
+                ("CCC".to_string(), 0, 20, 8),
+                ("ATG".to_string(), 14, 25, 3),
+                ("TGA".to_string(), 15, 26, 3),
+                ("GAT".to_string(), 16, 27, 3),
+                ("ATG".to_string(), 17, 28, 3),
+                ("TGA".to_string(), 18, 29, 3),
+                ("GAT".to_string(), 19, 30, 3),
+                ("ATG".to_string(), 20, 31, 3),
+                ("GGG".to_string(), 24, 40, 5),
+            ]
+        );
+    }
 }
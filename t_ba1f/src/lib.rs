@@ -8,35 +8,68 @@ pub fn rosalind_ba1f(filename: &Path) -> Result<(), Error> {
     Ok(())
 }
 
-fn get_counts(text: &str, character: char) -> Vec<usize> {
-    let presence: Vec<_> = text
-        .chars()
-        .map(|c| if c == character { 1 } else { 0 })
-        .collect();
-    let mut counts = Vec::new();
-    let mut current_count = 0;
-    for p in presence {
-        current_count += p;
-        counts.push(current_count);
-    }
-    counts
+/// Running G - C skew of a genome read one nucleotide at a time,
+/// with the prefix lengths at which it is lowest (candidate replication origins)
+#[derive(Debug, Clone, Default)]
+pub struct SkewTracker {
+    skew: isize,
+    length: usize,
+    minimum: isize,
+    minima: Vec<usize>,
 }
 
-fn minimize_skews(text: &str) -> Vec<usize> {
-    let mut skews = vec![(0, 0)];
-    let g_counts = get_counts(text, 'G');
-    let c_counts = get_counts(text, 'C');
-    for i in 0..text.len() {
-        skews.push((i, (g_counts[i] as isize) - (c_counts[i] as isize)));
+impl SkewTracker {
+    pub fn new() -> Self {
+        SkewTracker {
+            minima: vec![0],
+            ..Default::default()
+        }
     }
-    skews.sort_by(|a, b| a.1.cmp(&b.1));
-    let min_skew = skews[0].1;
-    let mut indices = Vec::new();
-    for (i, skew) in skews {
-        if skew > min_skew {
-            break;
+
+    /// Read the next nucleotide and return the skew of the prefix read so far
+    pub fn push(&mut self, nucleotide: u8) -> isize {
+        match nucleotide.to_ascii_uppercase() {
+            b'G' => self.skew += 1,
+            b'C' => self.skew -= 1,
+            _ => (),
+        }
+        self.length += 1;
+        if self.skew < self.minimum {
+            self.minimum = self.skew;
+            self.minima.clear();
         }
-        indices.push(i + 1);
+        if self.skew == self.minimum {
+            self.minima.push(self.length);
+        }
+        self.skew
+    }
+
+    pub fn skew(&self) -> isize {
+        self.skew
+    }
+
+    pub fn minimum(&self) -> isize {
+        self.minimum
+    }
+
+    /// Prefix lengths with the lowest skew, in increasing order
+    pub fn minima(&self) -> &[usize] {
+        &self.minima
+    }
+}
+
+/// Skew of every prefix of the genome, from the empty one to the whole genome
+pub fn cumulative_skew(text: &[u8]) -> Vec<isize> {
+    let mut tracker = SkewTracker::new();
+    std::iter::once(0)
+        .chain(text.iter().map(|c| tracker.push(*c)))
+        .collect()
+}
+
+fn minimize_skews(text: &str) -> Vec<usize> {
+    let mut tracker = SkewTracker::new();
+    for c in text.trim().bytes() {
+        tracker.push(c);
     }
-    indices
+    tracker.minima().to_vec()
 }
@@ -1,10 +1,73 @@
 use anyhow::Error;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmer};
 
 pub fn rosalind_ba1g(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
-    println!("{}", utility::string::hamming(lines[0], lines[1]));
+    println!(
+        "{}",
+        hamming_distance(lines[0].trim(), lines[1].trim(), &Alphabet::dna())?
+    );
     Ok(())
 }
+
+/// Hamming distance between two equally long sequences, compared a packed word at a time
+pub fn hamming_distance(
+    sequence_1: &str,
+    sequence_2: &str,
+    alphabet: &Alphabet,
+) -> Result<usize, Error> {
+    if sequence_1.len() != sequence_2.len() {
+        return Err(
+            utility::errors::RosalindParseError::InputFormatError(String::from(
+                "Sequences have different lengths",
+            ))
+            .into(),
+        );
+    }
+    let chunk_length = alphabet.max_k::<u128>();
+    let mut distance = 0;
+    for (chunk_1, chunk_2) in sequence_1
+        .as_bytes()
+        .chunks(chunk_length)
+        .zip(sequence_2.as_bytes().chunks(chunk_length))
+    {
+        match (
+            Kmer::<u128>::encode(chunk_1, alphabet),
+            Kmer::<u128>::encode(chunk_2, alphabet),
+        ) {
+            (Some(kmer_1), Some(kmer_2)) => distance += kmer_1.hamming_distance(kmer_2, alphabet),
+            _ => {
+                return Err(
+                    utility::errors::RosalindParseError::InputFormatError(String::from(
+                        "Sequences have symbols outside the alphabet",
+                    ))
+                    .into(),
+                )
+            }
+        }
+    }
+    Ok(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hamming() -> Result<(), Error> {
+        let dna = Alphabet::dna();
+        let sequence_1 = utility::testing::random_sequence(300, b"ACGT", 1);
+        let sequence_2 = utility::testing::random_sequence(300, b"ACGT", 2);
+        assert_eq!(
+            hamming_distance(&sequence_1, &sequence_2, &dna)?,
+            utility::string::hamming(&sequence_1, &sequence_2)
+        );
+        assert_eq!(hamming_distance("GGGCCGTTGGT", "GGACCGTTGAC", &dna)?, 3);
+        assert!(hamming_distance("ACGT", "ACG", &dna).is_err());
+        assert!(hamming_distance("ACGN", "ACGT", &dna).is_err());
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Error;
 
 use std::path::Path;
+use t_ba9o::{ApproximateMatcher, SearchStrategy};
 
 pub fn rosalind_ba1h(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -14,16 +15,9 @@ pub fn rosalind_ba1h(filename: &Path) -> Result<(), Error> {
 }
 
 fn find_pattern_approx(text: &str, pattern: &str, mismatch: usize) -> Vec<usize> {
-    let mut indices = Vec::new();
-    let text: Vec<_> = text.chars().collect();
-    for i in 0..=(text.len() - pattern.len()) {
-        if utility::string::hamming(
-            &text[i..(i + pattern.len())].iter().collect::<String>(),
-            pattern,
-        ) <= mismatch
-        {
-            indices.push(i);
-        }
-    }
-    indices
+    ApproximateMatcher::new(text)
+        .find(pattern.as_bytes(), mismatch, SearchStrategy::SeedAndVerify)
+        .into_iter()
+        .map(|(position, _)| position)
+        .collect()
 }
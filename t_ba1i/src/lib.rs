@@ -1,57 +1,211 @@
-use std::collections::{HashMap, HashSet};
-
 use anyhow::Error;
 
+use s_kmer::counter::{KmerCounter, KmerCounterConfig};
 use std::path::Path;
-use t_ba1b::get_most_frequent_kmers;
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
+
+/// Codes fewer than this are counted in an array indexed by code
+const DENSE_CODE_LIMIT: u128 = 1 << 24;
 
 pub fn rosalind_ba1i(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let numbers = usize::parse_line(lines[1])?;
     let (k, mismatch) = (numbers[0], numbers[1]);
-    let counts_tuple = get_sorted_kmer_counts_approx(lines[0], k, mismatch);
+    let alphabet = Alphabet::dna();
+    let (kmers, _) =
+        most_frequent_with_mismatches(lines[0].trim().as_bytes(), k, mismatch, false, &alphabet)?;
     println!(
         "{}",
-        utility::io::format_array(&get_most_frequent_kmers(&counts_tuple))
+        utility::io::format_array(
+            &kmers
+                .into_iter()
+                .map(|kmer| kmer.decode(&alphabet))
+                .collect::<Vec<_>>()
+        )
     );
     Ok(())
 }
 
-pub fn get_mismatch_sequences(sequence: &str, mismatch: usize) -> Vec<String> {
-    if mismatch == 0 {
-        vec![sequence.to_owned()]
-    } else if sequence.len() == 1 {
-        "ACGT".chars().map(|c| c.to_string()).collect()
-    } else {
-        let mut mismatch_sequences = HashSet::new();
-        let sequence: Vec<_> = sequence.chars().collect();
-        let suffix: String = sequence[1..].iter().collect();
-        let suffix_mismatch_sequences = get_mismatch_sequences(&suffix, mismatch);
-        for neighbor in suffix_mismatch_sequences {
-            if utility::string::hamming(&suffix, &neighbor) < mismatch {
-                for c in "ACGT".chars() {
-                    mismatch_sequences.insert(format!("{}{}", c, neighbor));
-                }
-            } else {
-                mismatch_sequences.insert(format!("{}{}", sequence[0], neighbor));
-            }
+/// Distinct codes of a sorted list, with their multiplicities
+fn run_lengths(sorted_codes: &[u64]) -> Vec<(u64, u64)> {
+    let mut runs: Vec<(u64, u64)> = Vec::new();
+    for code in sorted_codes {
+        match runs.last_mut() {
+            Some((last, count)) if last == code => *count += 1,
+            _ => runs.push((*code, 1)),
         }
-        mismatch_sequences.into_iter().collect()
     }
+    runs
 }
 
-fn get_sorted_kmer_counts_approx(text: &str, k: usize, mismatch: usize) -> Vec<(String, usize)> {
-    let text: Vec<_> = text.chars().collect();
-    let mut counts = HashMap::new();
-    for i in 0..=(text.len() - k) {
-        let text_kmer: String = text[i..(i + k)].iter().collect();
-        for kmer in get_mismatch_sequences(&text_kmer, mismatch) {
-            *counts.entry(kmer).or_insert(0usize) += 1;
+/// Counter of the neighbourhoods of (code, multiplicity) pairs of text k-mers, with each
+/// neighbourhood enumerated once
+fn neighbourhood_counter(
+    text_kmers: &[(u64, u64)],
+    k: usize,
+    mismatch: usize,
+    alphabet: &Alphabet,
+    memory_budget: usize,
+) -> Result<KmerCounter, Error> {
+    let mut counter = KmerCounter::with_alphabet(
+        KmerCounterConfig {
+            k,
+            canonical: false,
+            memory_budget,
+            ..KmerCounterConfig::default()
+        },
+        alphabet.clone(),
+    );
+    for (code, multiplicity) in text_kmers {
+        for neighbour in Kmer::new(*code, k).neighbours(mismatch, alphabet) {
+            counter.add_kmer(neighbour, *multiplicity)?;
+        }
+    }
+    Ok(counter)
+}
+
+/// k-mers with the most occurrences in `text` with at most `mismatch` mismatches
+/// (counting the occurrences of their reverse complements too with `reverse_complements`),
+/// sorted, and that number of occurrences.
+///
+/// Each distinct k-mer of the text adds its multiplicity to every k-mer of its neighbourhood.
+/// For small k the counts are kept in an array indexed by k-mer code; otherwise they go to a
+/// `KmerCounter`, which spills sorted runs of counts to disk when they outgrow its memory budget
+/// and merges them at the end.
+pub fn most_frequent_with_mismatches(
+    text: &[u8],
+    k: usize,
+    mismatch: usize,
+    reverse_complements: bool,
+    alphabet: &Alphabet,
+) -> Result<(Vec<Kmer>, u64), Error> {
+    let mut codes = Vec::new();
+    for (_, kmer) in Kmers::<u64>::new(text, k, alphabet) {
+        codes.push(kmer.value());
+        if reverse_complements {
+            codes.push(kmer.reverse_complement(alphabet).value());
+        }
+    }
+    codes.sort_unstable();
+    let text_kmers = run_lengths(&codes);
+    drop(codes);
+
+    let code_space = 1u128 << (k as u32 * alphabet.bits_per_symbol());
+    let mut best_codes = Vec::new();
+    let mut best_count = 0;
+    let mut consider = |code: u64, count: u64| {
+        if count > best_count {
+            best_count = count;
+            best_codes.clear();
+        }
+        if count == best_count {
+            best_codes.push(code);
         }
+    };
+    if code_space <= DENSE_CODE_LIMIT {
+        let mut counts = vec![0u64; code_space as usize];
+        for (code, multiplicity) in &text_kmers {
+            for neighbour in Kmer::new(*code, k).neighbours(mismatch, alphabet) {
+                counts[neighbour.value() as usize] += multiplicity;
+            }
+        }
+        for (code, count) in counts.into_iter().enumerate() {
+            if count > 0 {
+                consider(code as u64, count);
+            }
+        }
+    } else {
+        let memory_budget = KmerCounterConfig::default().memory_budget;
+        neighbourhood_counter(&text_kmers, k, mismatch, alphabet, memory_budget)?
+            .for_each_count(|kmer, count| consider(kmer.value(), count))?;
+    }
+    best_codes.sort_unstable();
+    Ok((
+        best_codes
+            .into_iter()
+            .map(|code| Kmer::new(code, k))
+            .collect(),
+        best_count,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequent_words_with_mismatches() -> Result<(), Error> {
+        let alphabet = Alphabet::dna();
+        let decode = |(kmers, count): (Vec<Kmer>, u64)| {
+            (
+                kmers
+                    .into_iter()
+                    .map(|kmer| kmer.decode(&alphabet))
+                    .collect::<Vec<_>>(),
+                count,
+            )
+        };
+        // sample datasets of BA1I and BA1J
+        let text = b"ACGTTGCATGTCGCATGATGCATGAGAGCT";
+        assert_eq!(
+            decode(most_frequent_with_mismatches(text, 4, 1, false, &alphabet)?),
+            (vec!["ATGC".into(), "ATGT".into(), "GATG".into()], 5)
+        );
+        assert_eq!(
+            decode(most_frequent_with_mismatches(text, 4, 1, true, &alphabet)?).0,
+            vec!["ACAT".to_string(), "ATGT".to_string()]
+        );
+
+        // the sparse counts agree with brute force for k-mers too long for the dense array
+        let text = utility::testing::random_sequence(200, b"ACGT", 11).into_bytes();
+        let (k, mismatch) = (13, 1);
+        let text_kmers: Vec<_> = Kmers::<u64>::new(&text, k, &alphabet)
+            .map(|(_, kmer)| kmer)
+            .collect();
+        let (kmers, count) = most_frequent_with_mismatches(&text, k, mismatch, true, &alphabet)?;
+        let occurrences = |pattern: Kmer| {
+            text_kmers
+                .iter()
+                .filter(|kmer| kmer.hamming_distance(pattern, &alphabet) <= mismatch)
+                .count() as u64
+        };
+        let mut candidates: Vec<_> = text_kmers
+            .iter()
+            .flat_map(|kmer| vec![*kmer, kmer.reverse_complement(&alphabet)])
+            .flat_map(|kmer| kmer.hamming_neighbours(mismatch, &alphabet))
+            .map(|pattern| {
+                (
+                    occurrences(pattern) + occurrences(pattern.reverse_complement(&alphabet)),
+                    pattern,
+                )
+            })
+            .collect();
+        let best = candidates.iter().map(|(count, _)| *count).max().unwrap();
+        candidates.retain(|(count, _)| *count == best);
+        let mut expected: Vec<_> = candidates.into_iter().map(|(_, pattern)| pattern).collect();
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!((kmers, count), (expected, best));
+        Ok(())
+    }
+
+    #[test]
+    fn spilled_neighbourhood_counts() -> Result<(), Error> {
+        let alphabet = Alphabet::dna();
+        let text = utility::testing::random_sequence(300, b"ACGT", 5).into_bytes();
+        let mut codes: Vec<_> = Kmers::<u64>::new(&text, 13, &alphabet)
+            .map(|(_, kmer)| kmer.value())
+            .collect();
+        codes.sort_unstable();
+        let text_kmers = run_lengths(&codes);
+        // a budget of a few thousand entries spills the ~12000 distinct neighbours many times
+        let spilling = neighbourhood_counter(&text_kmers, 13, 1, &alphabet, 100_000)?;
+        assert!(spilling.num_partitions() > 1);
+        let in_memory = neighbourhood_counter(&text_kmers, 13, 1, &alphabet, 1 << 30)?;
+        assert_eq!(in_memory.num_partitions(), 0);
+        assert_eq!(spilling.counts()?, in_memory.counts()?);
+        Ok(())
     }
-    let mut counts_tuple = counts.into_iter().collect::<Vec<_>>();
-    counts_tuple.sort_by(|a, b| b.1.cmp(&a.1));
-    counts_tuple
 }
@@ -1,41 +1,26 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
 
-use s_revc::reverse_complement;
 use std::path::Path;
-use t_ba1b::get_most_frequent_kmers;
-use t_ba1i::get_mismatch_sequences;
+use t_ba1i::most_frequent_with_mismatches;
 use utility::io::Parseable;
+use utility::kmer::Alphabet;
 
 pub fn rosalind_ba1j(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let numbers = usize::parse_line(lines[1])?;
     let (k, mismatch) = (numbers[0], numbers[1]);
-    let counts_tuple = get_sorted_kmer_counts_approx_revc(lines[0], k, mismatch);
+    let alphabet = Alphabet::dna();
+    let (kmers, _) =
+        most_frequent_with_mismatches(lines[0].trim().as_bytes(), k, mismatch, true, &alphabet)?;
     println!(
         "{}",
-        utility::io::format_array(&get_most_frequent_kmers(&counts_tuple))
+        utility::io::format_array(
+            &kmers
+                .into_iter()
+                .map(|kmer| kmer.decode(&alphabet))
+                .collect::<Vec<_>>()
+        )
     );
     Ok(())
 }
-
-fn get_sorted_kmer_counts_approx_revc(
-    text: &str,
-    k: usize,
-    mismatch: usize,
-) -> Vec<(String, usize)> {
-    let text: Vec<_> = text.chars().collect();
-    let mut counts = HashMap::new();
-    for i in 0..=(text.len() - k) {
-        let text_kmer: String = text[i..(i + k)].iter().collect();
-        for kmer in get_mismatch_sequences(&text_kmer, mismatch) {
-            *counts.entry(reverse_complement(&kmer)).or_insert(0usize) += 1;
-            *counts.entry(kmer).or_insert(0usize) += 1;
-        }
-    }
-    let mut counts_tuple = counts.into_iter().collect::<Vec<_>>();
-    counts_tuple.sort_by(|a, b| b.1.cmp(&a.1));
-    counts_tuple
-}
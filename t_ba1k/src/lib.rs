@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
 
-use s_lexf::enumerate_lex;
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmers};
 
 pub fn rosalind_ba1k(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -11,23 +9,16 @@ pub fn rosalind_ba1k(filename: &Path) -> Result<(), Error> {
     let (text, k) = (lines[0], lines[1].parse::<usize>()?);
     println!(
         "{}",
-        utility::io::format_array(&get_frequency_array(text, k))
+        utility::io::format_array(&get_frequency_array(text, k, &Alphabet::dna()))
     );
     Ok(())
 }
 
-fn get_frequency_array(text: &str, k: usize) -> Vec<usize> {
-    let kmers = enumerate_lex(&"ACGT".chars().collect::<Vec<_>>(), k);
-    let mut counts: Vec<_> = (0..kmers.len()).map(|_| 0).collect();
-    let kmer_indices: HashMap<_, _> = kmers
-        .into_iter()
-        .enumerate()
-        .map(|(i, kmer)| (kmer, i))
-        .collect();
-    let text: Vec<_> = text.chars().collect();
-    for i in 0..=(text.len() - k) {
-        let text_kmer: String = text[i..(i + k)].iter().collect();
-        counts[kmer_indices[&text_kmer]] += 1;
+/// Count of each k-mer in `text`, indexed by lexicographic rank
+pub fn get_frequency_array(text: &str, k: usize, alphabet: &Alphabet) -> Vec<usize> {
+    let mut counts = vec![0; alphabet.size().pow(k as u32)];
+    for (_, kmer) in Kmers::<u64>::new(text.as_bytes(), k, alphabet) {
+        counts[kmer.rank(alphabet) as usize] += 1;
     }
     counts
 }
@@ -1,40 +1,22 @@
-use std::char;
-use std::collections::HashMap;
-
 use anyhow::Error;
-use radix::RadixNum;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmer};
 
 pub fn rosalind_ba1l(filename: &Path) -> Result<(), Error> {
     let dna = utility::io::input_from_file(filename)?;
-    println!(
-        "{}",
-        pattern_to_number(&dna, &"ACGT".chars().collect::<Vec<_>>())?
-    );
+    println!("{}", pattern_to_number(dna.trim(), &Alphabet::dna())?);
     Ok(())
 }
 
-fn pattern_to_number(pattern: &str, alphabet: &[char]) -> Result<usize, Error> {
-    let num_alphabets = alphabet.len();
-    let alphabet_map: HashMap<char, char> = alphabet
-        .iter()
-        .enumerate()
-        .map(|(n, c)| {
-            (
-                *c,
-                char::from_digit(n as u32, num_alphabets as u32)
-                    .unwrap()
-                    .to_ascii_uppercase(),
-            )
-        })
-        .collect();
-    Ok(RadixNum::from_str(
-        &pattern
-            .chars()
-            .map(|c| alphabet_map[&c])
-            .collect::<String>(),
-        num_alphabets,
-    )?
-    .as_decimal()?)
+/// Lexicographic rank of a pattern among all patterns of its length
+pub fn pattern_to_number(pattern: &str, alphabet: &Alphabet) -> Result<u128, Error> {
+    let kmer: Kmer<u128> = Kmer::encode(pattern.as_bytes(), alphabet).ok_or_else(|| {
+        utility::errors::RosalindParseError::InputFormatError(format!(
+            "{} has symbols outside the alphabet or more than {} symbols",
+            pattern,
+            alphabet.max_k::<u128>()
+        ))
+    })?;
+    Ok(kmer.rank(alphabet))
 }
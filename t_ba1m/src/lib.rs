@@ -1,44 +1,53 @@
-use std::char;
-use std::collections::HashMap;
-
 use anyhow::Error;
-use radix::RadixNum;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmer};
 
 pub fn rosalind_ba1m(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
-    let (number, k) = (lines[0].parse::<usize>()?, lines[1].parse::<usize>()?);
-    let mut dna = number_to_pattern(number, &"ACGT".chars().collect::<Vec<_>>())?;
-    if dna.len() < k {
-        dna = format!(
-            "{}{}",
-            (0..k - dna.len()).map(|_| 'A').collect::<String>(),
-            dna
+    let (number, k) = (lines[0].parse::<u128>()?, lines[1].parse::<usize>()?);
+    println!("{}", number_to_pattern(number, k, &Alphabet::dna())?);
+    Ok(())
+}
+
+/// Pattern of length `k` with the given lexicographic rank
+pub fn number_to_pattern(number: u128, k: usize, alphabet: &Alphabet) -> Result<String, Error> {
+    if k > alphabet.max_k::<u128>() {
+        return Err(
+            utility::errors::RosalindParseError::InputFormatError(format!(
+                "k = {} is longer than the longest pattern ({})",
+                k,
+                alphabet.max_k::<u128>()
+            ))
+            .into(),
         );
     }
-    println!("{}", dna);
-    Ok(())
+    if let Some(patterns) = (alphabet.size() as u128).checked_pow(k as u32) {
+        if number >= patterns {
+            return Err(
+                utility::errors::RosalindParseError::InputFormatError(format!(
+                    "{} is not the rank of a pattern of length {}",
+                    number, k
+                ))
+                .into(),
+            );
+        }
+    }
+    Ok(Kmer::<u128>::from_rank(number, k, alphabet).decode(alphabet))
 }
 
-fn number_to_pattern(number: usize, alphabet: &[char]) -> Result<String, Error> {
-    let num_alphabets = alphabet.len();
-    let alphabet_map: HashMap<char, char> = alphabet
-        .iter()
-        .enumerate()
-        .map(|(n, c)| {
-            (
-                char::from_digit(n as u32, num_alphabets as u32)
-                    .unwrap()
-                    .to_ascii_uppercase(),
-                *c,
-            )
-        })
-        .collect();
-    Ok(RadixNum::from(number)
-        .with_radix(num_alphabets)?
-        .digits()
-        .map(|n| alphabet_map[&n])
-        .collect())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_length() -> Result<(), Error> {
+        let dna = Alphabet::dna();
+        assert_eq!(number_to_pattern(45, 4, &dna)?, "AGTC");
+        assert_eq!(number_to_pattern(u128::MAX, 64, &dna)?, "T".repeat(64));
+        assert!(number_to_pattern(0, 65, &dna).is_err());
+        assert!(number_to_pattern(256, 4, &dna).is_err());
+        Ok(())
+    }
 }
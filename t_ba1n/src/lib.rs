@@ -1,7 +1,7 @@
 use anyhow::Error;
 
 use std::path::Path;
-use t_ba1i::get_mismatch_sequences;
+use utility::kmer::{Alphabet, Kmer};
 
 pub fn rosalind_ba1n(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -10,8 +10,14 @@ pub fn rosalind_ba1n(filename: &Path) -> Result<(), Error> {
         lines.next().unwrap(),
         lines.next().unwrap().parse::<usize>().unwrap(),
     );
-    for neighbor in get_mismatch_sequences(pattern, mismatch) {
-        println!("{}", neighbor);
+    let alphabet = Alphabet::dna();
+    let pattern: Kmer = Kmer::encode(pattern.trim().as_bytes(), &alphabet).ok_or_else(|| {
+        utility::errors::RosalindParseError::InputFormatError(String::from(
+            "Pattern isn't DNA or is too long",
+        ))
+    })?;
+    for neighbor in pattern.hamming_neighbours(mismatch, &alphabet) {
+        println!("{}", neighbor.decode(&alphabet));
     }
     Ok(())
 }
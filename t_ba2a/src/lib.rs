@@ -3,8 +3,8 @@ use std::collections::HashSet;
 use anyhow::Error;
 
 use std::path::Path;
-use t_ba1i::get_mismatch_sequences;
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba1a(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -12,40 +12,46 @@ pub fn rosalind_ba1a(filename: &Path) -> Result<(), Error> {
     let numbers = usize::parse_line(lines.next().unwrap())?;
     let (k, mismatch) = (numbers[0], numbers[1]);
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
+    let alphabet = Alphabet::dna();
+    let mut motifs: Vec<_> = enumerate_motifs(&dna, k, mismatch, &alphabet)
+        .into_iter()
+        .collect();
+    motifs.sort_unstable();
     println!(
         "{}",
         utility::io::format_array(
-            &enumerate_motifs(&dna, k, mismatch)
-                .iter()
+            &motifs
+                .into_iter()
+                .map(|motif| motif.decode(&alphabet))
                 .collect::<Vec<_>>(),
         )
     );
     Ok(())
 }
 
-fn is_motif_in_sequence_approx(motif: &str, sequence: &str, mismatch: usize) -> bool {
-    let text: Vec<_> = sequence.chars().collect();
-    let k = motif.len();
-    for i in 0..=(text.len() - k) {
-        if utility::string::hamming(&text[i..(i + k)].iter().collect::<String>(), motif) <= mismatch
-        {
-            return true;
-        }
-    }
-    false
+fn is_motif_in_sequence_approx(
+    motif: Kmer,
+    sequence: &str,
+    mismatch: usize,
+    alphabet: &Alphabet,
+) -> bool {
+    Kmers::new(sequence.as_bytes(), motif.k(), alphabet)
+        .any(|(_, kmer)| kmer.hamming_distance(motif, alphabet) <= mismatch)
 }
 
-fn enumerate_motifs(dna: &[String], k: usize, mismatch: usize) -> HashSet<String> {
+fn enumerate_motifs(
+    dna: &[String],
+    k: usize,
+    mismatch: usize,
+    alphabet: &Alphabet,
+) -> HashSet<Kmer> {
     let mut patterns = HashSet::new();
     for text in dna {
-        let text: Vec<_> = text.chars().collect();
-        for i in 0..=(text.len() - k) {
-            let text_kmer: String = text[i..(i + k)].iter().collect();
-            for neighbor in get_mismatch_sequences(&text_kmer, mismatch) {
-                if dna
-                    .iter()
-                    .all(|sequence| is_motif_in_sequence_approx(&neighbor, sequence, mismatch))
-                {
+        for (_, text_kmer) in Kmers::new(text.as_bytes(), k, alphabet) {
+            for neighbor in text_kmer.neighbours(mismatch, alphabet) {
+                if dna.iter().all(|sequence| {
+                    is_motif_in_sequence_approx(neighbor, sequence, mismatch, alphabet)
+                }) {
                     patterns.insert(neighbor);
                 }
             }
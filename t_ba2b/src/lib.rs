@@ -1,42 +1,40 @@
 use anyhow::Error;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2b(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let mut lines = contents.split('\n');
     let k = lines.next().unwrap().parse::<usize>()?;
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
-    println!("{}", minimize_hamming_in_list(&dna, k));
+    let alphabet = Alphabet::dna();
+    println!(
+        "{}",
+        minimize_hamming_in_list(&dna, k, &alphabet).decode(&alphabet)
+    );
     Ok(())
 }
 
-fn hamming_in_text(text: &str, pattern: &str) -> usize {
-    let k = pattern.len();
-    let mut min_distance = k + 1;
-    let text: Vec<_> = text.chars().collect();
-    for i in 0..=(text.len() - k) {
-        let text_kmer: String = text[i..(i + k)].iter().collect();
-        let distance = utility::string::hamming(&text_kmer, pattern);
-        if distance < min_distance {
-            min_distance = distance
-        }
-    }
-    min_distance
+fn hamming_in_text(text: &str, pattern: Kmer, alphabet: &Alphabet) -> usize {
+    Kmers::new(text.as_bytes(), pattern.k(), alphabet)
+        .map(|(_, kmer)| kmer.hamming_distance(pattern, alphabet))
+        .min()
+        .unwrap_or(pattern.k() + 1)
 }
 
-fn hamming_in_list(dna: &[String], pattern: &str) -> usize {
-    dna.iter().map(|text| hamming_in_text(text, pattern)).sum()
+fn hamming_in_list(dna: &[String], pattern: Kmer, alphabet: &Alphabet) -> usize {
+    dna.iter()
+        .map(|text| hamming_in_text(text, pattern, alphabet))
+        .sum()
 }
 
-fn minimize_hamming_in_list(dna: &[String], k: usize) -> String {
-    let mut min_pattern = String::new();
+fn minimize_hamming_in_list(dna: &[String], k: usize, alphabet: &Alphabet) -> Kmer {
+    let mut min_pattern = Kmer::default();
     let mut min_distance = (k + 1) * dna.len();
     for text in dna {
-        let text: Vec<_> = text.chars().collect();
-        for i in 0..=(text.len() - k) {
-            let text_kmer: String = text[i..(i + k)].iter().collect();
-            let distance = hamming_in_list(dna, &text_kmer);
+        for (_, text_kmer) in Kmers::new(text.as_bytes(), k, alphabet) {
+            let distance = hamming_in_list(dna, text_kmer, alphabet);
             if distance < min_distance {
                 min_distance = distance;
                 min_pattern = text_kmer;
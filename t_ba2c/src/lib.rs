@@ -1,10 +1,10 @@
-use std::collections::HashMap;
 use std::path::Path;
 
 use anyhow::Error;
 use ndarray::Array2;
 
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2c(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -22,15 +22,18 @@ pub fn rosalind_ba2c(filename: &Path) -> Result<(), Error> {
             .flat_map(|line| line.into_iter())
             .collect(),
     )?;
-    println!("{}", get_profile_most_probable_kmer(text, k, &matrix));
+    let alphabet = Alphabet::dna();
+    println!(
+        "{}",
+        get_profile_most_probable_kmer(text, k, &matrix, &alphabet).decode(&alphabet)
+    );
     Ok(())
 }
 
-pub fn get_probability_kmer(kmer: &str, profile_matrix: &Array2<f64>) -> f64 {
-    let alphabet_map: HashMap<_, _> = "ACGT".chars().enumerate().map(|(n, c)| (c, n)).collect();
-    kmer.chars()
-        .enumerate()
-        .map(|(i, c)| profile_matrix[(alphabet_map[&c], i)])
+/// Probability of a k-mer under a profile with one row per symbol of the alphabet
+pub fn get_probability_kmer(kmer: Kmer, profile_matrix: &Array2<f64>, alphabet: &Alphabet) -> f64 {
+    (0..kmer.k())
+        .map(|i| profile_matrix[(kmer.code_at(i, alphabet) as usize, i)])
         .product()
 }
 
@@ -38,13 +41,12 @@ pub fn get_profile_most_probable_kmer(
     text: &str,
     k: usize,
     profile_matrix: &Array2<f64>,
-) -> String {
-    let text: Vec<_> = text.chars().collect();
+    alphabet: &Alphabet,
+) -> Kmer {
     let mut max_probability = -1.;
-    let mut max_kmer = String::new();
-    for i in 0..=(text.len() - k) {
-        let text_kmer: String = text[i..(i + k)].iter().collect();
-        let probability = get_probability_kmer(&text_kmer, profile_matrix);
+    let mut max_kmer = Kmer::default();
+    for (_, text_kmer) in Kmers::new(text.as_bytes(), k, alphabet) {
+        let probability = get_probability_kmer(text_kmer, profile_matrix, alphabet);
         if probability > max_probability {
             max_probability = probability;
             max_kmer = text_kmer;
@@ -1,11 +1,9 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
-use itertools::Itertools;
 use ndarray::{Array1, Array2};
 use std::path::Path;
 use t_ba2c::get_profile_most_probable_kmer;
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2d(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -13,17 +11,18 @@ pub fn rosalind_ba2d(filename: &Path) -> Result<(), Error> {
     let numbers = usize::parse_line(lines.next().unwrap())?;
     let (k, t) = (numbers[0], numbers[1]);
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
-    for motif in greedy_motif_search(&dna, k, t, false) {
-        println!("{}", motif);
+    let alphabet = Alphabet::dna();
+    for motif in greedy_motif_search(&dna, k, t, false, &alphabet) {
+        println!("{}", motif.decode(&alphabet));
     }
     Ok(())
 }
 
-pub fn get_profile(sequences: &[String], pseudocounts: bool) -> Array2<f64> {
-    let sequence_length = sequences[0].len();
-    let count_matrix = get_count_matrix(sequences, pseudocounts);
-    let mut matrix = Array2::zeros((4, sequence_length));
-    for i in 0..sequence_length {
+pub fn get_profile(motifs: &[Kmer], pseudocounts: bool, alphabet: &Alphabet) -> Array2<f64> {
+    let k = motifs[0].k();
+    let count_matrix = get_count_matrix(motifs, pseudocounts, alphabet);
+    let mut matrix = Array2::zeros((alphabet.size(), k));
+    for i in 0..k {
         let column = count_matrix.column(i).to_vec();
         let total: usize = column.iter().sum();
         matrix.column_mut(i).assign(&Array1::from_iter(
@@ -33,71 +32,104 @@ pub fn get_profile(sequences: &[String], pseudocounts: bool) -> Array2<f64> {
     matrix
 }
 
-fn get_count_matrix(sequences: &[String], pseudocounts: bool) -> Array2<usize> {
-    let sequence_length = sequences[0].len();
-    let mut matrix = Array2::zeros((4, sequence_length));
-    for i in 0..sequence_length {
-        let position_string = sequences
-            .iter()
-            .map(|sequence| sequence.chars().nth(i).unwrap())
-            .collect::<String>();
-        let counter = utility::string::char_counter(&position_string);
-        matrix
-            .column_mut(i)
-            .assign(&Array1::from_iter("ACGT".chars().map(|c| {
-                let count = *counter.get(&c).unwrap_or(&0);
-                if pseudocounts {
-                    count + 1
-                } else {
-                    count
-                }
-            })));
+fn get_count_matrix(motifs: &[Kmer], pseudocounts: bool, alphabet: &Alphabet) -> Array2<usize> {
+    let k = motifs[0].k();
+    let mut matrix = Array2::from_elem((alphabet.size(), k), usize::from(pseudocounts));
+    for motif in motifs {
+        for i in 0..k {
+            matrix[(motif.code_at(i, alphabet) as usize, i)] += 1;
+        }
     }
     matrix
 }
 
-fn get_consensus(count_matrix: &Array2<usize>) -> String {
-    let mut consensus = String::with_capacity(count_matrix.shape()[1]);
-    let alphabet_map: HashMap<_, _> = "ACGT".chars().enumerate().collect();
-    for i in 0..count_matrix.shape()[1] {
-        let mut sorted_column = count_matrix
-            .column(i)
-            .into_iter()
-            .enumerate()
-            .sorted_by(|a, b| a.1.cmp(b.1).reverse());
-        consensus.push(alphabet_map[&sorted_column.next().unwrap().0]);
-    }
-    consensus
+/// Most common symbol at each position, the first in the alphabet on ties
+fn get_consensus(count_matrix: &Array2<usize>, alphabet: &Alphabet) -> Kmer {
+    let k = count_matrix.shape()[1];
+    (0..k).fold(Kmer::new(0, k), |consensus, i| {
+        let column = count_matrix.column(i);
+        let code = (0..column.len()).fold(0, |best, code| {
+            if column[code] > column[best] {
+                code
+            } else {
+                best
+            }
+        });
+        consensus.with_code_at(i, code as u32, alphabet)
+    })
 }
 
-pub fn score_motifs(motifs: &[String], pseudocounts: bool) -> usize {
-    let consensus = get_consensus(&get_count_matrix(motifs, pseudocounts));
+pub fn score_motifs(motifs: &[Kmer], pseudocounts: bool, alphabet: &Alphabet) -> usize {
+    let consensus = get_consensus(&get_count_matrix(motifs, pseudocounts, alphabet), alphabet);
     motifs
         .iter()
-        .map(|motif| utility::string::hamming(motif, &consensus))
+        .map(|motif| motif.hamming_distance(consensus, alphabet))
         .sum()
 }
 
-pub fn greedy_motif_search(dna: &[String], k: usize, t: usize, pseudocounts: bool) -> Vec<String> {
+pub fn greedy_motif_search(
+    dna: &[String],
+    k: usize,
+    t: usize,
+    pseudocounts: bool,
+    alphabet: &Alphabet,
+) -> Vec<Kmer> {
     let mut best_motifs: Vec<_> = dna
         .iter()
         .map(|text| {
-            text.chars().collect::<Vec<_>>()[..k]
-                .iter()
-                .collect::<String>()
+            Kmers::new(text.as_bytes(), k, alphabet)
+                .next()
+                .map_or_else(Kmer::default, |(_, kmer)| kmer)
         })
         .collect();
-    let text_1: Vec<_> = dna[0].chars().collect();
     let mut profile;
-    for m in 0..=(text_1.len() - k) {
-        let mut motifs = vec![text_1[m..(m + k)].iter().collect::<String>()];
+    for (_, first_motif) in Kmers::new(dna[0].as_bytes(), k, alphabet) {
+        let mut motifs = vec![first_motif];
         for i in 1..t {
-            profile = get_profile(&motifs[..i], pseudocounts);
-            motifs.push(get_profile_most_probable_kmer(&dna[i], k, &profile));
+            profile = get_profile(&motifs[..i], pseudocounts, alphabet);
+            motifs.push(get_profile_most_probable_kmer(
+                &dna[i], k, &profile, alphabet,
+            ));
         }
-        if score_motifs(&motifs, pseudocounts) < score_motifs(&best_motifs, pseudocounts) {
+        if score_motifs(&motifs, pseudocounts, alphabet)
+            < score_motifs(&best_motifs, pseudocounts, alphabet)
+        {
             best_motifs = motifs;
         }
     }
     best_motifs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greedy_motifs() {
+        let alphabet = Alphabet::dna();
+        let dna: Vec<_> = [
+            "GGCGTTCAGGCA",
+            "AAGAATCAGTCA",
+            "CAAGGAGTTCGC",
+            "CACGTCAATCAC",
+            "CAATAATATTCG",
+        ]
+        .iter()
+        .map(|text| text.to_string())
+        .collect();
+        let decode = |motifs: Vec<Kmer>| -> Vec<String> {
+            motifs
+                .into_iter()
+                .map(|motif| motif.decode(&alphabet))
+                .collect()
+        };
+        assert_eq!(
+            decode(greedy_motif_search(&dna, 3, 5, false, &alphabet)),
+            vec!["CAG", "CAG", "CAA", "CAA", "CAA"]
+        );
+        assert_eq!(
+            decode(greedy_motif_search(&dna, 3, 5, true, &alphabet)),
+            vec!["TTC", "ATC", "TTC", "ATC", "TTC"]
+        );
+    }
+}
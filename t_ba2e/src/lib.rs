@@ -3,6 +3,7 @@ use anyhow::Error;
 use std::path::Path;
 use t_ba2d::greedy_motif_search;
 use utility::io::Parseable;
+use utility::kmer::Alphabet;
 
 pub fn rosalind_ba2e(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -10,8 +11,9 @@ pub fn rosalind_ba2e(filename: &Path) -> Result<(), Error> {
     let numbers = usize::parse_line(lines.next().unwrap())?;
     let (k, t) = (numbers[0], numbers[1]);
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
-    for motif in greedy_motif_search(&dna, k, t, true) {
-        println!("{}", motif);
+    let alphabet = Alphabet::dna();
+    for motif in greedy_motif_search(&dna, k, t, true, &alphabet) {
+        println!("{}", motif.decode(&alphabet));
     }
     Ok(())
 }
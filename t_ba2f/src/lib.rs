@@ -5,6 +5,7 @@ use std::path::Path;
 use t_ba2c::get_profile_most_probable_kmer;
 use t_ba2d::{get_profile, score_motifs};
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2f(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -12,16 +13,17 @@ pub fn rosalind_ba2f(filename: &Path) -> Result<(), Error> {
     let numbers = usize::parse_line(lines.next().unwrap())?;
     let (k, t) = (numbers[0], numbers[1]);
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
-    let mut best_motifs = randomized_motif_search(&dna, k, t, true);
+    let alphabet = Alphabet::dna();
+    let mut best_motifs = randomized_motif_search(&dna, k, t, true, &alphabet);
     let mut motifs;
     for _ in 1..1000 {
-        motifs = randomized_motif_search(&dna, k, t, true);
-        if score_motifs(&motifs, true) < score_motifs(&best_motifs, true) {
+        motifs = randomized_motif_search(&dna, k, t, true, &alphabet);
+        if score_motifs(&motifs, true, &alphabet) < score_motifs(&best_motifs, true, &alphabet) {
             best_motifs = motifs
         }
     }
     for motif in best_motifs {
-        println!("{}", motif);
+        println!("{}", motif.decode(&alphabet));
     }
     Ok(())
 }
@@ -37,22 +39,39 @@ pub fn rosalind_ba2f(filename: &Path) -> Result<(), Error> {
 //                BestMotifs ← Motifs
 //            else
 //                return BestMotifs
-fn randomized_motif_search(dna: &[String], k: usize, t: usize, pseudocounts: bool) -> Vec<String> {
-    let mut motifs: Vec<_> = (0..t)
-        .map(|i| utility::string::kmerize(&dna[i], k)[thread_rng().gen_range(0..t)].clone())
+fn randomized_motif_search(
+    dna: &[String],
+    k: usize,
+    t: usize,
+    pseudocounts: bool,
+    alphabet: &Alphabet,
+) -> Vec<Kmer> {
+    let mut motifs: Vec<_> = dna[..t]
+        .iter()
+        .map(|text| random_kmer(text, k, alphabet))
         .collect();
     let mut best_motifs = motifs.clone();
     let mut profile;
     loop {
-        profile = get_profile(&motifs, pseudocounts);
+        profile = get_profile(&motifs, pseudocounts, alphabet);
         motifs = dna
             .iter()
-            .map(|text| get_profile_most_probable_kmer(text, k, &profile))
+            .map(|text| get_profile_most_probable_kmer(text, k, &profile, alphabet))
             .collect();
-        if score_motifs(&motifs, pseudocounts) < score_motifs(&best_motifs, pseudocounts) {
+        if score_motifs(&motifs, pseudocounts, alphabet)
+            < score_motifs(&best_motifs, pseudocounts, alphabet)
+        {
             best_motifs = motifs.clone()
         } else {
             return best_motifs;
         }
     }
 }
+
+/// Uniformly random k-mer of a text
+pub fn random_kmer(text: &str, k: usize, alphabet: &Alphabet) -> Kmer {
+    let kmers: Vec<_> = Kmers::new(text.as_bytes(), k, alphabet)
+        .map(|(_, kmer)| kmer)
+        .collect();
+    kmers[thread_rng().gen_range(0..kmers.len())]
+}
@@ -6,7 +6,9 @@ use random_choice::random_choice;
 use std::path::Path;
 use t_ba2c::get_probability_kmer;
 use t_ba2d::{get_profile, score_motifs};
+use t_ba2f::random_kmer;
 use utility::io::Parseable;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2g(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
@@ -14,27 +16,35 @@ pub fn rosalind_ba2g(filename: &Path) -> Result<(), Error> {
     let numbers = usize::parse_line(lines.next().unwrap())?;
     let (k, t, n) = (numbers[0], numbers[1], numbers[2]);
     let dna: Vec<_> = lines.map(|l| l.to_owned()).collect();
-    let mut best_motifs = gibbs_sampler(&dna, k, t, n, true);
+    let alphabet = Alphabet::dna();
+    let mut best_motifs = gibbs_sampler(&dna, k, t, n, true, &alphabet);
     let mut motifs;
     for _ in 1..20 {
-        motifs = gibbs_sampler(&dna, k, t, n, true);
-        if score_motifs(&motifs, true) < score_motifs(&best_motifs, true) {
+        motifs = gibbs_sampler(&dna, k, t, n, true, &alphabet);
+        if score_motifs(&motifs, true, &alphabet) < score_motifs(&best_motifs, true, &alphabet) {
             best_motifs = motifs
         }
     }
     for motif in best_motifs {
-        println!("{}", motif);
+        println!("{}", motif.decode(&alphabet));
     }
     Ok(())
 }
 
-fn get_profile_random_kmer(text: &str, k: usize, profile_matrix: &Array2<f64>) -> String {
-    let kmers = utility::string::kmerize(text, k);
+fn get_profile_random_kmer(
+    text: &str,
+    k: usize,
+    profile_matrix: &Array2<f64>,
+    alphabet: &Alphabet,
+) -> Kmer {
+    let kmers: Vec<_> = Kmers::new(text.as_bytes(), k, alphabet)
+        .map(|(_, kmer)| kmer)
+        .collect();
     let probabilities: Vec<_> = kmers
         .iter()
-        .map(|kmer| get_probability_kmer(kmer, profile_matrix))
+        .map(|kmer| get_probability_kmer(*kmer, profile_matrix, alphabet))
         .collect();
-    random_choice().random_choice_f64(&kmers, &probabilities, 1)[0].to_owned()
+    *random_choice().random_choice_f64(&kmers, &probabilities, 1)[0]
 }
 
 // GIBBSSAMPLER(Dna, k, t, N)
@@ -49,18 +59,32 @@ fn get_profile_random_kmer(text: &str, k: usize, profile_matrix: &Array2<f64>) -
 //            if Score(Motifs) < Score(BestMotifs)
 //                BestMotifs ← Motifs
 //        return BestMotifs
-fn gibbs_sampler(dna: &[String], k: usize, t: usize, n: usize, pseudocounts: bool) -> Vec<String> {
-    let mut motifs: Vec<_> = (0..t)
-        .map(|i| utility::string::kmerize(&dna[i], k)[thread_rng().gen_range(0..t)].clone())
+fn gibbs_sampler(
+    dna: &[String],
+    k: usize,
+    t: usize,
+    n: usize,
+    pseudocounts: bool,
+    alphabet: &Alphabet,
+) -> Vec<Kmer> {
+    let mut motifs: Vec<_> = dna[..t]
+        .iter()
+        .map(|text| random_kmer(text, k, alphabet))
         .collect();
     let mut best_motifs = motifs.clone();
     let mut profile;
     let mut i;
     for _ in 0..n {
         i = thread_rng().gen_range(0..t);
-        profile = get_profile(&[&motifs[..i], &motifs[(i + 1)..]].concat(), pseudocounts);
-        motifs[i] = get_profile_random_kmer(&dna[i], k, &profile);
-        if score_motifs(&motifs, pseudocounts) < score_motifs(&best_motifs, pseudocounts) {
+        profile = get_profile(
+            &[&motifs[..i], &motifs[(i + 1)..]].concat(),
+            pseudocounts,
+            alphabet,
+        );
+        motifs[i] = get_profile_random_kmer(&dna[i], k, &profile, alphabet);
+        if score_motifs(&motifs, pseudocounts, alphabet)
+            < score_motifs(&best_motifs, pseudocounts, alphabet)
+        {
             best_motifs = motifs.clone()
         }
     }
@@ -1,25 +1,36 @@
 use anyhow::Error;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmer, Kmers};
 
 pub fn rosalind_ba2h(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let dna: Vec<_> = lines[1].split(' ').map(|l| l.to_owned()).collect();
+    let alphabet = Alphabet::dna();
+    let pattern = Kmer::encode(lines[0].trim().as_bytes(), &alphabet).ok_or_else(|| {
+        utility::errors::RosalindParseError::InputFormatError(String::from(
+            "Pattern isn't DNA or is too long",
+        ))
+    })?;
     println!(
         "{}",
-        get_distance_between_pattern_and_strings(lines[0], &dna)
+        get_distance_between_pattern_and_strings(pattern, &dna, &alphabet)
     );
     Ok(())
 }
 
-fn get_distance_between_pattern_and_strings(pattern: &str, dna: &[String]) -> usize {
-    let k = pattern.len();
+fn get_distance_between_pattern_and_strings(
+    pattern: Kmer,
+    dna: &[String],
+    alphabet: &Alphabet,
+) -> usize {
+    let k = pattern.k();
     let mut distance = 0;
     for text in dna {
         let mut hamming_distance = k + 1;
-        for kmer in utility::string::kmerize(text, k) {
-            let kmer_distance = utility::string::hamming(pattern, &kmer);
+        for (_, kmer) in Kmers::new(text.as_bytes(), k, alphabet) {
+            let kmer_distance = kmer.hamming_distance(pattern, alphabet);
             if hamming_distance > kmer_distance {
                 hamming_distance = kmer_distance;
             }
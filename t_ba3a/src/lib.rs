@@ -1,13 +1,24 @@
 use anyhow::Error;
 
 use std::path::Path;
+use utility::kmer::{Alphabet, Kmers};
 
 pub fn rosalind_ba3a(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines = contents.split('\n').collect::<Vec<_>>();
     let k = lines[0].parse::<usize>()?;
-    for kmer in utility::string::kmerize(lines[1], k) {
-        println!("{}", kmer);
+    let alphabet = Alphabet::dna();
+    if k == 0 || k > alphabet.max_k::<u128>() {
+        return Err(
+            utility::errors::RosalindParseError::InputFormatError(format!(
+                "k must be between 1 and {}",
+                alphabet.max_k::<u128>()
+            ))
+            .into(),
+        );
+    }
+    for (_, kmer) in Kmers::<u128>::new(lines[1].trim().as_bytes(), k, &alphabet) {
+        println!("{}", kmer.decode(&alphabet));
     }
     Ok(())
 }
@@ -1,5 +1,6 @@
 use std::collections::btree_map::BTreeMap;
 
+use a_scc::StronglyConnected;
 use anyhow::Error;
 use std::path::Path;
 use t_ba3f::EulerianCycle;
@@ -66,25 +67,70 @@ impl EulerianPath for utility::graph::IntegerGraph {
                 std::cmp::Ordering::Equal => {}
             }
         }
-        let mut new_graph = self.clone();
-        let start_node = match (unbalanced_incoming, unbalanced_outgoing) {
+        let mut adjacency_list = self.adjacency_list.clone();
+        // an edge closing the path into a cycle
+        let added_edge = match (unbalanced_incoming, unbalanced_outgoing) {
             (Some(incoming), Some(outgoing)) => {
-                new_graph
-                    .adjacency_list
-                    .entry(new_graph.nodes[incoming])
-                    .or_insert_with(Vec::new)
-                    .push(new_graph.nodes[outgoing]);
-                Some(outgoing)
+                let edge = (self.nodes[incoming], self.nodes[outgoing]);
+                adjacency_list.entry(edge.0).or_default().push(edge.1);
+                Some(edge)
             }
             (None, None) => None,
             _ => return None,
         };
-        match new_graph.get_eulerian_cycle(start_node) {
-            Some(cycle) => {
-                let length = cycle.len();
-                Some(cycle.into_iter().take(length - 1).collect())
-            }
-            None => None,
+        // isolated nodes can't be on the path, so only the nodes with edges need to be connected
+        adjacency_list.retain(|_, edges| !edges.is_empty());
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .copied()
+            .filter(|node| {
+                adjacency_list.contains_key(node) || adjacency_list_reverse.contains_key(node)
+            })
+            .collect();
+        if nodes.is_empty() {
+            return Some(Vec::new());
+        }
+        let new_graph = utility::graph::IntegerGraph::new(adjacency_list, nodes, false);
+        let start_node = added_edge.map(|(_, outgoing)| new_graph.node_to_index[&outgoing]);
+        // a balanced graph has an Eulerian cycle only if it is strongly connected
+        if new_graph
+            .get_strongly_connected_components()
+            .num_components()
+            > 1
+        {
+            return None;
         }
+        let mut cycle = new_graph.get_eulerian_cycle(start_node)?;
+        cycle.pop();
+        if let Some(edge) = added_edge {
+            // the path starts just after the added edge, wherever the cycle used it
+            let length = cycle.len();
+            let position = (0..length)
+                .find(|&i| (cycle[i], cycle[(i + 1) % length]) == edge)
+                .unwrap();
+            cycle.rotate_left((position + 1) % length);
+        }
+        Some(cycle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utility::graph::IntegerGraph;
+
+    #[test]
+    fn isolated_nodes() {
+        let adjacency_list: BTreeMap<_, _> = vec![(0, vec![1]), (1, vec![2]), (2, vec![0, 3])]
+            .into_iter()
+            .collect();
+        let graph = IntegerGraph::new(adjacency_list.clone(), vec![0, 1, 2, 3, 4], false);
+        assert_eq!(graph.get_eulerian_path(), Some(vec![2, 0, 1, 2, 3]));
+        // two pieces with edges still have no path
+        let mut disconnected = adjacency_list;
+        disconnected.insert(5, vec![6]);
+        let graph = IntegerGraph::new(disconnected, vec![0, 1, 2, 3, 4, 5, 6], false);
+        assert_eq!(graph.get_eulerian_path(), None);
     }
 }
@@ -1,14 +1,17 @@
 use anyhow::Error;
 
-use s_lexf::enumerate_lex;
 use std::path::Path;
 use t_ba3d::de_bruijn_graph;
 use t_ba3f::EulerianCycle;
+use utility::kmer::{Alphabet, Kmer};
 
 pub fn rosalind_ba3i(filename: &Path) -> Result<(), Error> {
     let contents = utility::io::input_from_file(filename)?;
     let length = contents.parse::<usize>()?;
-    let patterns = enumerate_lex(&['0', '1'], length);
+    let binary = Alphabet::new(b"01");
+    let patterns: Vec<_> = (0..1u128 << length)
+        .map(|rank| Kmer::<u128>::from_rank(rank, length, &binary).decode(&binary))
+        .collect();
     let adjacency_list = de_bruijn_graph(&patterns);
     let (index_to_node, indexed_adjacency_list) = utility::graph::convert_graph(&adjacency_list);
     let graph = utility::graph::IntegerGraph::new(
@@ -0,0 +1,479 @@
+//! Pairwise alignment of two strings in any of the usual modes, with linear or affine gaps.
+//!
+//! Gotoh's three-state recurrence is filled one row at a time, keeping only two rows of scores
+//! and one byte of traceback per cell. A linear gap model is the affine one with equal
+//! opening and extension penalties.
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::Error;
+use ndarray::Array2;
+
+use utility::errors::RosalindParseError;
+
+/// Score of cells no alignment reaches, low enough that subtracting penalties cannot overflow
+const UNREACHABLE: isize = isize::MIN / 4;
+
+// Traceback byte of a cell: the state the best score came from (bits 0-1), and whether the
+// deletion (bit 2) and insertion (bit 3) states extend a gap rather than open one
+const FROM_START: u8 = 0;
+const FROM_DIAGONAL: u8 = 1;
+const FROM_DELETION: u8 = 2;
+const FROM_INSERTION: u8 = 3;
+const DELETION_EXTENDS: u8 = 4;
+const INSERTION_EXTENDS: u8 = 8;
+
+/// Which parts of string_1 (v) and string_2 (w) have to be aligned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlignmentMode {
+    /// All of v with all of w
+    Global,
+    /// A substring of v with a substring of w
+    Local,
+    /// All of w with a substring of v
+    Fitting,
+    /// A suffix of v with a prefix of w
+    Overlap,
+    /// All of v with all of w, without penalising gaps at the start or end of either
+    SemiGlobal,
+}
+
+impl AlignmentMode {
+    /// Whether an alignment may start at (i, j) at no cost
+    fn free_start(self, i: usize, j: usize) -> bool {
+        match self {
+            AlignmentMode::Global => i == 0 && j == 0,
+            AlignmentMode::Local => true,
+            AlignmentMode::Fitting | AlignmentMode::Overlap => j == 0,
+            AlignmentMode::SemiGlobal => i == 0 || j == 0,
+        }
+    }
+
+    /// Whether an alignment may end at (i, j) of an m x n matrix at no cost
+    fn free_end(self, i: usize, j: usize, m: usize, n: usize) -> bool {
+        match self {
+            AlignmentMode::Global => i == m && j == n,
+            AlignmentMode::Local => true,
+            AlignmentMode::Fitting => j == n,
+            AlignmentMode::Overlap => i == m,
+            AlignmentMode::SemiGlobal => i == m || j == n,
+        }
+    }
+}
+
+/// Penalty (subtracted from the score) of a run of gaps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GapModel {
+    /// Every gap costs the same
+    Linear(isize),
+    /// The first gap of a run costs `open` and each further one `extend`
+    Affine { open: isize, extend: isize },
+}
+
+impl GapModel {
+    pub fn open(self) -> isize {
+        match self {
+            GapModel::Linear(penalty) => penalty,
+            GapModel::Affine { open, .. } => open,
+        }
+    }
+
+    pub fn extend(self) -> isize {
+        match self {
+            GapModel::Linear(penalty) => penalty,
+            GapModel::Affine { extend, .. } => extend,
+        }
+    }
+
+    /// Penalty of a run of `length` gaps
+    pub fn penalty(self, length: usize) -> isize {
+        if length == 0 {
+            0
+        } else {
+            self.open() + (length as isize - 1) * self.extend()
+        }
+    }
+}
+
+/// Scores of aligning each pair of symbols
+#[derive(Debug, Clone)]
+pub struct ScoringMatrix {
+    scores: Array2<isize>,
+    symbol_order: HashMap<char, usize>,
+}
+
+impl ScoringMatrix {
+    /// `scores[(a, b)]` scores `symbols[a]` against `symbols[b]`
+    pub fn new(scores: Array2<isize>, symbols: Vec<char>) -> Self {
+        let symbol_order = symbols
+            .into_iter()
+            .enumerate()
+            .map(|(i, symbol)| (symbol, i))
+            .collect();
+        ScoringMatrix {
+            scores,
+            symbol_order,
+        }
+    }
+
+    /// Matrix in the format of BLOSUM62 and PAM250 files: a header line of symbols, then one
+    /// line per symbol with the symbol and its scores against each symbol of the header
+    pub fn from_file(filename: &Path) -> Result<Self, Error> {
+        let error = |message: String| {
+            RosalindParseError::InputFormatError(format!("{}: {}", filename.display(), message))
+        };
+        let contents = utility::io::input_from_file(filename)?;
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let symbols = lines
+            .next()
+            .ok_or_else(|| error(String::from("empty scoring matrix")))?
+            .split_whitespace()
+            .map(|symbol| {
+                let mut chars = symbol.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(error(format!("{:?} is not a single symbol", symbol))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut scores = Array2::zeros((symbols.len(), symbols.len()));
+        let mut rows = 0;
+        for line in lines {
+            let parts: Vec<_> = line.split_whitespace().collect();
+            let symbol = symbols
+                .get(rows)
+                .ok_or_else(|| error(String::from("more rows than symbols")))?;
+            if parts[0] != symbol.to_string() || parts.len() != symbols.len() + 1 {
+                return Err(error(format!(
+                    "row {} should be {} followed by {} scores",
+                    rows + 1,
+                    symbol,
+                    symbols.len()
+                ))
+                .into());
+            }
+            for (column, score) in parts[1..].iter().enumerate() {
+                scores[(rows, column)] = score
+                    .parse::<isize>()
+                    .map_err(|_| error(format!("{:?} is not a score", score)))?;
+            }
+            rows += 1;
+        }
+        if rows != symbols.len() {
+            return Err(error(format!("{} rows for {} symbols", rows, symbols.len())).into());
+        }
+        Ok(ScoringMatrix::new(scores, symbols))
+    }
+
+    /// `match_score` for equal symbols and `mismatch_score` for different ones
+    pub fn uniform(
+        symbols: impl IntoIterator<Item = char>,
+        match_score: isize,
+        mismatch_score: isize,
+    ) -> Self {
+        let mut symbols: Vec<_> = symbols.into_iter().collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        let mut scores = Array2::from_elem((symbols.len(), symbols.len()), mismatch_score);
+        scores.diag_mut().fill(match_score);
+        ScoringMatrix::new(scores, symbols)
+    }
+
+    pub fn score(&self, symbol_1: char, symbol_2: char) -> Option<isize> {
+        Some(
+            self.scores[(
+                *self.symbol_order.get(&symbol_1)?,
+                *self.symbol_order.get(&symbol_2)?,
+            )],
+        )
+    }
+
+    /// Row and column index of each character of a string
+    pub fn indices(&self, string: &str) -> Result<Vec<usize>, Error> {
+        string
+            .chars()
+            .map(|c| {
+                self.symbol_order
+                    .get(&c)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("{:?} is not in the scoring matrix", c))
+            })
+            .collect()
+    }
+
+    /// Score of the symbols with indices `index_1` and `index_2`
+    pub fn score_indices(&self, index_1: usize, index_2: usize) -> isize {
+        self.scores[(index_1, index_2)]
+    }
+}
+
+/// Column of an alignment of string_1 with string_2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlignmentOperation {
+    Match,
+    Substitution,
+    /// character of string_2 against a gap
+    Insertion,
+    /// character of string_1 against a gap
+    Deletion,
+}
+
+impl AlignmentOperation {
+    /// Number of characters consumed from (string_1, string_2)
+    pub fn steps(self) -> (usize, usize) {
+        match self {
+            AlignmentOperation::Match | AlignmentOperation::Substitution => (1, 1),
+            AlignmentOperation::Insertion => (0, 1),
+            AlignmentOperation::Deletion => (1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+    pub score: isize,
+    /// Characters (not bytes) of string_1 that are aligned
+    pub range_1: Range<usize>,
+    /// Characters of string_2 that are aligned
+    pub range_2: Range<usize>,
+    /// The aligned parts of the strings with '-' for gaps
+    pub aligned_1: String,
+    pub aligned_2: String,
+    /// Columns from left to right
+    pub operations: Vec<AlignmentOperation>,
+}
+
+impl Alignment {
+    /// Alignment given by its columns, starting at character `start.0` of string_1 and
+    /// `start.1` of string_2
+    pub fn from_operations(
+        score: isize,
+        string_1: &[char],
+        string_2: &[char],
+        start: (usize, usize),
+        operations: Vec<AlignmentOperation>,
+    ) -> Self {
+        let (mut i, mut j) = start;
+        let (mut aligned_1, mut aligned_2) = (String::new(), String::new());
+        for operation in &operations {
+            let (step_1, step_2) = operation.steps();
+            aligned_1.push(if step_1 == 1 { string_1[i] } else { '-' });
+            aligned_2.push(if step_2 == 1 { string_2[j] } else { '-' });
+            i += step_1;
+            j += step_2;
+        }
+        Alignment {
+            score,
+            range_1: start.0..i,
+            range_2: start.1..j,
+            aligned_1,
+            aligned_2,
+            operations,
+        }
+    }
+
+    /// Whether this is an alignment of character ranges of the two strings: the aligned strings
+    /// spell out `range_1` and `range_2` with gaps where the operations put them, and the
+    /// columns marked as matches are exactly those with equal characters
+    pub fn aligns(&self, string_1: &str, string_2: &str) -> bool {
+        let chars_1: Vec<_> = string_1.chars().collect();
+        let chars_2: Vec<_> = string_2.chars().collect();
+        let (length_1, length_2) = self
+            .operations
+            .iter()
+            .map(|operation| operation.steps())
+            .fold((0, 0), |(i, j), (step_1, step_2)| (i + step_1, j + step_2));
+        if self.range_1.len() != length_1
+            || self.range_2.len() != length_2
+            || self.range_1.end > chars_1.len()
+            || self.range_2.end > chars_2.len()
+        {
+            return false;
+        }
+        let start = (self.range_1.start, self.range_2.start);
+        let matches_consistent = self
+            .operations
+            .iter()
+            .zip(self.aligned_1.chars().zip(self.aligned_2.chars()))
+            .all(|(operation, (a, b))| match operation {
+                AlignmentOperation::Match => a == b,
+                AlignmentOperation::Substitution => a != b,
+                _ => true,
+            });
+        matches_consistent
+            && Alignment::from_operations(
+                self.score,
+                &chars_1,
+                &chars_2,
+                start,
+                self.operations.clone(),
+            ) == *self
+    }
+}
+
+/// Aligns pairs of strings with a mode, a scoring matrix and a gap model
+#[derive(Debug, Clone)]
+pub struct Aligner {
+    pub mode: AlignmentMode,
+    pub scoring_matrix: ScoringMatrix,
+    pub gap_model: GapModel,
+}
+
+impl Aligner {
+    pub fn new(mode: AlignmentMode, scoring_matrix: ScoringMatrix, gap_model: GapModel) -> Self {
+        Aligner {
+            mode,
+            scoring_matrix,
+            gap_model,
+        }
+    }
+
+    /// Score of an alignment of the two strings under this aligner's scoring, recomputed from
+    /// its columns
+    pub fn rescore(
+        &self,
+        string_1: &str,
+        string_2: &str,
+        alignment: &Alignment,
+    ) -> Result<isize, Error> {
+        let indices_1 = self.scoring_matrix.indices(string_1)?;
+        let indices_2 = self.scoring_matrix.indices(string_2)?;
+        let (mut i, mut j) = (alignment.range_1.start, alignment.range_2.start);
+        let mut score = 0;
+        let mut previous = None;
+        for operation in &alignment.operations {
+            score += match operation {
+                AlignmentOperation::Match | AlignmentOperation::Substitution => self
+                    .scoring_matrix
+                    .score_indices(indices_1[i], indices_2[j]),
+                _ if previous == Some(*operation) => -self.gap_model.extend(),
+                _ => -self.gap_model.open(),
+            };
+            let (step_1, step_2) = operation.steps();
+            i += step_1;
+            j += step_2;
+            previous = Some(*operation);
+        }
+        Ok(score)
+    }
+
+    /// Highest-scoring alignment. Ties are broken, from the end of the alignment, in favour of
+    /// a match or substitution, then a deletion, then an insertion, and gaps are extended
+    /// rather than opened.
+    pub fn align(&self, string_1: &str, string_2: &str) -> Result<Alignment, Error> {
+        let indices_1 = self.scoring_matrix.indices(string_1)?;
+        let indices_2 = self.scoring_matrix.indices(string_2)?;
+        let (m, n) = (indices_1.len(), indices_2.len());
+        let (open, extend) = (self.gap_model.open(), self.gap_model.extend());
+
+        let mut traceback = Array2::<u8>::zeros((m + 1, n + 1));
+        // best score, and best ending in a deletion, of the previous and current rows
+        let mut previous_best = vec![UNREACHABLE; n + 1];
+        let mut previous_deletion = vec![UNREACHABLE; n + 1];
+        let mut best = vec![UNREACHABLE; n + 1];
+        let mut deletion = vec![UNREACHABLE; n + 1];
+        let (mut end, mut end_score) = ((0, 0), UNREACHABLE);
+        for i in 0..=m {
+            let mut insertion = UNREACHABLE;
+            for j in 0..=n {
+                let mut trace = 0;
+                deletion[j] = UNREACHABLE;
+                if i > 0 {
+                    let (extended, opened) =
+                        (previous_deletion[j] - extend, previous_best[j] - open);
+                    if extended >= opened {
+                        deletion[j] = extended;
+                        trace |= DELETION_EXTENDS;
+                    } else {
+                        deletion[j] = opened;
+                    }
+                }
+                if j > 0 {
+                    let (extended, opened) = (insertion - extend, best[j - 1] - open);
+                    if extended >= opened {
+                        insertion = extended;
+                        trace |= INSERTION_EXTENDS;
+                    } else {
+                        insertion = opened;
+                    }
+                }
+                let diagonal = if i > 0 && j > 0 {
+                    previous_best[j - 1]
+                        + self
+                            .scoring_matrix
+                            .score_indices(indices_1[i - 1], indices_2[j - 1])
+                } else {
+                    UNREACHABLE
+                };
+                let (mut from, mut score) = (FROM_DIAGONAL, diagonal);
+                if deletion[j] > score {
+                    from = FROM_DELETION;
+                    score = deletion[j];
+                }
+                if insertion > score {
+                    from = FROM_INSERTION;
+                    score = insertion;
+                }
+                if self.mode.free_start(i, j) && 0 > score {
+                    from = FROM_START;
+                    score = 0;
+                }
+                best[j] = score;
+                traceback[(i, j)] = trace | from;
+                if self.mode.free_end(i, j, m, n) && score > end_score {
+                    end = (i, j);
+                    end_score = score;
+                }
+            }
+            std::mem::swap(&mut previous_best, &mut best);
+            std::mem::swap(&mut previous_deletion, &mut deletion);
+        }
+
+        let (mut i, mut j) = end;
+        let mut operations = Vec::new();
+        let mut state = FROM_DIAGONAL;
+        loop {
+            let trace = traceback[(i, j)];
+            match state {
+                FROM_DELETION => {
+                    operations.push(AlignmentOperation::Deletion);
+                    if trace & DELETION_EXTENDS == 0 {
+                        state = FROM_DIAGONAL;
+                    }
+                    i -= 1;
+                }
+                FROM_INSERTION => {
+                    operations.push(AlignmentOperation::Insertion);
+                    if trace & INSERTION_EXTENDS == 0 {
+                        state = FROM_DIAGONAL;
+                    }
+                    j -= 1;
+                }
+                _ => match trace & 3 {
+                    FROM_START => break,
+                    FROM_DIAGONAL => {
+                        operations.push(if indices_1[i - 1] == indices_2[j - 1] {
+                            AlignmentOperation::Match
+                        } else {
+                            AlignmentOperation::Substitution
+                        });
+                        i -= 1;
+                        j -= 1;
+                    }
+                    from => state = from,
+                },
+            }
+        }
+        operations.reverse();
+        let (chars_1, chars_2): (Vec<_>, Vec<_>) =
+            (string_1.chars().collect(), string_2.chars().collect());
+        Ok(Alignment::from_operations(
+            end_score,
+            &chars_1,
+            &chars_2,
+            (i, j),
+            operations,
+        ))
+    }
+}
@@ -1,8 +1,10 @@
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Error;
-use ndarray::{Array, Array2};
+
+pub use aligner::{Aligner, Alignment, AlignmentMode, AlignmentOperation, GapModel, ScoringMatrix};
+
+mod aligner;
 
 /// Find a Highest-Scoring Alignment of Two Strings
 ///
@@ -11,145 +13,212 @@ use ndarray::{Array, Array2};
 /// Return: The maximum alignment score of these strings followed by an alignment achieving this
 /// maximum score. Use the BLOSUM62 scoring matrix and indel penalty σ = 5.
 /// (If multiple alignments achieving the maximum score exist, you may return any one.)
-pub fn rosalind_ba5e(filename: &Path) -> Result<(), Error> {
+pub fn rosalind_ba5e(filename: &Path) -> Result<Alignment, Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = contents.split('\n').collect();
-    let (scoring_matrix, amino_acids) = read_scoring_matrix(&PathBuf::from("data/blosum62.txt"))?;
-    let parameters = AlignmentParameters::new(scoring_matrix, amino_acids, 5);
-    let (score, aln_string_1, aln_string_2) = global_align(lines[0], lines[1], &parameters);
-    println!("{}\n{}\n{}", score, aln_string_1, aln_string_2);
-    Ok(())
-}
-
-pub fn read_scoring_matrix(filename: &Path) -> Result<(Array2<isize>, Vec<char>), Error> {
-    let contents = utility::io::input_from_file(filename)?;
-    let mut lines = contents.split('\n');
-    let amino_acids: Vec<_> = lines
-        .next()
-        .unwrap()
-        .trim_start()
-        .trim_end()
-        .split_whitespace()
-        .map(|s| s.chars().next().unwrap())
+    let blosum_file: PathBuf = [env!("CARGO_WORKSPACE_DIR"), utility::io::BLOSUM_FILE]
+        .iter()
         .collect();
-    let mut scoring_matrix = Array2::zeros((amino_acids.len(), amino_acids.len()));
-    for (i, line) in lines.enumerate() {
-        let parts: Vec<_> = line.split_whitespace().collect();
-        scoring_matrix.row_mut(i).assign(&Array::from(
-            parts[1..]
-                .iter()
-                .map(|x| x.parse::<isize>().unwrap())
-                .collect::<Vec<_>>(),
-        ));
-    }
-    Ok((scoring_matrix, amino_acids))
+    let aligner = Aligner::new(
+        AlignmentMode::Global,
+        ScoringMatrix::from_file(&blosum_file)?,
+        GapModel::Linear(5),
+    );
+    let alignment = aligner.align(lines[0], lines[1])?;
+    println!(
+        "{}\n{}\n{}",
+        alignment.score, alignment.aligned_1, alignment.aligned_2
+    );
+    Ok(alignment)
 }
 
-pub struct AlignmentParameters {
-    pub scoring_matrix: Array2<isize>,
-    pub amino_acid_order: HashMap<char, usize>,
-    pub gap_penalty: isize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl AlignmentParameters {
-    pub fn new(scoring_matrix: Array2<isize>, amino_acids: Vec<char>, gap_penalty: isize) -> Self {
-        let amino_acid_order: HashMap<_, _> = amino_acids
-            .into_iter()
-            .enumerate()
-            .map(|(i, a)| (a, i))
-            .collect();
-        AlignmentParameters {
-            scoring_matrix,
-            amino_acid_order,
-            gap_penalty,
-        }
+    #[test]
+    fn ba5e() -> Result<(), Error> {
+        let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_ba5e")?;
+        let output = utility::io::input_from_file(&output_file)?;
+        let alignment = rosalind_ba5e(&input_file)?;
+        assert_eq!(
+            alignment.score,
+            output.lines().next().unwrap().parse::<isize>()?
+        );
+        // any alignment achieving the score is accepted
+        let input = utility::io::input_from_file(&input_file)?;
+        let lines: Vec<_> = input.lines().collect();
+        assert!(alignment.aligns(lines[0], lines[1]));
+        Ok(())
     }
-}
 
-pub fn global_alignment_backtrack(
-    string_1: &str,
-    string_2: &str,
-    parameters: &AlignmentParameters,
-) -> (Array2<isize>, Array2<usize>) {
-    let chars_1: Vec<_> = string_1.chars().collect();
-    let chars_2: Vec<_> = string_2.chars().collect();
-    let mut scores = Array2::zeros((chars_1.len() + 1, chars_2.len() + 1));
-    let mut backtrack = Array2::zeros((chars_1.len() + 1, chars_2.len() + 1));
-    for i in 1..chars_1.len() {
-        scores[(i, 0)] = scores[(i - 1, 0)] - parameters.gap_penalty;
-        backtrack[(i, 0)] = 1;
+    /// Every alignment of strings of lengths m and n, with diagonal columns as matches
+    fn all_operations(m: usize, n: usize) -> Vec<Vec<AlignmentOperation>> {
+        if m == 0 && n == 0 {
+            return vec![Vec::new()];
+        }
+        let mut alignments = Vec::new();
+        for operation in [
+            AlignmentOperation::Match,
+            AlignmentOperation::Deletion,
+            AlignmentOperation::Insertion,
+        ] {
+            let (step_1, step_2) = operation.steps();
+            if step_1 <= m && step_2 <= n {
+                for mut alignment in all_operations(m - step_1, n - step_2) {
+                    alignment.push(operation);
+                    alignments.push(alignment);
+                }
+            }
+        }
+        alignments
     }
-    for j in 1..=chars_2.len() {
-        scores[(0, j)] = scores[(0, j - 1)] - parameters.gap_penalty;
-        backtrack[(0, j)] = 2;
+
+    /// Whether the mode aligns string_1[a..b] with string_2[c..d] of strings of lengths m and n
+    fn allowed(
+        mode: AlignmentMode,
+        (a, b, c, d): (usize, usize, usize, usize),
+        m: usize,
+        n: usize,
+    ) -> bool {
+        match mode {
+            AlignmentMode::Global => a == 0 && b == m && c == 0 && d == n,
+            AlignmentMode::Local => true,
+            AlignmentMode::Fitting => c == 0 && d == n,
+            AlignmentMode::Overlap => b == m && c == 0,
+            AlignmentMode::SemiGlobal => (a == 0 || c == 0) && (b == m || d == n),
+        }
     }
-    for i in 1..=chars_1.len() {
-        for j in 1..=chars_2.len() {
-            let values: Vec<isize> = vec![
-                (scores[(i - 1, j)] - parameters.gap_penalty),
-                (scores[(i, j - 1)] - parameters.gap_penalty),
-                (scores[(i - 1, j - 1)]
-                    + parameters.scoring_matrix[(
-                        parameters.amino_acid_order[&chars_1[i - 1]],
-                        parameters.amino_acid_order[&chars_2[j - 1]],
-                    )]),
-            ];
-            let (max_index, max_value) = values
-                .into_iter()
-                .enumerate()
-                .max_by(|a, b| a.1.cmp(&b.1))
-                .unwrap();
-            scores[(i, j)] = max_value;
-            backtrack[(i, j)] = max_index + 1;
+
+    /// Highest score of all the alignments the aligner's mode allows
+    fn brute_force_score(aligner: &Aligner, string_1: &str, string_2: &str) -> isize {
+        let chars_1: Vec<_> = string_1.chars().collect();
+        let chars_2: Vec<_> = string_2.chars().collect();
+        let (m, n) = (chars_1.len(), chars_2.len());
+        let mut best = isize::MIN;
+        for a in 0..=m {
+            for b in a..=m {
+                for c in 0..=n {
+                    for d in c..=n {
+                        if !allowed(aligner.mode, (a, b, c, d), m, n) {
+                            continue;
+                        }
+                        for operations in all_operations(b - a, d - c) {
+                            let alignment = Alignment::from_operations(
+                                0,
+                                &chars_1,
+                                &chars_2,
+                                (a, c),
+                                operations,
+                            );
+                            best =
+                                best.max(aligner.rescore(string_1, string_2, &alignment).unwrap());
+                        }
+                    }
+                }
+            }
         }
+        best
     }
-    (scores, backtrack)
-}
 
-pub fn align(
-    backtrack: &Array2<usize>,
-    string_1: &[char],
-    string_2: &[char],
-    n: usize,
-    m: usize,
-) -> (String, String) {
-    let (mut aln_1, mut aln_2) = (String::new(), String::new());
-    let (mut n, mut m) = (n, m);
-    while !(n == 0 && m == 0) {
-        if backtrack[(n, m)] == 0 {
-            break;
-        } else if m == 0 || backtrack[(n, m)] == 1 {
-            n -= 1;
-            aln_1.push(string_1[n]);
-            aln_2.push('-');
-        } else if n == 0 || backtrack[(n, m)] == 2 {
-            m -= 1;
-            aln_1.push('-');
-            aln_2.push(string_2[m]);
-        } else {
-            n -= 1;
-            m -= 1;
-            aln_1.push(string_1[n]);
-            aln_2.push(string_2[m]);
+    #[test]
+    fn alignment_modes() -> Result<(), Error> {
+        let random_string =
+            |seed: u64| utility::testing::random_sequence(seed as usize % 5, b"ACGT", seed);
+        let modes = [
+            AlignmentMode::Global,
+            AlignmentMode::Local,
+            AlignmentMode::Fitting,
+            AlignmentMode::Overlap,
+            AlignmentMode::SemiGlobal,
+        ];
+        let gap_models = [GapModel::Linear(1), GapModel::Affine { open: 3, extend: 1 }];
+        for seed in 0..20 {
+            let (string_1, string_2) = (random_string(2 * seed), random_string(3 * seed + 1));
+            for mode in modes {
+                for gap_model in gap_models {
+                    let aligner = Aligner::new(
+                        mode,
+                        ScoringMatrix::uniform("ACGT".chars(), 2, -1),
+                        gap_model,
+                    );
+                    let alignment = aligner.align(&string_1, &string_2)?;
+                    assert_eq!(
+                        alignment.score,
+                        brute_force_score(&aligner, &string_1, &string_2),
+                        "{:?} {:?} {} {}",
+                        mode,
+                        gap_model,
+                        string_1,
+                        string_2
+                    );
+                    assert_eq!(
+                        alignment.score,
+                        aligner.rescore(&string_1, &string_2, &alignment)?
+                    );
+                    let (m, n) = (string_1.len(), string_2.len());
+                    let (a, b) = (alignment.range_1.start, alignment.range_1.end);
+                    let (c, d) = (alignment.range_2.start, alignment.range_2.end);
+                    assert!(allowed(mode, (a, b, c, d), m, n));
+                    assert!(alignment.aligns(&string_1, &string_2));
+                }
+            }
         }
+
+        // one long gap is cheaper than two short ones under affine penalties
+        let scoring_matrix = ScoringMatrix::uniform("ACGT".chars(), 1, -1);
+        let affine = Aligner::new(
+            AlignmentMode::Global,
+            scoring_matrix.clone(),
+            GapModel::Affine { open: 4, extend: 1 },
+        );
+        let alignment = affine.align("ACGTACGT", "ACGT")?;
+        assert_eq!(alignment.score, 4 - 7);
+        assert!(alignment.aligned_2.contains("----"));
+        let local = Aligner::new(AlignmentMode::Local, scoring_matrix, GapModel::Linear(1));
+        let alignment = local.align("TTTTGATTACATTTT", "CCGATTACACC")?;
+        assert_eq!(
+            (alignment.score, alignment.range_1, alignment.range_2),
+            (7, 4..11, 2..9)
+        );
+        assert!(local.align("ACGN", "ACG").is_err());
+        Ok(())
     }
-    (aln_1.chars().rev().collect(), aln_2.chars().rev().collect())
-}
 
-fn global_align(
-    string_1: &str,
-    string_2: &str,
-    parameters: &AlignmentParameters,
-) -> (isize, String, String) {
-    let (scores, backtrack) = global_alignment_backtrack(string_1, string_2, parameters);
-    let (string_1, string_2): (Vec<_>, Vec<_>) =
-        (string_1.chars().collect(), string_2.chars().collect());
-    let (aln_1, aln_2) = align(
-        &backtrack,
-        &string_1,
-        &string_2,
-        string_1.len(),
-        string_2.len(),
-    );
-    (scores[(string_1.len(), string_2.len())], aln_1, aln_2)
+    #[test]
+    fn scoring_matrix_file() -> Result<(), Error> {
+        let blosum_file: PathBuf = [env!("CARGO_WORKSPACE_DIR"), utility::io::BLOSUM_FILE]
+            .iter()
+            .collect();
+        let blosum = ScoringMatrix::from_file(&blosum_file)?;
+        assert_eq!(blosum.score('W', 'W'), Some(11));
+        assert_eq!(blosum.score('A', 'Y'), Some(-2));
+        assert_eq!(blosum.score('A', 'B'), None);
+
+        let temp_dir = std::env::temp_dir().join(format!("t_ba5e_test_{}", std::process::id()));
+        std::fs::create_dir_all(&temp_dir)?;
+        let matrix_file = temp_dir.join("matrix.txt");
+        let parse = |contents: &str| -> Result<ScoringMatrix, Error> {
+            std::fs::write(&matrix_file, contents)?;
+            ScoringMatrix::from_file(&matrix_file)
+        };
+        let matrix = parse("   A  B\nA  1 -1\nB -1  2\n")?;
+        assert_eq!(matrix.score('B', 'B'), Some(2));
+        let malformed = [
+            "",
+            "   A  B\nA  1 -1\n",
+            "   A  B\nA  1 -1\nB -1  x\n",
+            "   A  B\nA  1 -1\nC -1  2\n",
+            "   A  B\nA  1\nB -1  2\n",
+            "   A  B\nA  1 -1\nB -1  2\nB -1  2\n",
+            "   A  BC\nA  1 -1\nBC -1  2\n",
+        ];
+        let errors: Vec<_> = malformed
+            .iter()
+            .map(|contents| parse(contents).is_err())
+            .collect();
+        std::fs::remove_dir_all(&temp_dir)?;
+        assert!(errors.into_iter().all(|error| error));
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@ use anyhow::Error;
 use ndarray::{Array1, Array2};
 
 use std::path::{Path, PathBuf};
-use t_ba5e::{read_scoring_matrix, AlignmentParameters};
+use t_ba5e::{GapModel, ScoringMatrix};
 
 /// Find a Middle Edge in an Alignment Graph in Linear Space
 ///
@@ -19,33 +19,50 @@ pub fn rosalind_ba5k(filename: &Path) -> Result<(), Error> {
     let blosum_file: PathBuf = [env!("CARGO_WORKSPACE_DIR"), utility::io::BLOSUM_FILE]
         .iter()
         .collect();
-    let (scoring_matrix, amino_acids) = read_scoring_matrix(&blosum_file)?;
-    let parameters = AlignmentParameters::new(scoring_matrix, amino_acids, 5);
-    let lsa = LinearSpaceAlignment {
-        string_1: lines[0].chars().collect(),
-        string_2: lines[1].chars().collect(),
-        parameters,
-    };
-    let (middle_node, direction) =
-        lsa.get_middle_node_and_edge(0, lsa.string_1.len(), 0, lsa.string_2.len());
-    let middle = ((lsa.string_2.len() as f32) / 2.).floor() as usize;
-    let (start, end) = match direction {
-        1 => ((middle_node, middle), (middle_node + 1, middle)),
-        2 => ((middle_node, middle), (middle_node, middle + 1)),
-        3 => ((middle_node, middle), (middle_node + 1, middle + 1)),
-        _ => panic!("no/invalid backtracking direction set"),
-    };
+    let lsa = LinearSpaceAlignment::new(
+        lines[0].trim(),
+        lines[1].trim(),
+        ScoringMatrix::from_file(&blosum_file)?,
+        GapModel::Linear(5),
+    )?;
+    let (start, end) = lsa.middle_edge();
     println!("{:?} {:?}", start, end);
     Ok(())
 }
 
 pub struct LinearSpaceAlignment {
-    pub string_1: Vec<char>,
-    pub string_2: Vec<char>,
-    pub parameters: AlignmentParameters,
+    /// Indices of the symbols in the scoring matrix
+    string_1: Vec<usize>,
+    string_2: Vec<usize>,
+    scoring_matrix: ScoringMatrix,
+    gap_penalty: isize,
 }
 
 impl LinearSpaceAlignment {
+    /// Only linear gap penalties are supported
+    pub fn new(
+        string_1: &str,
+        string_2: &str,
+        scoring_matrix: ScoringMatrix,
+        gap_model: GapModel,
+    ) -> Result<Self, Error> {
+        let gap_penalty = match gap_model {
+            GapModel::Linear(penalty) => penalty,
+            GapModel::Affine { .. } => {
+                return Err(anyhow::anyhow!(
+                    "middle edges are only found with linear gap penalties, not {:?}",
+                    gap_model
+                ))
+            }
+        };
+        Ok(LinearSpaceAlignment {
+            string_1: scoring_matrix.indices(string_1)?,
+            string_2: scoring_matrix.indices(string_2)?,
+            scoring_matrix,
+            gap_penalty,
+        })
+    }
+
     pub fn get_scores(
         &self,
         top: usize,
@@ -57,10 +74,10 @@ impl LinearSpaceAlignment {
         let (n, m) = (bottom - top + 1, right - left + 1);
         let mut scores = Array2::zeros((2, n));
         let mut backtrack = Array1::zeros(n);
-        scores[(1, 0)] = scores[(0, 0)] - self.parameters.gap_penalty;
+        scores[(1, 0)] = scores[(0, 0)] - self.gap_penalty;
         backtrack[0] = 1;
         for j in 1..n {
-            scores[(0, j)] = scores[(0, j - 1)] - self.parameters.gap_penalty;
+            scores[(0, j)] = scores[(0, j - 1)] - self.gap_penalty;
         }
         let mut i_index = 1;
         let mut i_1_index;
@@ -69,19 +86,17 @@ impl LinearSpaceAlignment {
             i_1_index = (i + 1) % 2;
             for j in 1..n {
                 let values: Vec<isize> = vec![
-                    (scores[(i_1_index, j)] - self.parameters.gap_penalty),
-                    (scores[(i_index, j - 1)] - self.parameters.gap_penalty),
+                    (scores[(i_1_index, j)] - self.gap_penalty),
+                    (scores[(i_index, j - 1)] - self.gap_penalty),
                     (scores[(i_1_index, j - 1)]
                         + if reverse {
-                            self.parameters.scoring_matrix[(
-                                self.parameters.amino_acid_order[&self.string_1[bottom - j]],
-                                self.parameters.amino_acid_order[&self.string_2[right - i]],
-                            )]
+                            self.scoring_matrix
+                                .score_indices(self.string_1[bottom - j], self.string_2[right - i])
                         } else {
-                            self.parameters.scoring_matrix[(
-                                self.parameters.amino_acid_order[&self.string_1[top + j - 1]],
-                                self.parameters.amino_acid_order[&self.string_2[left + i - 1]],
-                            )]
+                            self.scoring_matrix.score_indices(
+                                self.string_1[top + j - 1],
+                                self.string_2[left + i - 1],
+                            )
                         }),
                 ];
                 let (max_index, max_value) = values
@@ -113,4 +128,56 @@ impl LinearSpaceAlignment {
             .1;
         (max_node, backtrack_to_sink[bottom - max_node])
     }
+
+    /// Middle edge of the whole alignment graph, as the nodes (i, j) and (k, l) it connects
+    pub fn middle_edge(&self) -> ((usize, usize), (usize, usize)) {
+        let (middle_node, direction) =
+            self.get_middle_node_and_edge(0, self.string_1.len(), 0, self.string_2.len());
+        let middle = self.string_2.len() / 2;
+        match direction {
+            1 => ((middle_node, middle), (middle_node + 1, middle)),
+            2 => ((middle_node, middle), (middle_node, middle + 1)),
+            3 => ((middle_node, middle), (middle_node + 1, middle + 1)),
+            _ => panic!("no/invalid backtracking direction set"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn middle_edge() -> Result<(), Error> {
+        let blosum_file: PathBuf = [env!("CARGO_WORKSPACE_DIR"), utility::io::BLOSUM_FILE]
+            .iter()
+            .collect();
+        let scoring_matrix = ScoringMatrix::from_file(&blosum_file)?;
+        // sample dataset
+        let lsa = LinearSpaceAlignment::new(
+            "PLEASANTLY",
+            "MEASNLY",
+            scoring_matrix.clone(),
+            GapModel::Linear(5),
+        )?;
+        assert_eq!(lsa.middle_edge(), ((4, 3), (5, 4)));
+        assert!(LinearSpaceAlignment::new(
+            "PLEASANTLY",
+            "MEASNLY",
+            scoring_matrix.clone(),
+            GapModel::Affine {
+                open: 11,
+                extend: 1
+            },
+        )
+        .is_err());
+        assert!(LinearSpaceAlignment::new(
+            "PLEASANTLY",
+            "MEASN1Y",
+            scoring_matrix,
+            GapModel::Linear(5)
+        )
+        .is_err());
+        Ok(())
+    }
 }
@@ -1,87 +1,342 @@
 use anyhow::Error;
 
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use t_ba5e::{read_scoring_matrix, AlignmentParameters};
-use t_ba5k::LinearSpaceAlignment;
+use t_ba5e::{Aligner, Alignment, AlignmentMode, AlignmentOperation, GapModel, ScoringMatrix};
 
-/// W.I.P
-
-pub fn rosalind_ba5l(filename: &Path) -> Result<(), Error> {
+/// Align Two Strings Using Linear Space
+///
+/// Given: Two long amino acid strings (of length approximately 10,000).
+///
+/// Return: The maximum alignment score of these strings, followed by an alignment achieving this
+/// maximum score. Use the BLOSUM62 scoring matrix and indel penalty σ = 5.
+pub fn rosalind_ba5l(filename: &Path) -> Result<Alignment, Error> {
     let contents = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = contents.split('\n').collect();
     let blosum_file: PathBuf = [env!("CARGO_WORKSPACE_DIR"), utility::io::BLOSUM_FILE]
         .iter()
         .collect();
-    let (scoring_matrix, amino_acids) = read_scoring_matrix(&blosum_file)?;
-    //    scoring_matrix.fill(-1);
-    //    scoring_matrix.diag_mut().fill(1);
-    let parameters = AlignmentParameters::new(scoring_matrix, amino_acids, 5);
-    let lsa = LinearSpaceAlignment {
-        string_1: lines[0].chars().collect(),
-        string_2: lines[1].chars().collect(),
-        parameters,
-    };
-    let directions = lsa.align(0, lsa.string_1.len(), 0, lsa.string_2.len());
-    let (mut chars_1, mut chars_2) = (lines[0].chars(), lines[1].chars());
-    let aln_string_1 = directions
-        .iter()
-        .map(|d| match d {
-            1 | 3 => chars_1.next().unwrap(),
-            2 => '-',
-            _ => panic!("Unknown direction"),
-        })
-        .collect::<String>();
-    let aln_string_2 = directions
-        .iter()
-        .map(|d| match d {
-            2 | 3 => chars_2.next().unwrap(),
-            1 => '-',
-            _ => panic!("Unknown direction"),
-        })
-        .collect::<String>();
-    println!("{}\n{}", aln_string_1, aln_string_2);
-    Ok(())
+    let aligner = Aligner::new(
+        AlignmentMode::Global,
+        ScoringMatrix::from_file(&blosum_file)?,
+        GapModel::Linear(5),
+    );
+    let alignment = linear_space_align(&aligner, lines[0], lines[1])?;
+    println!(
+        "{}\n{}\n{}",
+        alignment.score, alignment.aligned_1, alignment.aligned_2
+    );
+    Ok(alignment)
 }
 
-pub trait Align {
-    fn align(&self, top: usize, bottom: usize, left: usize, right: usize) -> Vec<usize>;
+/// Highest-scoring global alignment in memory linear in the length of string_2, with the same
+/// score as `aligner.align` (Hirschberg's divide and conquer, extended to affine gaps by
+/// Myers and Miller). The gap opening penalty must be at least the extension penalty.
+pub fn linear_space_align(
+    aligner: &Aligner,
+    string_1: &str,
+    string_2: &str,
+) -> Result<Alignment, Error> {
+    if aligner.mode != AlignmentMode::Global {
+        return Err(anyhow::anyhow!(
+            "linear space alignment is only global, not {:?}",
+            aligner.mode
+        ));
+    }
+    if aligner.gap_model.open() < aligner.gap_model.extend() {
+        return Err(anyhow::anyhow!(
+            "linear space alignment needs a gap opening penalty of at least the extension \
+             penalty, not {:?}",
+            aligner.gap_model
+        ));
+    }
+    let mut myers_miller = MyersMiller::new(aligner, string_1, string_2)?;
+    let (m, n) = (myers_miller.string_1.len(), myers_miller.string_2.len());
+    let open = myers_miller.open;
+    myers_miller.align(0..m, 0..n, open, open);
+    let (chars_1, chars_2): (Vec<_>, Vec<_>) =
+        (string_1.chars().collect(), string_2.chars().collect());
+    let mut alignment =
+        Alignment::from_operations(0, &chars_1, &chars_2, (0, 0), myers_miller.operations);
+    alignment.score = aligner.rescore(string_1, string_2, &alignment)?;
+    Ok(alignment)
 }
 
-//LinearSpaceAlignment(top, bottom, left, right)
-//        if left = right
-//            return alignment formed by bottom − top vertical edges
-//        if top = bottom
-//            return alignment formed by right − left horizontal edges
-//        middle ← ⌊ (left + right)/2⌋
-//        midNode ← MiddleNode(top, bottom, left, right)
-//        midEdge ← MiddleEdge(top, bottom, left, right)
-//        LinearSpaceAlignment(top, midNode, left, middle)
-//        output midEdge
-//        if midEdge = "→" or midEdge = "↘"
-//            middle ← middle + 1
-//        if midEdge = "↓" or midEdge ="↘"
-//            midNode ← midNode + 1
-//        LinearSpaceAlignment(midNode, bottom, middle, right)
+/// A gap of length k costs `open + extend * k` here, i.e. `open` is the gap model's opening
+/// penalty minus its extension penalty
+struct MyersMiller<'a> {
+    aligner: &'a Aligner,
+    string_1: Vec<usize>,
+    string_2: Vec<usize>,
+    open: isize,
+    extend: isize,
+    /// Best scores, and best scores ending in a deletion, of the last row of the forward and
+    /// reverse passes
+    forward: (Vec<isize>, Vec<isize>),
+    reverse: (Vec<isize>, Vec<isize>),
+    /// Columns found so far, from left to right
+    operations: Vec<AlignmentOperation>,
+}
+
+impl<'a> MyersMiller<'a> {
+    fn new(aligner: &'a Aligner, string_1: &str, string_2: &str) -> Result<Self, Error> {
+        let string_1 = aligner.scoring_matrix.indices(string_1)?;
+        let string_2 = aligner.scoring_matrix.indices(string_2)?;
+        let columns = string_2.len() + 1;
+        Ok(MyersMiller {
+            aligner,
+            string_1,
+            string_2,
+            open: aligner.gap_model.open() - aligner.gap_model.extend(),
+            extend: aligner.gap_model.extend(),
+            forward: (vec![0; columns], vec![0; columns]),
+            reverse: (vec![0; columns], vec![0; columns]),
+            operations: Vec::new(),
+        })
+    }
+
+    fn gap(&self, length: usize) -> isize {
+        if length == 0 {
+            0
+        } else {
+            self.open + self.extend * length as isize
+        }
+    }
+
+    fn substitution(&self, i: usize, j: usize) -> AlignmentOperation {
+        if self.string_1[i] == self.string_2[j] {
+            AlignmentOperation::Match
+        } else {
+            AlignmentOperation::Substitution
+        }
+    }
+
+    /// Scores of aligning `rows` of string_1 with each prefix of `columns` of string_2
+    /// (each suffix with `reverse`), where a deletion in the first column costs
+    /// `start_open` to open
+    fn last_row(
+        &mut self,
+        rows: Range<usize>,
+        columns: Range<usize>,
+        start_open: isize,
+        reverse: bool,
+    ) {
+        let (open, extend) = (self.open, self.extend);
+        let n = columns.len();
+        let (best, deletion) = if reverse {
+            (&mut self.reverse.0, &mut self.reverse.1)
+        } else {
+            (&mut self.forward.0, &mut self.forward.1)
+        };
+        best[0] = 0;
+        deletion[0] = -open;
+        for j in 1..=n {
+            best[j] = -(open + extend * j as isize);
+            deletion[j] = best[j] - open;
+        }
+        let mut column_0 = -start_open;
+        for i in 1..=rows.len() {
+            let a = if reverse {
+                self.string_1[rows.end - i]
+            } else {
+                self.string_1[rows.start + i - 1]
+            };
+            let mut diagonal = best[0];
+            column_0 -= extend;
+            best[0] = column_0;
+            deletion[0] = column_0;
+            let (mut left, mut insertion) = (column_0, column_0 - open);
+            for j in 1..=n {
+                let b = if reverse {
+                    self.string_2[columns.end - j]
+                } else {
+                    self.string_2[columns.start + j - 1]
+                };
+                insertion = insertion.max(left - open) - extend;
+                let d = deletion[j].max(best[j] - open) - extend;
+                left = d
+                    .max(insertion)
+                    .max(diagonal + self.aligner.scoring_matrix.score_indices(a, b));
+                diagonal = best[j];
+                best[j] = left;
+                deletion[j] = d;
+            }
+        }
+    }
 
-impl Align for LinearSpaceAlignment {
-    fn align(&self, top: usize, bottom: usize, left: usize, right: usize) -> Vec<usize> {
-        if left == right {
-            return (0..bottom - top).map(|_| 1).collect();
+    /// Append an optimal alignment of `rows` of string_1 with `columns` of string_2, where a
+    /// deletion costs `start_open` to open at the start and `end_open` at the end
+    /// (zero where it continues a gap of the enclosing alignment)
+    fn align(
+        &mut self,
+        rows: Range<usize>,
+        columns: Range<usize>,
+        start_open: isize,
+        end_open: isize,
+    ) {
+        let (m, n) = (rows.len(), columns.len());
+        if n == 0 {
+            self.operations
+                .extend(std::iter::repeat_n(AlignmentOperation::Deletion, m));
+            return;
         }
-        if top == bottom {
-            return (0..right - left).map(|_| 2).collect();
+        if m == 0 {
+            self.operations
+                .extend(std::iter::repeat_n(AlignmentOperation::Insertion, n));
+            return;
         }
-        let mut middle = ((left + right) as f32 / 2.) as usize;
-        let (mut middle_node, direction) = self.get_middle_node_and_edge(top, bottom, left, right);
-        let mut alignment = self.align(top, middle_node, left, middle);
-        alignment.push(direction);
-        if (direction == 2) | (direction == 3) {
-            middle += 1;
+        if m == 1 {
+            // either the character is aligned with one of columns and the rest are insertions,
+            // or it is deleted and joined to the cheaper of the neighbouring gaps
+            let score = |j: usize| {
+                -self.gap(j)
+                    + self
+                        .aligner
+                        .scoring_matrix
+                        .score_indices(self.string_1[rows.start], self.string_2[columns.start + j])
+                    - self.gap(n - j - 1)
+            };
+            let (best_j, best_score) = (1..n).fold((0, score(0)), |(best_j, best_score), j| {
+                let score = score(j);
+                if score > best_score {
+                    (j, score)
+                } else {
+                    (best_j, best_score)
+                }
+            });
+            let deleted = -(start_open.min(end_open) + self.extend) - self.gap(n);
+            let insertions = std::iter::repeat_n(AlignmentOperation::Insertion, n);
+            if deleted > best_score {
+                if start_open <= end_open {
+                    self.operations.push(AlignmentOperation::Deletion);
+                    self.operations.extend(insertions);
+                } else {
+                    self.operations.extend(insertions);
+                    self.operations.push(AlignmentOperation::Deletion);
+                }
+            } else {
+                self.operations
+                    .extend(std::iter::repeat_n(AlignmentOperation::Insertion, best_j));
+                self.operations
+                    .push(self.substitution(rows.start, columns.start + best_j));
+                self.operations.extend(std::iter::repeat_n(
+                    AlignmentOperation::Insertion,
+                    n - best_j - 1,
+                ));
+            }
+            return;
+        }
+
+        let middle = rows.start + m / 2;
+        self.last_row(rows.start..middle, columns.clone(), start_open, false);
+        self.last_row(middle..rows.end, columns.clone(), end_open, true);
+        // the best path crosses the middle row at column j either between two cells or
+        // inside a deletion, whose opening penalty both halves have paid
+        let (mut best_j, mut best_score, mut in_deletion) = (0, isize::MIN, false);
+        for j in 0..=n {
+            let crossing = self.forward.0[j] + self.reverse.0[n - j];
+            if crossing > best_score {
+                best_j = j;
+                best_score = crossing;
+                in_deletion = false;
+            }
+            let deleting = self.forward.1[j] + self.reverse.1[n - j] + self.open;
+            if deleting > best_score {
+                best_j = j;
+                best_score = deleting;
+                in_deletion = true;
+            }
         }
-        if (direction == 1) | (direction == 3) {
-            middle_node += 1;
+        let split = columns.start + best_j;
+        if in_deletion {
+            self.align(rows.start..middle - 1, columns.start..split, start_open, 0);
+            self.operations.push(AlignmentOperation::Deletion);
+            self.operations.push(AlignmentOperation::Deletion);
+            self.align(middle + 1..rows.end, split..columns.end, 0, end_open);
+        } else {
+            self.align(
+                rows.start..middle,
+                columns.start..split,
+                start_open,
+                self.open,
+            );
+            self.align(middle..rows.end, split..columns.end, self.open, end_open);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ba5l() -> Result<(), Error> {
+        let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_ba5l")?;
+        let output = utility::io::input_from_file(&output_file)?;
+        let alignment = rosalind_ba5l(&input_file)?;
+        assert_eq!(
+            alignment.score,
+            output.lines().next().unwrap().parse::<isize>()?
+        );
+        // any alignment achieving the score is accepted
+        let input = utility::io::input_from_file(&input_file)?;
+        let lines: Vec<_> = input.lines().collect();
+        assert!(alignment.aligns(lines[0], lines[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn quadratic_space_scores() -> Result<(), Error> {
+        let random_string = |max_length: usize, seed: u64| {
+            let length = seed as usize * 7919 % (max_length + 1);
+            utility::testing::random_sequence(length, b"ACGT", seed)
+        };
+        let gap_models = [
+            GapModel::Linear(2),
+            GapModel::Affine { open: 3, extend: 1 },
+            GapModel::Affine { open: 5, extend: 1 },
+            GapModel::Affine { open: 2, extend: 2 },
+        ];
+        for max_length in [3, 10, 40, 500] {
+            for round in 0..20 {
+                let seed = 2 * (max_length as u64 + round);
+                let (string_1, string_2) = (
+                    random_string(max_length, seed),
+                    random_string(max_length, seed + 1),
+                );
+                for gap_model in gap_models {
+                    let aligner = Aligner::new(
+                        AlignmentMode::Global,
+                        ScoringMatrix::uniform("ACGT".chars(), 2, -1),
+                        gap_model,
+                    );
+                    let expected = aligner.align(&string_1, &string_2)?;
+                    let alignment = linear_space_align(&aligner, &string_1, &string_2)?;
+                    assert_eq!(
+                        alignment.score, expected.score,
+                        "{:?} {} {}",
+                        gap_model, string_1, string_2
+                    );
+                    assert!(alignment.aligns(&string_1, &string_2));
+                    assert_eq!(
+                        (alignment.range_1, alignment.range_2),
+                        (expected.range_1, expected.range_2)
+                    );
+                }
+            }
         }
-        alignment.extend_from_slice(&self.align(middle_node, bottom, middle, right));
-        alignment
+        let local = Aligner::new(
+            AlignmentMode::Local,
+            ScoringMatrix::uniform("ACGT".chars(), 1, -1),
+            GapModel::Linear(1),
+        );
+        assert!(linear_space_align(&local, "ACGT", "ACGT").is_err());
+        let cheap_opening = Aligner::new(
+            AlignmentMode::Global,
+            ScoringMatrix::uniform("ACGT".chars(), 1, -1),
+            GapModel::Affine { open: 1, extend: 2 },
+        );
+        assert!(linear_space_align(&cheap_opening, "ACGT", "AGT").is_err());
+        Ok(())
     }
 }
@@ -2,6 +2,7 @@ use std::hash::Hash;
 
 use anyhow::Error;
 
+use s_trie::aho_corasick::AhoCorasick;
 use s_trie::Trie;
 use std::path::Path;
 
@@ -55,13 +56,15 @@ impl<T: Eq + Clone, U: Eq + Hash + Clone> PrefixMatching<T, U> for Trie<T, U> {
         }
     }
 
+    /// Starting positions of all patterns in one pass with an Aho-Corasick automaton
     fn matching(&self, text: &[U]) -> Vec<usize> {
-        let mut indices = Vec::new();
-        for i in 0..text.len() {
-            if self.prefix_matching(&text[i..]).is_some() {
-                indices.push(i);
-            }
-        }
+        let mut indices: Vec<_> = AhoCorasick::new(self)
+            .find_all(text)
+            .into_iter()
+            .map(|m| m.start)
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
         indices
     }
 }
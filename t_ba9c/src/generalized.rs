@@ -0,0 +1,348 @@
+//! Generalized suffix tree of several strings, built with Ukkonen's algorithm over their
+//! concatenation with a unique terminator after each string.
+//! Every leaf belongs to the string its suffix starts in.
+use crate::ukkonen::{self, Node, ROOT};
+
+/// Terminator symbols come after the byte range, one per string
+const FIRST_TERMINATOR: usize = 256;
+
+/// Characters preceding the occurrences of a node's path label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LeftContext {
+    Empty,
+    Char(u8),
+    /// different characters, or an occurrence at the start of a string
+    Diverse,
+}
+
+impl LeftContext {
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (LeftContext::Empty, other) | (other, LeftContext::Empty) => other,
+            (LeftContext::Char(a), LeftContext::Char(b)) if a == b => self,
+            _ => LeftContext::Diverse,
+        }
+    }
+}
+
+/// Substring with the (string index, offset) of each of its occurrences, sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat<'a> {
+    pub string: &'a [u8],
+    pub positions: Vec<(usize, usize)>,
+}
+
+pub struct GeneralizedSuffixTree {
+    strings: Vec<Vec<u8>>,
+    /// the strings concatenated, each followed by its terminator
+    text: Vec<usize>,
+    nodes: Vec<Node>,
+    /// string depth of each node, counting the terminator on leaf edges
+    depths: Vec<usize>,
+    /// parent of each node (the root is its own parent)
+    parents: Vec<usize>,
+    /// for leaves, the string and offset of their suffix
+    leaf_suffixes: Vec<Option<(usize, usize)>>,
+    /// bitset of the strings with a leaf below each node, `words_per_node` words per node
+    string_sets: Vec<u64>,
+    words_per_node: usize,
+    /// number of leaves below each node, i.e. occurrences of its path label
+    leaf_counts: Vec<usize>,
+    left_contexts: Vec<LeftContext>,
+}
+
+impl GeneralizedSuffixTree {
+    pub fn new<S: AsRef<[u8]>>(strings: &[S]) -> Self {
+        let strings: Vec<Vec<u8>> = strings.iter().map(|s| s.as_ref().to_vec()).collect();
+        let mut text = Vec::with_capacity(strings.iter().map(|s| s.len() + 1).sum());
+        // string index and offset of each position in the text
+        let mut positions = Vec::with_capacity(text.capacity());
+        for (index, string) in strings.iter().enumerate() {
+            for (offset, c) in string.iter().enumerate() {
+                text.push(*c as usize);
+                positions.push((index, offset));
+            }
+            text.push(FIRST_TERMINATOR + index);
+            positions.push((index, string.len()));
+        }
+        let mut nodes = ukkonen::build(&text);
+        let untrimmed_depths = ukkonen::node_depths(&nodes);
+        // leaf edges run on through later strings, cut them at their own string's terminator
+        let mut leaf_suffixes = vec![None; nodes.len()];
+        for (node, depth) in untrimmed_depths.iter().enumerate() {
+            if node != ROOT && nodes[node].is_leaf() {
+                let (index, offset) = positions[text.len() - depth];
+                let terminator = text.len() - depth + strings[index].len() - offset;
+                nodes[node].end = Some(terminator + 1);
+                leaf_suffixes[node] = Some((index, offset));
+            }
+        }
+        let depths = ukkonen::node_depths(&nodes);
+        let mut tree = GeneralizedSuffixTree {
+            words_per_node: strings.len() / 64 + 1,
+            strings,
+            text,
+            depths,
+            parents: vec![ROOT; nodes.len()],
+            leaf_suffixes,
+            string_sets: Vec::new(),
+            leaf_counts: Vec::new(),
+            left_contexts: Vec::new(),
+            nodes,
+        };
+        tree.fill_subtree_summaries();
+        tree
+    }
+
+    pub fn num_strings(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Nodes in depth-first preorder, so that parents come before children
+    fn preorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![ROOT];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(self.nodes[node].children.values().rev());
+        }
+        order
+    }
+
+    /// Parents, and bottom-up the string sets, leaf counts and left contexts of each subtree
+    fn fill_subtree_summaries(&mut self) {
+        let words = self.words_per_node;
+        self.string_sets = vec![0; self.nodes.len() * words];
+        self.leaf_counts = vec![0; self.nodes.len()];
+        self.left_contexts = vec![LeftContext::Empty; self.nodes.len()];
+        let order = self.preorder();
+        for node in &order {
+            for child in self.nodes[*node].children.values() {
+                self.parents[*child] = *node;
+            }
+        }
+        for node in order.into_iter().rev() {
+            if let Some((index, offset)) = self.leaf_suffixes[node] {
+                self.string_sets[node * words + index / 64] |= 1 << (index % 64);
+                self.leaf_counts[node] = 1;
+                self.left_contexts[node] = match offset {
+                    0 => LeftContext::Diverse,
+                    _ => LeftContext::Char(self.strings[index][offset - 1]),
+                };
+            }
+            if node != ROOT {
+                let parent = self.parents[node];
+                self.leaf_counts[parent] += self.leaf_counts[node];
+                self.left_contexts[parent] =
+                    self.left_contexts[parent].merge(self.left_contexts[node]);
+                for word in 0..words {
+                    self.string_sets[parent * words + word] |=
+                        self.string_sets[node * words + word];
+                }
+            }
+        }
+    }
+
+    fn string_set(&self, node: usize) -> &[u64] {
+        &self.string_sets[node * self.words_per_node..(node + 1) * self.words_per_node]
+    }
+
+    fn contains_string(&self, node: usize, index: usize) -> bool {
+        self.string_set(node)[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Length of the node's path label without any terminator
+    fn label_length(&self, node: usize) -> usize {
+        if self.nodes[node].is_leaf() && node != ROOT {
+            self.depths[node] - 1
+        } else {
+            self.depths[node]
+        }
+    }
+
+    /// Path label of the node (or its first `length` characters), read from a leaf below it,
+    /// preferring a leaf of string `index` if given
+    fn label(&self, node: usize, length: usize, index: Option<usize>) -> &[u8] {
+        let mut current = node;
+        while self.leaf_suffixes[current].is_none() {
+            current = *self.nodes[current]
+                .children
+                .values()
+                .find(|child| index.is_none_or(|index| self.contains_string(**child, index)))
+                .unwrap();
+        }
+        let (index, offset) = self.leaf_suffixes[current].unwrap();
+        &self.strings[index][offset..offset + length]
+    }
+
+    /// A longest substring occurring at least twice, in one string or across strings
+    pub fn longest_repeat(&self) -> Option<&[u8]> {
+        self.longest_k_fold_repeat(2)
+    }
+
+    /// A longest substring occurring at least `k` times, in one string or across strings
+    pub fn longest_k_fold_repeat(&self, k: usize) -> Option<&[u8]> {
+        (1..self.nodes.len())
+            .filter(|node| self.leaf_counts[*node] >= k)
+            .map(|node| (node, self.label_length(node)))
+            .filter(|(_, length)| *length > 0)
+            .max_by_key(|(_, length)| *length)
+            .map(|(node, length)| self.label(node, length, None))
+    }
+
+    /// Occurrences of the path label of a node, from the leaves below it
+    fn repeat(&self, node: usize) -> Repeat<'_> {
+        let mut positions = Vec::with_capacity(self.leaf_counts[node]);
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            positions.extend(self.leaf_suffixes[current]);
+            stack.extend(self.nodes[current].children.values());
+        }
+        positions.sort_unstable();
+        Repeat {
+            string: self.label(node, self.depths[node], None),
+            positions,
+        }
+    }
+
+    /// Internal nodes with a path label of at least `min_length` occurring at least `min_count` times
+    fn repeat_nodes(
+        &self,
+        min_length: usize,
+        min_count: usize,
+    ) -> impl Iterator<Item = usize> + '_ {
+        (1..self.nodes.len()).filter(move |node| {
+            !self.nodes[*node].is_leaf()
+                && self.depths[*node] >= min_length.max(1)
+                && self.leaf_counts[*node] >= min_count
+        })
+    }
+
+    /// Longest first, then in lexicographic order
+    fn sorted_repeats(&self, nodes: impl Iterator<Item = usize>) -> Vec<Repeat<'_>> {
+        let mut repeats: Vec<_> = nodes.map(|node| self.repeat(node)).collect();
+        repeats.sort_unstable_by(|a, b| {
+            b.string
+                .len()
+                .cmp(&a.string.len())
+                .then_with(|| a.string.cmp(b.string))
+        });
+        repeats
+    }
+
+    /// Maximal repeats, which can't be extended left or right without losing an occurrence:
+    /// the path labels of internal nodes whose occurrences aren't all preceded by the same character
+    pub fn maximal_repeats(&self, min_length: usize, min_count: usize) -> Vec<Repeat<'_>> {
+        self.sorted_repeats(
+            self.repeat_nodes(min_length, min_count)
+                .filter(|node| self.left_contexts[*node] == LeftContext::Diverse),
+        )
+    }
+
+    /// Supermaximal repeats, which aren't a substring of any other repeat occurring at least
+    /// `min_count` (and at least two) times: repeats none of whose extensions by one character,
+    /// on the right (a child of the node) or on the left, occur that often
+    pub fn supermaximal_repeats(&self, min_length: usize, min_count: usize) -> Vec<Repeat<'_>> {
+        let threshold = min_count.max(2);
+        self.sorted_repeats(self.repeat_nodes(min_length, min_count).filter(|node| {
+            if self.nodes[*node]
+                .children
+                .values()
+                .any(|child| self.leaf_counts[*child] >= threshold)
+            {
+                return false;
+            }
+            let mut left_counts = [0; 256];
+            !self.repeat(*node).positions.iter().any(|(index, offset)| {
+                offset.checked_sub(1).is_some_and(|left| {
+                    let count = &mut left_counts[self.strings[*index][left] as usize];
+                    *count += 1;
+                    *count >= threshold
+                })
+            })
+        }))
+    }
+
+    /// A longest substring shared by at least `k` of the strings
+    pub fn longest_common_substring(&self, k: usize) -> Option<&[u8]> {
+        (1..self.nodes.len())
+            .filter(|node| {
+                self.string_set(*node)
+                    .iter()
+                    .map(|word| word.count_ones() as usize)
+                    .sum::<usize>()
+                    >= k
+            })
+            .map(|node| (node, self.label_length(node)))
+            .filter(|(_, length)| *length > 0)
+            .max_by_key(|(_, length)| *length)
+            .map(|(node, length)| self.label(node, length, None))
+    }
+
+    /// A shortest substring of string `index_1` that doesn't occur in string `index_2`
+    pub fn shortest_non_shared_substring(&self, index_1: usize, index_2: usize) -> Option<&[u8]> {
+        // the shortest such string ends one character into an edge leading to a subtree with
+        // leaves of the first string but not the second
+        (1..self.nodes.len())
+            .filter(|node| {
+                self.contains_string(*node, index_1)
+                    && !self.contains_string(*node, index_2)
+                    && self.text[self.nodes[*node].start] < FIRST_TERMINATOR
+            })
+            .min_by_key(|node| self.depths[self.parents[*node]])
+            .map(|node| self.label(node, self.depths[self.parents[node]] + 1, Some(index_1)))
+    }
+
+    /// For each position of `query`, the length of its longest prefix starting there which
+    /// occurs in any of the strings. Uses suffix links, so takes O(|query|) steps.
+    pub fn matching_statistics(&self, query: &[u8]) -> Vec<usize> {
+        let query: Vec<_> = query.iter().map(|c| *c as usize).collect();
+        let mut statistics = Vec::with_capacity(query.len());
+        // the matched string query[i..i + length] ends below `node`, the deepest node above it
+        let (mut node, mut length) = (ROOT, 0);
+        for i in 0..query.len() {
+            while i + length < query.len() {
+                let remaining = length - self.depths[node];
+                let child = if remaining == 0 {
+                    match self.nodes[node].children.get(&query[i + length]) {
+                        Some(child) => *child,
+                        None => break,
+                    }
+                } else {
+                    let child = self.nodes[node].children[&query[i + self.depths[node]]];
+                    if self.text[self.nodes[child].start + remaining] != query[i + length] {
+                        break;
+                    }
+                    child
+                };
+                length += 1;
+                if length == self.depths[child] {
+                    node = child;
+                }
+            }
+            statistics.push(length);
+            if length == 0 {
+                continue;
+            }
+            length -= 1;
+            node = if node == ROOT {
+                ROOT
+            } else {
+                self.nodes[node].suffix_link
+            };
+            // skip/count back down to the deepest node above query[i + 1..i + 1 + length]
+            loop {
+                let remaining = length - self.depths[node];
+                if remaining == 0 {
+                    break;
+                }
+                let child = self.nodes[node].children[&query[i + 1 + self.depths[node]]];
+                if self.depths[child] > length {
+                    break;
+                }
+                node = child;
+            }
+        }
+        statistics
+    }
+}
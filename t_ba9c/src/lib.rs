@@ -1,13 +1,17 @@
-use std::collections::btree_map::BTreeMap;
-use std::collections::HashMap;
-
 use anyhow::Error;
-use petgraph::stable_graph::{EdgeIndex, EdgeReference, NodeIndex, StableGraph};
-use petgraph::visit::{EdgeRef, IntoEdgeReferences};
-use petgraph::{Directed, Outgoing};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::visit::IntoEdgeReferences;
+use petgraph::Directed;
 
 use std::path::Path;
-use t_ba3m::MaximalNonbranching;
+
+pub mod generalized;
+mod ukkonen;
+
+pub use crate::generalized::{GeneralizedSuffixTree, Repeat};
+
+/// Symbol appended to the text, larger than any byte
+const SENTINEL: usize = 256;
 
 /// Construct the Suffix Tree of a String
 ///
@@ -29,93 +33,144 @@ pub fn rosalind_ba9c(filename: &Path) -> Result<Vec<String>, Error> {
     Ok(strings)
 }
 
-pub struct SuffixTrie {
-    trie: StableGraph<Option<usize>, (char, usize), Directed, u32>,
-    root: NodeIndex<u32>,
-}
-
 pub struct SuffixTree {
     pub tree: StableGraph<Option<usize>, (usize, usize), Directed, u32>,
     pub root: NodeIndex<u32>,
 }
 
 impl SuffixTree {
+    /// Build the suffix tree with Ukkonen's algorithm.
+    /// Edges are labelled with (start, length) in the text and leaves with the start of their suffix.
     pub fn construct(text: &str) -> Self {
-        let trie = SuffixTrie::construct(text);
-        let mut tree = trie.trie.map(|_, n| *n, |_, (_, pos)| (*pos, 1));
-        let root = trie.root;
-        for path in trie.get_maximal_nonbranching_paths() {
-            for edge in &path {
-                let (source, target) = trie.trie.edge_endpoints(*edge).unwrap();
-                tree.remove_edge(tree.find_edge(source, target).unwrap());
+        // a sentinel outside the byte range makes every suffix end at a leaf
+        let symbols: Vec<_> = text
+            .bytes()
+            .map(|c| c as usize)
+            .chain(std::iter::once(SENTINEL))
+            .collect();
+        let nodes = ukkonen::build(&symbols);
+        let depths = ukkonen::node_depths(&nodes);
+        let mut tree = StableGraph::new();
+        let root = tree.add_node(None);
+        let mut stack = vec![(ukkonen::ROOT, root)];
+        while let Some((node, graph_node)) = stack.pop() {
+            for child in nodes[node].children.values() {
+                let start = nodes[*child].start;
+                // drop the sentinel from the end of leaf edges
+                let end = nodes[*child].end.unwrap().min(text.len());
+                if end == start {
+                    continue;
+                }
+                let weight = if nodes[*child].is_leaf() {
+                    Some(symbols.len() - depths[*child])
+                } else {
+                    None
+                };
+                let graph_child = tree.add_node(weight);
+                tree.add_edge(graph_node, graph_child, (start, end - start));
+                stack.push((*child, graph_child));
             }
-            let (source, _) = trie.trie.edge_endpoints(path[0]).unwrap();
-            let (_, target) = trie.trie.edge_endpoints(path[path.len() - 1]).unwrap();
-            let (_, position) = trie.trie.edge_weight(path[0]).unwrap();
-            tree.add_edge(source, target, (*position, path.len()));
         }
-        tree.retain_nodes(|t, n| t.edges(n).next().is_some() || t[n].is_some());
         SuffixTree { tree, root }
     }
 }
 
-impl SuffixTrie {
-    fn construct(text: &str) -> Self {
-        let mut trie = StableGraph::new();
-        let root = trie.add_node(None);
-        let mut current_node;
-        let text: Vec<_> = text.chars().collect();
-        for i in 0..text.len() {
-            current_node = root;
-            for (j, current_symbol) in text.iter().enumerate().skip(i) {
-                if let Some(edge) = trie
-                    .edges_directed(current_node, Outgoing)
-                    .find(|e: &EdgeReference<(char, usize), u32>| e.weight().0 == *current_symbol)
-                {
-                    current_node = edge.target();
-                } else {
-                    let new_node = trie.add_node(None);
-                    trie.add_edge(current_node, new_node, (*current_symbol, j));
-                    current_node = new_node;
-                }
-            }
-            if trie.edges_directed(current_node, Outgoing).next().is_none() {
-                *trie.node_weight_mut(current_node).unwrap() = Some(i);
-            }
-        }
-        SuffixTrie { root, trie }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Random DNA strings of various lengths, some over fewer nucleotides
+    fn random_strings(count: usize, seed: u64) -> Vec<String> {
+        (0..count)
+            .map(|i| {
+                utility::testing::random_sequence(
+                    5 + i * 7 % 23,
+                    &b"ACGT"[..1 + i % 4],
+                    seed * count as u64 + i as u64,
+                )
+            })
+            .collect()
+    }
+
+    fn substrings(string: &str) -> Vec<&str> {
+        (0..string.len())
+            .flat_map(|i| ((i + 1)..=string.len()).map(move |j| &string[i..j]))
+            .collect()
     }
 
-    fn get_maximal_nonbranching_paths(&self) -> Vec<Vec<EdgeIndex<u32>>> {
-        let nodes: Vec<_> = self.trie.node_indices().collect();
-        let node_to_index: HashMap<_, _> = self
-            .trie
-            .node_indices()
-            .enumerate()
-            .map(|(i, n)| (n, i))
+    #[test]
+    fn ukkonen_suffix_trees() {
+        let text = "ATAAATG$";
+        let suffix_tree = SuffixTree::construct(text);
+        let mut labels: Vec<_> = suffix_tree
+            .tree
+            .edge_references()
+            .map(|e| &text[e.weight().0..e.weight().0 + e.weight().1])
             .collect();
-        let mut adjacency_list = BTreeMap::new();
-        for e in self.trie.edge_references() {
-            adjacency_list
-                .entry(node_to_index[&e.source()])
-                .or_insert(Vec::new())
-                .push(node_to_index[&e.target()]);
+        labels.sort_unstable();
+        let mut expected = vec![
+            "AAATG$", "G$", "T", "ATG$", "TG$", "A", "A", "AAATG$", "G$", "T", "G$", "$",
+        ];
+        expected.sort_unstable();
+        assert_eq!(labels, expected);
+
+        for seed in 0..20 {
+            let strings = random_strings(4, seed);
+            let suffix_tree = GeneralizedSuffixTree::new(&strings);
+            let count_strings =
+                |substring: &str| strings.iter().filter(|s| s.contains(substring)).count();
+            for k in 1..=4 {
+                let expected = strings
+                    .iter()
+                    .flat_map(|s| substrings(s))
+                    .filter(|substring| count_strings(substring) >= k)
+                    .map(str::len)
+                    .max();
+                let result = suffix_tree.longest_common_substring(k);
+                assert_eq!(result.map(<[u8]>::len), expected);
+                if let Some(result) = result {
+                    assert!(count_strings(std::str::from_utf8(result).unwrap()) >= k);
+                }
+            }
+            let expected = strings
+                .iter()
+                .flat_map(|s| substrings(s))
+                .filter(|substring| {
+                    strings
+                        .iter()
+                        .map(|s| {
+                            (0..s.len())
+                                .filter(|j| s[*j..].starts_with(substring))
+                                .count()
+                        })
+                        .sum::<usize>()
+                        >= 2
+                })
+                .map(str::len)
+                .max();
+            assert_eq!(suffix_tree.longest_repeat().map(<[u8]>::len), expected);
+            let expected = substrings(&strings[0])
+                .into_iter()
+                .filter(|substring| !strings[1].contains(substring))
+                .map(str::len)
+                .min();
+            let result = suffix_tree.shortest_non_shared_substring(0, 1);
+            assert_eq!(result.map(<[u8]>::len), expected);
+            if let Some(result) = result {
+                let result = std::str::from_utf8(result).unwrap();
+                assert!(strings[0].contains(result) && !strings[1].contains(result));
+            }
+            let query = &random_strings(6, seed + 100)[5];
+            let expected: Vec<_> = (0..query.len())
+                .map(|i| {
+                    (i..=query.len())
+                        .take_while(|j| count_strings(&query[i..*j]) > 0)
+                        .last()
+                        .unwrap()
+                        - i
+                })
+                .collect();
+            assert_eq!(suffix_tree.matching_statistics(query.as_bytes()), expected);
         }
-        let graph = utility::graph::IntegerGraph::new(
-            adjacency_list,
-            (0..node_to_index.len()).collect(),
-            true,
-        );
-        let paths = graph.get_maximal_nonbranching_paths();
-        paths
-            .into_iter()
-            .map(|path| {
-                path.iter()
-                    .take(path.len() - 1)
-                    .zip(path.iter().skip(1))
-                    .map(|(n1, n2)| self.trie.find_edge(nodes[*n1], nodes[*n2]).unwrap())
-                    .collect()
-            })
-            .collect()
     }
 }
@@ -0,0 +1,121 @@
+//! Ukkonen's online suffix tree construction (1995) over an integer alphabet, in O(n log σ) time.
+use std::collections::BTreeMap;
+
+pub(crate) const ROOT: usize = 0;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    /// start of the edge label leading into this node
+    pub(crate) start: usize,
+    /// end (exclusive) of the edge label, or None for leaves, whose edges grow with the text
+    pub(crate) end: Option<usize>,
+    pub(crate) suffix_link: usize,
+    /// children by the first symbol of their edge label
+    pub(crate) children: BTreeMap<usize, usize>,
+}
+
+impl Node {
+    fn new(start: usize, end: Option<usize>) -> Self {
+        Node {
+            start,
+            end,
+            suffix_link: ROOT,
+            children: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// Suffix tree of `text`, which must end with a unique symbol so that every suffix ends at a leaf.
+/// Leaf edges are closed off at the end of the text.
+pub(crate) fn build(text: &[usize]) -> Vec<Node> {
+    let mut nodes = vec![Node::new(0, Some(0))];
+    let (mut active_node, mut active_edge, mut active_length) = (ROOT, 0, 0);
+    let mut remainder = 0;
+    for i in 0..text.len() {
+        remainder += 1;
+        let mut last_new_node: Option<usize> = None;
+        while remainder > 0 {
+            if active_length == 0 {
+                active_edge = i;
+            }
+            match nodes[active_node].children.get(&text[active_edge]).copied() {
+                None => {
+                    nodes.push(Node::new(i, None));
+                    let leaf = nodes.len() - 1;
+                    nodes[active_node].children.insert(text[active_edge], leaf);
+                    if let Some(node) = last_new_node.take() {
+                        nodes[node].suffix_link = active_node;
+                    }
+                }
+                Some(next) => {
+                    let edge_length = nodes[next].end.unwrap_or(i + 1) - nodes[next].start;
+                    if active_length >= edge_length {
+                        // walk down to the next node (skip/count trick)
+                        active_edge += edge_length;
+                        active_length -= edge_length;
+                        active_node = next;
+                        continue;
+                    }
+                    if text[nodes[next].start + active_length] == text[i] {
+                        // the suffix is already in the tree, so are all shorter ones
+                        if let Some(node) = last_new_node.take() {
+                            if active_node != ROOT {
+                                nodes[node].suffix_link = active_node;
+                            }
+                        }
+                        active_length += 1;
+                        break;
+                    }
+                    let split_start = nodes[next].start;
+                    nodes.push(Node::new(split_start, Some(split_start + active_length)));
+                    let split = nodes.len() - 1;
+                    nodes[active_node].children.insert(text[active_edge], split);
+                    nodes.push(Node::new(i, None));
+                    let leaf = nodes.len() - 1;
+                    nodes[split].children.insert(text[i], leaf);
+                    nodes[next].start += active_length;
+                    let next_symbol = text[nodes[next].start];
+                    nodes[split].children.insert(next_symbol, next);
+                    if let Some(node) = last_new_node {
+                        nodes[node].suffix_link = split;
+                    }
+                    last_new_node = Some(split);
+                }
+            }
+            remainder -= 1;
+            if active_node == ROOT && active_length > 0 {
+                active_length -= 1;
+                active_edge = i + 1 - remainder;
+            } else if active_node != ROOT {
+                active_node = nodes[active_node].suffix_link;
+            }
+        }
+    }
+    for node in nodes.iter_mut() {
+        if node.end.is_none() {
+            node.end = Some(text.len());
+        }
+    }
+    nodes
+}
+
+/// String depth of every node, i.e. the length of the path label from the root
+pub(crate) fn node_depths(nodes: &[Node]) -> Vec<usize> {
+    let mut depths = vec![0; nodes.len()];
+    let mut stack = vec![ROOT];
+    while let Some(node) = stack.pop() {
+        for child in nodes[node].children.values() {
+            depths[*child] = depths[node] + edge_length(nodes, *child);
+            stack.push(*child);
+        }
+    }
+    depths
+}
+
+pub(crate) fn edge_length(nodes: &[Node], node: usize) -> usize {
+    nodes[node].end.unwrap() - nodes[node].start
+}
@@ -7,6 +7,7 @@ use petgraph::Outgoing;
 
 use std::path::Path;
 use t_ba9c::SuffixTree;
+use t_ba9g::SuffixArray;
 
 /// Find the longest repeat in a string.
 ///
@@ -15,10 +16,9 @@ use t_ba9c::SuffixTree;
 /// Return: A longest substring of Text that appears in Text more than once.
 /// (Multiple solutions may exist, in which case you may return any one.)
 pub fn rosalind_ba9d(filename: &Path) -> Result<String, Error> {
-    let mut text = utility::io::input_from_file(filename)?;
-    text.push('$');
-    let suffix_tree = SuffixTree::construct(&text);
-    let longest_repeat = suffix_tree.get_longest_repeat(&text);
+    let text = utility::io::input_from_file(filename)?;
+    let suffix_array = SuffixArray::construct(&text);
+    let longest_repeat = suffix_array.longest_repeat().unwrap_or_default().to_owned();
     println!("{}", longest_repeat);
     Ok(longest_repeat)
 }
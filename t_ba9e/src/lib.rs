@@ -8,8 +8,7 @@ use std::fmt;
 use std::str::FromStr;
 
 use std::path::Path;
-use t_ba9c::SuffixTree;
-use t_ba9d::LongestRepeat;
+use t_ba9c::{GeneralizedSuffixTree, SuffixTree};
 
 /// Find the Longest Substring Shared by Two Strings
 ///
@@ -20,13 +19,13 @@ use t_ba9d::LongestRepeat;
 pub fn rosalind_ba9e(filename: &Path) -> Result<String, Error> {
     let contents = utility::io::input_from_file(filename)?;
     let (text_1, text_2) = contents.trim().split('\n').collect_tuple().unwrap();
-    let mut text: String = text_1.to_owned();
-    text.push('#');
-    text.push_str(text_2);
-    text.push('$');
-    let suffix_tree = SuffixTree::construct(&text);
-    let longest_shared_substring =
-        suffix_tree.get_longest_shared_substring(&text, text_1.len() + 1);
+    let suffix_tree = GeneralizedSuffixTree::new(&[text_1, text_2]);
+    let longest_shared_substring = String::from_utf8(
+        suffix_tree
+            .longest_common_substring(2)
+            .unwrap_or_default()
+            .to_vec(),
+    )?;
     println!("{}", longest_shared_substring);
     Ok(longest_shared_substring)
 }
@@ -79,10 +78,6 @@ pub trait TreeColor {
     ) -> HashMap<NodeIndex<u32>, NodeColor>;
 }
 
-pub trait LongestSharedSubstring {
-    fn get_longest_shared_substring(&self, text: &str, split_index: usize) -> String;
-}
-
 impl TreeColor for SuffixTree {
     fn _get_children(&self, node: NodeIndex<u32>, children: &mut Vec<NodeIndex<u32>>) {
         children.push(node);
@@ -131,36 +126,3 @@ impl TreeColor for SuffixTree {
         node_colors
     }
 }
-
-impl LongestSharedSubstring for SuffixTree {
-    fn get_longest_shared_substring(&self, text: &str, split_index: usize) -> String {
-        let node_colors: HashMap<_, _> = self
-            .tree
-            .node_indices()
-            .map(|n| {
-                if let Some(index) = self.tree[n] {
-                    if index <= split_index {
-                        (n, NodeColor::Red)
-                    } else {
-                        (n, NodeColor::Blue)
-                    }
-                } else {
-                    (n, NodeColor::Gray)
-                }
-            })
-            .collect();
-        let node_colors = self.tree_color(node_colors);
-        let (node_depths, node_ranges) = self.get_node_depths_edges();
-        let deepest_node = node_depths
-            .into_iter()
-            .filter(|(n, _)| node_colors[n] == NodeColor::Purple)
-            .max_by(|a, b| a.1.cmp(&b.1))
-            .unwrap()
-            .0;
-        node_ranges[&deepest_node]
-            .iter()
-            .map(|e| &text[self.tree[*e].0..self.tree[*e].0 + self.tree[*e].1])
-            .collect::<Vec<_>>()
-            .join("")
-    }
-}
@@ -1,7 +1,10 @@
 use anyhow::Error;
 
+use std::cmp::Reverse;
 use std::path::Path;
 
+use crate::sais::{inverse_suffix_array, lcp_array, suffix_array_from_bytes};
+
 /// Construct the suffix array of a string.
 ///
 /// Given: A string Text.
@@ -22,23 +25,47 @@ pub fn rosalind_ba9g(filename: &Path) -> Result<Vec<usize>, Error> {
     Ok(suffix_array.suffix_array)
 }
 
+pub mod sais;
+
 pub struct SuffixArray {
     pub suffix_array: Vec<usize>,
+    /// rank of each suffix in `suffix_array`
+    pub inverse_suffix_array: Vec<usize>,
+    /// `lcp_array[i]` is the longest common prefix of suffixes `suffix_array[i - 1]` and `suffix_array[i]`
+    pub lcp_array: Vec<usize>,
     pub text: String,
 }
 
 impl SuffixArray {
+    /// Build the suffix array in linear time with SA-IS, along with its inverse and LCP array
     pub fn construct(text: &str) -> Self {
-        let mut suffixes = Vec::new();
-        for i in 0..text.len() {
-            suffixes.push((text[i..text.len()].to_owned(), i));
-        }
-        suffixes.sort();
+        let suffix_array = suffix_array_from_bytes(text.as_bytes());
+        let inverse_suffix_array = inverse_suffix_array(&suffix_array);
+        let lcp_array = lcp_array(text.as_bytes(), &suffix_array, &inverse_suffix_array);
         SuffixArray {
-            suffix_array: suffixes.into_iter().map(|(_, i)| i).collect(),
+            suffix_array,
+            inverse_suffix_array,
+            lcp_array,
             text: text.to_owned(),
         }
     }
+
+    /// A longest substring that occurs more than once (possibly overlapping): the common prefix
+    /// of the adjacent suffixes with the largest LCP
+    pub fn longest_repeat(&self) -> Option<&str> {
+        let (rank, length) = self
+            .lcp_array
+            .iter()
+            .enumerate()
+            .skip(1)
+            .max_by_key(|(rank, length)| (**length, Reverse(*rank)))?;
+        if *length == 0 {
+            None
+        } else {
+            let start = self.suffix_array[rank];
+            Some(&self.text[start..start + length])
+        }
+    }
 }
 
 //impl From<SuffixTree> for SuffixArray {
@@ -63,3 +90,44 @@ impl SuffixArray {
 //    }
 //
 //}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sais_lcp() {
+        // small random DNA strings with lots of repeats, checked against naive sorting
+        for length in 0..200 {
+            let alphabet = &b"ACGT"[..1 + length % 4];
+            let text = utility::testing::random_sequence(length, alphabet, length as u64);
+            let suffix_array = SuffixArray::construct(&text);
+            let mut expected: Vec<usize> = (0..text.len()).collect();
+            expected.sort_by(|a, b| text[*a..].cmp(&text[*b..]));
+            assert_eq!(suffix_array.suffix_array, expected);
+            for rank in 1..text.len() {
+                let (previous, current) = (
+                    &text[suffix_array.suffix_array[rank - 1]..],
+                    &text[suffix_array.suffix_array[rank]..],
+                );
+                let lcp = previous
+                    .bytes()
+                    .zip(current.bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                assert_eq!(suffix_array.lcp_array[rank], lcp);
+                assert_eq!(
+                    suffix_array.inverse_suffix_array[suffix_array.suffix_array[rank]],
+                    rank
+                );
+            }
+        }
+        let suffix_array = SuffixArray::construct("CATACATAC");
+        assert_eq!(suffix_array.longest_repeat(), Some("CATAC"));
+        assert_eq!(SuffixArray::construct("ACGT").longest_repeat(), None);
+        assert_eq!(
+            sais::suffix_array_from_integers(&[2, 1, 0, 1, 0], 3),
+            vec![4, 2, 3, 1, 0]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Linear-time suffix array construction by induced sorting (SA-IS, Nong, Zhang & Chan, 2009),
+//! plus the inverse suffix array and Kasai's LCP array.
+
+const EMPTY: usize = usize::MAX;
+
+/// Suffix array of a byte string
+pub fn suffix_array_from_bytes(text: &[u8]) -> Vec<usize> {
+    let text: Vec<_> = text.iter().map(|c| *c as usize).collect();
+    suffix_array_from_integers(&text, 256)
+}
+
+/// Suffix array of a string over the integer alphabet `0..alphabet_size`
+pub fn suffix_array_from_integers(text: &[usize], alphabet_size: usize) -> Vec<usize> {
+    assert!(
+        text.iter().all(|c| *c < alphabet_size),
+        "character outside of alphabet"
+    );
+    // shift the alphabet up by one to append a unique smallest sentinel
+    let mut with_sentinel: Vec<_> = text.iter().map(|c| c + 1).collect();
+    with_sentinel.push(0);
+    let mut suffix_array = sais(&with_sentinel, alphabet_size + 1);
+    // the sentinel suffix always sorts first
+    suffix_array.remove(0);
+    suffix_array
+}
+
+/// Rank of each suffix, i.e. `inverse[suffix_array[i]] == i`
+pub fn inverse_suffix_array(suffix_array: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; suffix_array.len()];
+    for (rank, suffix) in suffix_array.iter().enumerate() {
+        inverse[*suffix] = rank;
+    }
+    inverse
+}
+
+/// Kasai's algorithm: `lcp[i]` is the length of the longest common prefix of the suffixes
+/// at `suffix_array[i - 1]` and `suffix_array[i]` (`lcp[0]` is 0)
+pub fn lcp_array<T: Eq>(text: &[T], suffix_array: &[usize], inverse: &[usize]) -> Vec<usize> {
+    let mut lcp = vec![0; text.len()];
+    let mut length = 0;
+    for suffix in 0..text.len() {
+        let rank = inverse[suffix];
+        if rank == 0 {
+            length = 0;
+            continue;
+        }
+        let previous = suffix_array[rank - 1];
+        while suffix + length < text.len()
+            && previous + length < text.len()
+            && text[suffix + length] == text[previous + length]
+        {
+            length += 1;
+        }
+        lcp[rank] = length;
+        length = length.saturating_sub(1);
+    }
+    lcp
+}
+
+/// SA-IS on a text whose last character is a unique smallest sentinel
+fn sais(text: &[usize], alphabet_size: usize) -> Vec<usize> {
+    let length = text.len();
+    if length == 1 {
+        return vec![0];
+    }
+    // a suffix is S-type if it's smaller than the next one, and L-type otherwise
+    let mut is_s_type = vec![false; length];
+    is_s_type[length - 1] = true;
+    for i in (0..length - 1).rev() {
+        is_s_type[i] = text[i] < text[i + 1] || (text[i] == text[i + 1] && is_s_type[i + 1]);
+    }
+    let is_lms = |i: usize| i > 0 && is_s_type[i] && !is_s_type[i - 1];
+    let mut bucket_sizes = vec![0; alphabet_size];
+    for c in text {
+        bucket_sizes[*c] += 1;
+    }
+
+    // sort the LMS substrings by induction from arbitrarily ordered LMS suffixes
+    let lms_positions: Vec<_> = (1..length).filter(|i| is_lms(*i)).collect();
+    let mut suffix_array = vec![EMPTY; length];
+    let mut tails = bucket_tails(&bucket_sizes);
+    for position in lms_positions.iter().rev() {
+        tails[text[*position]] -= 1;
+        suffix_array[tails[text[*position]]] = *position;
+    }
+    induce(text, &is_s_type, &bucket_sizes, &mut suffix_array);
+
+    // name each LMS substring by its rank, giving equal substrings equal names
+    let mut names = vec![EMPTY; length];
+    let mut name = 0;
+    let mut previous: Option<usize> = None;
+    for position in suffix_array.iter().copied().filter(|i| is_lms(*i)) {
+        if let Some(previous) = previous {
+            if !lms_substrings_equal(text, &is_s_type, previous, position) {
+                name += 1;
+            }
+        }
+        names[position] = name;
+        previous = Some(position);
+    }
+    let reduced_text: Vec<_> = lms_positions.iter().map(|i| names[*i]).collect();
+    let reduced_suffix_array = if name + 1 == reduced_text.len() {
+        let mut reduced_suffix_array = vec![0; reduced_text.len()];
+        for (i, name) in reduced_text.iter().enumerate() {
+            reduced_suffix_array[*name] = i;
+        }
+        reduced_suffix_array
+    } else {
+        sais(&reduced_text, name + 1)
+    };
+
+    // induce the full suffix array from the correctly sorted LMS suffixes
+    suffix_array.iter_mut().for_each(|s| *s = EMPTY);
+    let mut tails = bucket_tails(&bucket_sizes);
+    for reduced_position in reduced_suffix_array.into_iter().rev() {
+        let position = lms_positions[reduced_position];
+        tails[text[position]] -= 1;
+        suffix_array[tails[text[position]]] = position;
+    }
+    induce(text, &is_s_type, &bucket_sizes, &mut suffix_array);
+    suffix_array
+}
+
+fn bucket_heads(bucket_sizes: &[usize]) -> Vec<usize> {
+    bucket_sizes
+        .iter()
+        .scan(0, |start, size| {
+            let head = *start;
+            *start += size;
+            Some(head)
+        })
+        .collect()
+}
+
+fn bucket_tails(bucket_sizes: &[usize]) -> Vec<usize> {
+    bucket_sizes
+        .iter()
+        .scan(0, |end, size| {
+            *end += size;
+            Some(*end)
+        })
+        .collect()
+}
+
+/// Place L-type suffixes left to right from the bucket heads, then S-type suffixes
+/// right to left from the bucket tails
+fn induce(text: &[usize], is_s_type: &[bool], bucket_sizes: &[usize], suffix_array: &mut [usize]) {
+    let mut heads = bucket_heads(bucket_sizes);
+    for i in 0..text.len() {
+        let suffix = suffix_array[i];
+        if suffix != EMPTY && suffix > 0 && !is_s_type[suffix - 1] {
+            let c = text[suffix - 1];
+            suffix_array[heads[c]] = suffix - 1;
+            heads[c] += 1;
+        }
+    }
+    let mut tails = bucket_tails(bucket_sizes);
+    for i in (0..text.len()).rev() {
+        let suffix = suffix_array[i];
+        if suffix != EMPTY && suffix > 0 && is_s_type[suffix - 1] {
+            let c = text[suffix - 1];
+            tails[c] -= 1;
+            suffix_array[tails[c]] = suffix - 1;
+        }
+    }
+}
+
+/// Do the LMS substrings starting at `first` and `second` have the same characters and types
+fn lms_substrings_equal(text: &[usize], is_s_type: &[bool], first: usize, second: usize) -> bool {
+    let last = text.len() - 1;
+    if first == last || second == last {
+        return first == second;
+    }
+    let is_lms = |i: usize| is_s_type[i] && !is_s_type[i - 1];
+    for offset in 0.. {
+        let (first_lms, second_lms) = (is_lms(first + offset), is_lms(second + offset));
+        if offset > 0 && first_lms && second_lms {
+            return true;
+        }
+        if first_lms != second_lms
+            || text[first + offset] != text[second + offset]
+            || is_s_type[first + offset] != is_s_type[second + offset]
+        {
+            return false;
+        }
+    }
+    unreachable!()
+}
@@ -1,5 +1,6 @@
 use anyhow::Error;
 
+use std::cmp::Ordering;
 use std::path::Path;
 use t_ba9g::SuffixArray;
 
@@ -13,48 +14,173 @@ pub fn rosalind_ba9h(filename: &Path) -> Result<Vec<usize>, Error> {
     let mut lines = content.split('\n');
     let text = lines.next().unwrap().to_owned();
     let suffix_array = SuffixArray::construct(&text);
+    let search = LcpSearch::new(&suffix_array);
     let mut positions = Vec::new();
     for pattern in lines {
-        if let Some((first, last)) = suffix_array.pattern_match(pattern.trim()) {
-            positions.extend((first..=last).map(|i| suffix_array.suffix_array[i]));
-        }
+        positions.extend(search.positions(pattern.trim()));
     }
     println!("{}", utility::io::format_array(&positions));
     Ok(positions)
 }
 
 pub trait PatternMatch {
+    /// Range of ranks (inclusive) of the suffixes starting with `pattern`
     fn pattern_match(&self, pattern: &str) -> Option<(usize, usize)>;
 }
 
 impl PatternMatch for SuffixArray {
+    /// Builds the LCP search intervals on every call, prefer `LcpSearch` for many patterns
     fn pattern_match(&self, pattern: &str) -> Option<(usize, usize)> {
-        let mut min_index = 0;
-        let mut max_index = self.text.len();
-        let mut mid_index;
-        while min_index < max_index {
-            mid_index = (min_index + max_index) / 2;
-            if pattern > &self.text[self.suffix_array[mid_index]..] {
-                min_index = mid_index + 1;
+        LcpSearch::new(self).pattern_match(pattern)
+    }
+}
+
+/// Manber-Myers suffix array search in O(m + log n) per pattern.
+///
+/// The binary search always visits the same (left, right) intervals, so the LCP of the suffixes
+/// at the ends of each interval and its midpoint can be precomputed from the LCP array.
+/// Ranks are shifted by one so that 0 and n + 1 act as sentinels smaller and larger than every suffix.
+pub struct LcpSearch<'a> {
+    suffix_array: &'a SuffixArray,
+    /// `left_lcp[mid]` is the LCP of the suffixes at ranks left and mid of the interval with midpoint mid
+    left_lcp: Vec<usize>,
+    /// `right_lcp[mid]` is the LCP of the suffixes at ranks mid and right of the interval with midpoint mid
+    right_lcp: Vec<usize>,
+}
+
+impl<'a> LcpSearch<'a> {
+    pub fn new(suffix_array: &'a SuffixArray) -> Self {
+        let length = suffix_array.suffix_array.len();
+        let mut search = LcpSearch {
+            suffix_array,
+            left_lcp: vec![0; length + 2],
+            right_lcp: vec![0; length + 2],
+        };
+        search.fill_interval_lcps(0, length + 1);
+        search
+    }
+
+    /// LCP of the suffixes at the ends of the interval (left, right), filling in its sub-intervals
+    fn fill_interval_lcps(&mut self, left: usize, right: usize) -> usize {
+        let length = self.suffix_array.suffix_array.len();
+        if right - left <= 1 {
+            return if left == 0 || right == length + 1 {
+                0
             } else {
-                max_index = mid_index;
-            }
+                self.suffix_array.lcp_array[right - 1]
+            };
+        }
+        let mid = (left + right) / 2;
+        self.left_lcp[mid] = self.fill_interval_lcps(left, mid);
+        self.right_lcp[mid] = self.fill_interval_lcps(mid, right);
+        self.left_lcp[mid].min(self.right_lcp[mid])
+    }
+
+    pub fn pattern_match(&self, pattern: &str) -> Option<(usize, usize)> {
+        let first = self.boundary(pattern.as_bytes(), false);
+        let end = self.boundary(pattern.as_bytes(), true);
+        if first < end {
+            Some((first, end - 1))
+        } else {
+            None
         }
-        let first = min_index;
-        max_index = self.text.len();
-        while min_index < max_index {
-            mid_index = (min_index + max_index) / 2;
-            if pattern < &self.text[self.suffix_array[mid_index]..] {
-                max_index = mid_index;
+    }
+
+    /// Starting positions in the text of every occurrence of `pattern`
+    pub fn positions(&self, pattern: &str) -> Vec<usize> {
+        self.pattern_match(pattern)
+            .map_or_else(Vec::new, |(first, last)| {
+                self.suffix_array.suffix_array[first..=last].to_vec()
+            })
+    }
+
+    /// Rank of the first suffix not smaller than the pattern, or, if `after_matches`,
+    /// of the first suffix greater than the pattern and not starting with it
+    fn boundary(&self, pattern: &[u8], after_matches: bool) -> usize {
+        let text = self.suffix_array.text.as_bytes();
+        let (mut left, mut right) = (0, self.suffix_array.suffix_array.len() + 1);
+        // LCPs of the pattern with the suffixes at left and right
+        let (mut left_match, mut right_match) = (0, 0);
+        while right - left > 1 {
+            let mid = (left + right) / 2;
+            let (goes_left, matched) = if left_match >= right_match {
+                match self.left_lcp[mid].cmp(&left_match) {
+                    Ordering::Greater => (false, left_match),
+                    Ordering::Less => (true, self.left_lcp[mid]),
+                    Ordering::Equal => self.compare(text, pattern, mid, left_match, after_matches),
+                }
             } else {
-                min_index = mid_index + 1;
+                match self.right_lcp[mid].cmp(&right_match) {
+                    Ordering::Greater => (true, right_match),
+                    Ordering::Less => (false, self.right_lcp[mid]),
+                    Ordering::Equal => self.compare(text, pattern, mid, right_match, after_matches),
+                }
+            };
+            if goes_left {
+                right = mid;
+                right_match = matched;
+            } else {
+                left = mid;
+                left_match = matched;
             }
         }
-        let last = max_index;
-        if first > last {
-            None
+        right - 1
+    }
+
+    /// Compare the pattern to the suffix at (shifted) rank `mid`, skipping the first `matched` characters.
+    /// Returns whether the pattern sorts before the suffix and their LCP
+    fn compare(
+        &self,
+        text: &[u8],
+        pattern: &[u8],
+        mid: usize,
+        matched: usize,
+        after_matches: bool,
+    ) -> (bool, usize) {
+        let suffix = &text[self.suffix_array.suffix_array[mid - 1]..];
+        let mut matched = matched;
+        while matched < pattern.len()
+            && matched < suffix.len()
+            && pattern[matched] == suffix[matched]
+        {
+            matched += 1;
+        }
+        let goes_left = if matched == pattern.len() {
+            !after_matches
+        } else if matched == suffix.len() {
+            false
         } else {
-            Some((first, last))
+            pattern[matched] < suffix[matched]
+        };
+        (goes_left, matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lcp_search() {
+        let text = "AATCGGGTTCAATCGGGGTAATCGGGGAATCGGGTTCAAT";
+        let suffix_array = SuffixArray::construct(text);
+        let search = LcpSearch::new(&suffix_array);
+        for start in 0..text.len() {
+            for end in (start + 1)..=text.len().min(start + 8) {
+                let pattern = &text[start..end];
+                let mut positions = search.positions(pattern);
+                positions.sort_unstable();
+                let expected: Vec<_> = (0..=text.len() - pattern.len())
+                    .filter(|i| text[*i..].starts_with(pattern))
+                    .collect();
+                assert_eq!(positions, expected);
+            }
+        }
+        for pattern in &["AATCGA", "GGGGG", "C", "TTTT", "AATC"] {
+            let expected = (0..text.len())
+                .filter(|i| text[*i..].starts_with(pattern))
+                .count();
+            assert_eq!(search.positions(pattern).len(), expected);
         }
     }
 }
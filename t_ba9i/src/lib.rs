@@ -1,7 +1,13 @@
 use anyhow::Error;
 
 use std::path::Path;
+use t_ba9g::SuffixArray;
 
+/// Construct the Burrows-Wheeler Transform of a String
+///
+/// Given: A string Text.
+///
+/// Return: BWT(Text).
 pub fn rosalind_ba9i(filename: &Path) -> Result<String, Error> {
     let input_string = utility::io::input_from_file(filename)?;
     let output_string = bwt(&input_string);
@@ -9,14 +15,26 @@ pub fn rosalind_ba9i(filename: &Path) -> Result<String, Error> {
     Ok(output_string)
 }
 
-fn bwt(input_string: &str) -> String {
-    let mut rotations = Vec::with_capacity(input_string.len());
-    for i in 0..input_string.len() {
-        rotations.push(format!("{}{}", &input_string[i..], &input_string[..i]));
-    }
-    rotations.sort();
-    rotations
-        .into_iter()
-        .map(|r| r.chars().last().unwrap())
+/// Burrows-Wheeler transform of a text ending in a unique smallest character (like '$'),
+/// read off its suffix array
+pub fn bwt(input_string: &str) -> String {
+    String::from_utf8(bwt_from_suffix_array(
+        input_string.as_bytes(),
+        &SuffixArray::construct(input_string).suffix_array,
+    ))
+    .unwrap()
+}
+
+/// Character preceding each suffix, in suffix array order
+pub fn bwt_from_suffix_array(text: &[u8], suffix_array: &[usize]) -> Vec<u8> {
+    suffix_array
+        .iter()
+        .map(|suffix| {
+            if *suffix == 0 {
+                text[text.len() - 1]
+            } else {
+                text[suffix - 1]
+            }
+        })
         .collect()
 }
@@ -1,9 +1,12 @@
-use std::collections::HashMap;
-
 use anyhow::Error;
 
 use std::path::Path;
 
+/// Reconstruct a String from its Burrows-Wheeler Transform
+///
+/// Given: A string Transform (with a single "$" symbol).
+///
+/// Return: The string Text such that BWT(Text) = Transform.
 pub fn rosalind_ba9j(filename: &Path) -> Result<String, Error> {
     let bwt_string = utility::io::input_from_file(filename)?;
     let input_string = inverse_bwt(&bwt_string)?;
@@ -11,36 +14,37 @@ pub fn rosalind_ba9j(filename: &Path) -> Result<String, Error> {
     Ok(input_string)
 }
 
-fn inverse_bwt(text: &str) -> Result<String, Error> {
-    fn get_char_index_from_index(string: &[char], character: char, index: usize) -> Option<usize> {
-        let mut char_index = 0;
-        for (i, c) in string.iter().enumerate() {
-            if i == index && *c == character {
-                return Some(char_index);
-            }
-            if *c == character {
-                char_index += 1;
-            }
-        }
-        None
+/// Invert the Burrows-Wheeler transform in linear time by following the last-to-first mapping
+/// backwards from the row starting with '$'
+pub fn inverse_bwt(text: &str) -> Result<String, Error> {
+    let last_column = text.as_bytes();
+    if last_column.iter().filter(|c| **c == b'$').count() != 1 {
+        return Err(anyhow::anyhow!("BWT should have exactly one '$'"));
     }
-
-    let mut first_column: Vec<_> = text.chars().collect();
-    first_column.sort_unstable();
-    let last_column: Vec<_> = text.chars().collect();
-    let mut mapping_last = HashMap::new();
-    for (i, c) in last_column.iter().enumerate() {
-        mapping_last.entry(*c).or_insert_with(Vec::new).push(i);
+    // first row of each character in the first column, and rank of each character in the last
+    let mut counts = [0; 256];
+    let ranks: Vec<_> = last_column
+        .iter()
+        .map(|c| {
+            counts[*c as usize] += 1;
+            counts[*c as usize] - 1
+        })
+        .collect();
+    let mut first_row = [0; 256];
+    let mut total = 0;
+    for (c, count) in counts.iter().enumerate() {
+        first_row[c] = total;
+        total += count;
     }
-    let mut input_string = String::with_capacity(text.len());
-    let mut last_index = last_column.iter().position(|a| *a == '$').unwrap();
-    let mut first_char = first_column[last_index];
-    input_string.push(first_char);
-    while input_string.len() < text.len() {
-        let char_index = get_char_index_from_index(&first_column, first_char, last_index).unwrap();
-        last_index = mapping_last[&first_char][char_index];
-        first_char = first_column[last_index];
-        input_string.push(first_char);
+    // row 0 is the rotation starting with '$', so its last character is the one before '$'
+    let mut input_string = Vec::with_capacity(last_column.len());
+    let mut row = 0;
+    for _ in 1..last_column.len() {
+        let c = last_column[row];
+        input_string.push(c);
+        row = first_row[c as usize] + ranks[row];
     }
-    Ok(input_string)
+    input_string.reverse();
+    input_string.push(b'$');
+    Ok(String::from_utf8(input_string)?)
 }
@@ -0,0 +1,405 @@
+//! FM-index: the Burrows-Wheeler transform with occurrence checkpoints for backward search
+//! (`count`) and a suffix array sampled at every `sa_interval`-th text position (`locate`).
+//!
+//! Indices can be written to disk once with `save` and reloaded with `load`.
+use std::convert::{TryFrom, TryInto};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::Error;
+
+use t_ba9g::sais::suffix_array_from_bytes;
+use t_ba9i::bwt_from_suffix_array;
+
+const MAGIC: &[u8; 8] = b"FMINDEX1";
+const NOT_IN_ALPHABET: usize = usize::MAX;
+
+/// Sampling rates of an `FmIndex`, trading memory for query time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmIndexConfig {
+    /// Distance between occurrence checkpoints in the BWT
+    pub occ_interval: usize,
+    /// Distance between sampled text positions
+    pub sa_interval: usize,
+}
+
+impl Default for FmIndexConfig {
+    fn default() -> Self {
+        FmIndexConfig {
+            occ_interval: 64,
+            sa_interval: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FmIndex {
+    config: FmIndexConfig,
+    bwt: Vec<u8>,
+    /// index of each byte in the sorted alphabet, or NOT_IN_ALPHABET
+    symbol_indices: Vec<usize>,
+    /// first row of each symbol in the first column
+    first_occurrence: Vec<usize>,
+    /// `checkpoints[i * alphabet_size + symbol]` counts the symbol in `bwt[..i * occ_interval]`
+    checkpoints: Vec<usize>,
+    /// bit set for each row whose suffix starts at a sampled text position
+    sampled_rows: Vec<u64>,
+    /// number of set bits in `sampled_rows` before each word
+    sampled_row_ranks: Vec<usize>,
+    /// text positions of the sampled rows, in row order
+    suffix_array_samples: Vec<usize>,
+}
+
+impl FmIndex {
+    /// Index a text ending in a unique smallest character (like '$').
+    /// The suffix array samples are taken straight from the suffix array.
+    pub fn new(text: &str, config: FmIndexConfig) -> Self {
+        let suffix_array = suffix_array_from_bytes(text.as_bytes());
+        let mut index = Self::with_checkpoints(
+            bwt_from_suffix_array(text.as_bytes(), &suffix_array),
+            config,
+        );
+        let samples = suffix_array
+            .into_iter()
+            .enumerate()
+            .filter(|(_, position)| position % config.sa_interval == 0)
+            .collect();
+        index.set_suffix_array_samples(samples);
+        index
+    }
+
+    /// Index a Burrows-Wheeler transform directly.
+    /// The suffix array samples are recovered by walking the last-to-first mapping through the text.
+    pub fn from_bwt(bwt: Vec<u8>, config: FmIndexConfig) -> Self {
+        let mut index = Self::with_checkpoints(bwt, config);
+        index.sample_suffix_array();
+        index
+    }
+
+    /// Index without suffix array samples
+    fn with_checkpoints(bwt: Vec<u8>, config: FmIndexConfig) -> Self {
+        assert!(
+            config.occ_interval > 0 && config.sa_interval > 0,
+            "sampling intervals must be positive"
+        );
+        let mut counts = vec![0; 256];
+        for c in &bwt {
+            counts[*c as usize] += 1;
+        }
+        let mut symbol_indices = vec![NOT_IN_ALPHABET; 256];
+        let mut first_occurrence = Vec::new();
+        let mut total = 0;
+        for (c, count) in counts.iter().enumerate() {
+            if *count > 0 {
+                symbol_indices[c] = first_occurrence.len();
+                first_occurrence.push(total);
+                total += count;
+            }
+        }
+        let alphabet_size = first_occurrence.len();
+        let mut checkpoints =
+            Vec::with_capacity((bwt.len() / config.occ_interval + 1) * alphabet_size);
+        let mut running = vec![0; alphabet_size];
+        for (row, c) in bwt.iter().enumerate() {
+            if row % config.occ_interval == 0 {
+                checkpoints.extend_from_slice(&running);
+            }
+            running[symbol_indices[*c as usize]] += 1;
+        }
+        // queries at row bwt.len() need a checkpoint for its block too
+        let num_blocks = bwt.len() / config.occ_interval + 1;
+        if checkpoints.len() < num_blocks * alphabet_size {
+            checkpoints.extend_from_slice(&running);
+        }
+        FmIndex {
+            config,
+            bwt,
+            symbol_indices,
+            first_occurrence,
+            checkpoints,
+            sampled_rows: Vec::new(),
+            sampled_row_ranks: Vec::new(),
+            suffix_array_samples: Vec::new(),
+        }
+    }
+
+    /// Walk backwards through the text from row 0 (the suffix made of just the last character),
+    /// recording the rows of sampled text positions
+    fn sample_suffix_array(&mut self) {
+        let length = self.bwt.len();
+        let mut samples = Vec::with_capacity(length / self.config.sa_interval + 1);
+        let mut row = 0;
+        for position in (0..length).rev() {
+            if position % self.config.sa_interval == 0 {
+                samples.push((row, position));
+            }
+            if position > 0 {
+                row = self.last_to_first(row);
+            }
+        }
+        samples.sort_unstable();
+        self.set_suffix_array_samples(samples);
+    }
+
+    /// Store (row, text position) samples, sorted by row
+    fn set_suffix_array_samples(&mut self, samples: Vec<(usize, usize)>) {
+        let mut sampled_rows = vec![0u64; self.bwt.len() / 64 + 1];
+        for (row, _) in &samples {
+            sampled_rows[row / 64] |= 1 << (row % 64);
+        }
+        self.sampled_row_ranks = sampled_rows
+            .iter()
+            .scan(0, |rank, word| {
+                let before = *rank;
+                *rank += word.count_ones() as usize;
+                Some(before)
+            })
+            .collect();
+        self.sampled_rows = sampled_rows;
+        self.suffix_array_samples = samples.into_iter().map(|(_, position)| position).collect();
+    }
+
+    /// Length of the indexed text
+    pub fn len(&self) -> usize {
+        self.bwt.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bwt.is_empty()
+    }
+
+    pub fn bwt(&self) -> &[u8] {
+        &self.bwt
+    }
+
+    /// Number of occurrences of the symbol with alphabet index `symbol` in `bwt[..row]`
+    fn occurrences(&self, symbol: usize, row: usize) -> usize {
+        let block = row / self.config.occ_interval;
+        let start = block * self.config.occ_interval;
+        let alphabet_size = self.first_occurrence.len();
+        self.checkpoints[block * alphabet_size + symbol]
+            + self.bwt[start..row]
+                .iter()
+                .filter(|c| self.symbol_indices[**c as usize] == symbol)
+                .count()
+    }
+
+    /// Row of the rotation starting with the last character of `row`
+    fn last_to_first(&self, row: usize) -> usize {
+        let symbol = self.symbol_indices[self.bwt[row] as usize];
+        self.first_occurrence[symbol] + self.occurrences(symbol, row)
+    }
+
+    /// Characters of the text in sorted order (the first being the terminating character)
+    pub fn alphabet(&self) -> Vec<u8> {
+        (0..=255)
+            .filter(|c| self.symbol_indices[*c as usize] != NOT_IN_ALPHABET)
+            .collect()
+    }
+
+    /// Rows of all the rotations starting with `c` followed by one of the rows in `rows`
+    pub fn extend(&self, rows: Range<usize>, c: u8) -> Range<usize> {
+        let symbol = self.symbol_indices[c as usize];
+        if symbol == NOT_IN_ALPHABET {
+            return 0..0;
+        }
+        let top = self.first_occurrence[symbol] + self.occurrences(symbol, rows.start);
+        let bottom = self.first_occurrence[symbol] + self.occurrences(symbol, rows.end);
+        top..bottom
+    }
+
+    /// Backward search: the range of rows whose suffixes start with `pattern`
+    pub fn rows(&self, pattern: &[u8]) -> Range<usize> {
+        let mut rows = 0..self.bwt.len();
+        for c in pattern.iter().rev() {
+            rows = self.extend(rows, *c);
+            if rows.is_empty() {
+                return 0..0;
+            }
+        }
+        rows
+    }
+
+    /// Number of occurrences of `pattern` in the text
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.rows(pattern).len()
+    }
+
+    /// Sorted starting positions of `pattern` in the text
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions: Vec<_> = self
+            .rows(pattern)
+            .map(|row| self.suffix_position(row))
+            .collect();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// Text position of the suffix in `row`, found by walking back to a sampled position
+    pub fn suffix_position(&self, row: usize) -> usize {
+        let mut row = row;
+        let mut steps = 0;
+        loop {
+            let (word, bit) = (row / 64, row % 64);
+            if self.sampled_rows[word] & (1 << bit) != 0 {
+                let sample = self.sampled_row_ranks[word]
+                    + (self.sampled_rows[word] & ((1 << bit) - 1)).count_ones() as usize;
+                return self.suffix_array_samples[sample] + steps;
+            }
+            row = self.last_to_first(row);
+            steps += 1;
+        }
+    }
+
+    /// Write the index to a file
+    pub fn save(&self, filename: &Path) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read an index written by `save`
+    pub fn load(filename: &Path) -> Result<Self, Error> {
+        Self::read_from(&mut BufReader::new(File::open(filename)?))
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(MAGIC)?;
+        write_usizes(
+            writer,
+            &[
+                self.config.occ_interval,
+                self.config.sa_interval,
+                self.bwt.len(),
+            ],
+        )?;
+        writer.write_all(&self.bwt)?;
+        for array in &[
+            &self.symbol_indices,
+            &self.first_occurrence,
+            &self.checkpoints,
+            &self.sampled_row_ranks,
+            &self.suffix_array_samples,
+        ] {
+            write_usizes(writer, &[array.len()])?;
+            write_usizes(writer, array)?;
+        }
+        write_usizes(writer, &[self.sampled_rows.len()])?;
+        for word in &self.sampled_rows {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read an index written by `write_to`. Lengths in the file are checked against the rest
+    /// of the input, and the arrays against each other, so a corrupt or truncated file is an
+    /// error rather than a panic or a huge allocation.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let input = &mut &bytes[..];
+        if take(input, MAGIC.len())? != MAGIC {
+            return Err(anyhow::anyhow!("Not an FM-index file"));
+        }
+        let header = read_usizes(input, 3)?;
+        let config = FmIndexConfig {
+            occ_interval: header[0],
+            sa_interval: header[1],
+        };
+        let bwt = take(input, header[2])?.to_vec();
+        let mut arrays = Vec::with_capacity(5);
+        for _ in 0..5 {
+            let length = read_usizes(input, 1)?[0];
+            arrays.push(read_usizes(input, length)?);
+        }
+        let num_words = read_usizes(input, 1)?[0];
+        let sampled_rows = read_u64s(input, num_words)?;
+        if !input.is_empty() {
+            return Err(anyhow::anyhow!("Trailing bytes after the FM-index"));
+        }
+        let mut arrays = arrays.into_iter();
+        let index = FmIndex {
+            config,
+            bwt,
+            symbol_indices: arrays.next().unwrap(),
+            first_occurrence: arrays.next().unwrap(),
+            checkpoints: arrays.next().unwrap(),
+            sampled_row_ranks: arrays.next().unwrap(),
+            suffix_array_samples: arrays.next().unwrap(),
+            sampled_rows,
+        };
+        if !index.is_consistent() {
+            return Err(anyhow::anyhow!("Inconsistent FM-index file"));
+        }
+        Ok(index)
+    }
+
+    /// Whether the arrays have the sizes `with_checkpoints` and `set_suffix_array_samples` give
+    /// them, so that queries stay in bounds
+    fn is_consistent(&self) -> bool {
+        let alphabet_size = self.first_occurrence.len();
+        self.config.occ_interval > 0
+            && self.config.sa_interval > 0
+            && self.symbol_indices.len() == 256
+            && self
+                .bwt
+                .iter()
+                .all(|c| self.symbol_indices[*c as usize] < alphabet_size)
+            && self
+                .first_occurrence
+                .iter()
+                .all(|first| *first <= self.bwt.len())
+            && self.checkpoints.len()
+                == (self.bwt.len() / self.config.occ_interval + 1) * alphabet_size
+            && self.sampled_rows.len() == self.bwt.len() / 64 + 1
+            && self.sampled_row_ranks.len() == self.sampled_rows.len()
+            && self.suffix_array_samples.len()
+                == self
+                    .sampled_rows
+                    .iter()
+                    .map(|word| word.count_ones() as usize)
+                    .sum::<usize>()
+    }
+}
+
+/// Integers are stored as little-endian u64s so that files are portable
+fn write_usizes<W: Write>(writer: &mut W, values: &[usize]) -> Result<(), Error> {
+    for value in values {
+        writer.write_all(&(*value as u64).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// The next `length` bytes of the input, which must have that many left
+fn take<'a>(input: &mut &'a [u8], length: usize) -> Result<&'a [u8], Error> {
+    if length > input.len() {
+        return Err(anyhow::anyhow!(
+            "Truncated FM-index file: {} bytes expected, {} left",
+            length,
+            input.len()
+        ));
+    }
+    let (taken, rest) = input.split_at(length);
+    *input = rest;
+    Ok(taken)
+}
+
+fn read_u64s(input: &mut &[u8], count: usize) -> Result<Vec<u64>, Error> {
+    let length = count
+        .checked_mul(8)
+        .ok_or_else(|| anyhow::anyhow!("Truncated FM-index file: {} values expected", count))?;
+    Ok(take(input, length)?
+        .chunks_exact(8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+fn read_usizes(input: &mut &[u8], count: usize) -> Result<Vec<usize>, Error> {
+    read_u64s(input, count)?
+        .into_iter()
+        .map(|value| Ok(usize::try_from(value)?))
+        .collect()
+}
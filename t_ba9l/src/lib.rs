@@ -1,10 +1,10 @@
-use std::collections::HashMap;
-use std::hash::Hash;
-
 use anyhow::Error;
 
 use std::path::Path;
-use t_ba9k::get_last_to_first_mapping;
+
+pub mod fm_index;
+
+pub use crate::fm_index::{FmIndex, FmIndexConfig};
 
 /// Implement BWMatching
 ///
@@ -15,47 +15,64 @@ use t_ba9k::get_last_to_first_mapping;
 pub fn rosalind_ba9l(filename: &Path) -> Result<Vec<usize>, Error> {
     let content = utility::io::input_from_file(filename)?;
     let lines: Vec<_> = content.split('\n').collect();
-    let bwt_text: Vec<_> = lines[0].chars().collect();
-    let mut first_column = bwt_text.clone();
-    first_column.sort_unstable();
-    let mut counts = Vec::new();
-    let last_to_first = get_last_to_first_mapping(&bwt_text, &first_column);
-    for pattern in lines[1].split_whitespace() {
-        let pattern: Vec<_> = pattern.chars().collect();
-        counts.push(bwm_matching(
-            &first_column,
-            &bwt_text,
-            &pattern,
-            &last_to_first,
-        ));
-    }
+    let fm_index = FmIndex::from_bwt(
+        lines[0].trim().as_bytes().to_vec(),
+        FmIndexConfig::default(),
+    );
+    let counts: Vec<_> = lines[1]
+        .split_whitespace()
+        .map(|pattern| fm_index.count(pattern.as_bytes()))
+        .collect();
     println!("{}", utility::io::format_array(&counts));
     Ok(counts)
 }
 
-fn bwm_matching<T: Hash + Eq>(
-    _first_column: &[T],
-    last_column: &[T],
-    pattern: &[T],
-    last_to_first: &HashMap<usize, usize>,
-) -> usize {
-    let mut top = 0;
-    let mut bottom = last_column.len() - 1;
-    let mut pattern = pattern.iter().rev();
-    while top <= bottom {
-        if let Some(last_letter) = pattern.next() {
-            let indices: Vec<_> = (top..=bottom)
-                .filter(|i| &last_column[*i] == last_letter)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fm_index() -> Result<(), Error> {
+        let text = "panamabananas$";
+        let config = FmIndexConfig {
+            occ_interval: 3,
+            sa_interval: 4,
+        };
+        let fm_index = FmIndex::new(text, config);
+        assert_eq!(fm_index.bwt(), b"smnpbnnaaaaa$a");
+        // sampling the suffix array and walking the BWT give the same samples
+        assert_eq!(FmIndex::from_bwt(fm_index.bwt().to_vec(), config), fm_index);
+        for pattern in &["ana", "a", "nas", "panamabananas$", "x", "ban", "s$"] {
+            let expected: Vec<_> = (0..text.len())
+                .filter(|i| text[*i..].starts_with(pattern))
                 .collect();
-            if indices.is_empty() {
-                return 0;
-            } else {
-                top = last_to_first[&indices[0]];
-                bottom = last_to_first[&indices[indices.len() - 1]];
-            }
-        } else {
-            return bottom - top + 1;
+            assert_eq!(fm_index.count(pattern.as_bytes()), expected.len());
+            assert_eq!(fm_index.locate(pattern.as_bytes()), expected);
+        }
+        let mut buffer = Vec::new();
+        fm_index.write_to(&mut buffer)?;
+        assert_eq!(FmIndex::read_from(&mut &buffer[..])?, fm_index);
+        // truncated files and absurd lengths are errors, not panics or huge allocations
+        for length in 0..buffer.len() {
+            assert!(FmIndex::read_from(&mut &buffer[..length]).is_err());
         }
+        let mut huge_bwt = buffer.clone();
+        huge_bwt[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(FmIndex::read_from(&mut &huge_bwt[..]).is_err());
+        let mut huge_array = buffer.clone();
+        let symbol_indices_length = 32 + fm_index.len();
+        huge_array[symbol_indices_length..symbol_indices_length + 8]
+            .copy_from_slice(&(u64::MAX / 4).to_le_bytes());
+        assert!(FmIndex::read_from(&mut &huge_array[..]).is_err());
+        let mut bad_interval = buffer.clone();
+        bad_interval[8..16].copy_from_slice(&0u64.to_le_bytes());
+        assert!(FmIndex::read_from(&mut &bad_interval[..]).is_err());
+        buffer.push(0);
+        assert!(FmIndex::read_from(&mut &buffer[..]).is_err());
+        assert_eq!(
+            t_ba9j::inverse_bwt(std::str::from_utf8(fm_index.bwt())?)?,
+            text
+        );
+        Ok(())
     }
-    0
 }
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::thread;
+
+use anyhow::Error;
+
+use t_ba9l::{FmIndex, FmIndexConfig};
+
+/// Solve the Multiple Approximate Pattern Matching Problem
+///
+/// Given: A string Text, a collection of strings Patterns, and an integer d.
+///
+/// Return: All positions in Text where a string from Patterns appears as a substring with at most
+/// d mismatches.
+pub fn rosalind_ba9o(filename: &Path) -> Result<Vec<usize>, Error> {
+    let contents = utility::io::input_from_file(filename)?;
+    let lines: Vec<_> = contents.split('\n').collect();
+    let text = lines[0].trim();
+    let patterns: Vec<_> = lines[1].split_whitespace().map(|p| p.as_bytes()).collect();
+    let max_mismatches = lines[2].trim().parse::<usize>()?;
+    let matcher = ApproximateMatcher::new(text);
+    let mut positions: Vec<_> = matcher
+        .find_all(&patterns, max_mismatches, SearchStrategy::SeedAndVerify, 4)
+        .into_iter()
+        .map(|m| m.position)
+        .collect();
+    positions.sort_unstable();
+    println!("{}", utility::io::format_array(&positions));
+    Ok(positions)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStrategy {
+    /// Split each pattern into d + 1 pieces, at least one of which must match exactly
+    /// (pigeonhole principle), locate those with the FM-index and check the candidates
+    /// against the text. Fast for long patterns and small d.
+    SeedAndVerify,
+    /// Extend the pattern backwards one character at a time through the FM-index, branching
+    /// on every character while mismatches remain. Doesn't need the text, but explores
+    /// O((σm)^d) paths.
+    Backtracking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApproximateMatch {
+    /// index of the matching pattern
+    pub pattern_index: usize,
+    /// starting position in the text
+    pub position: usize,
+    pub mismatches: usize,
+}
+
+/// Text with its FM-index, for finding patterns with mismatches
+pub struct ApproximateMatcher {
+    text: Vec<u8>,
+    fm_index: FmIndex,
+}
+
+impl ApproximateMatcher {
+    /// Index a text (without a terminating '$')
+    pub fn new(text: &str) -> Self {
+        let fm_index = FmIndex::new(&format!("{}$", text), FmIndexConfig::default());
+        Self::from_index(text, fm_index)
+    }
+
+    /// Use an existing index of `text` followed by '$', e.g. one loaded from disk
+    pub fn from_index(text: &str, fm_index: FmIndex) -> Self {
+        assert_eq!(
+            fm_index.len(),
+            text.len() + 1,
+            "index doesn't match the text"
+        );
+        ApproximateMatcher {
+            text: text.as_bytes().to_vec(),
+            fm_index,
+        }
+    }
+
+    /// Starting positions and mismatch counts of every occurrence of `pattern`
+    /// with at most `max_mismatches` mismatches, sorted by position
+    pub fn find(
+        &self,
+        pattern: &[u8],
+        max_mismatches: usize,
+        strategy: SearchStrategy,
+    ) -> Vec<(usize, usize)> {
+        if pattern.is_empty() || pattern.len() > self.text.len() {
+            return Vec::new();
+        }
+        let mut matches = match strategy {
+            SearchStrategy::SeedAndVerify => self.seed_and_verify(pattern, max_mismatches),
+            SearchStrategy::Backtracking => {
+                let mut matches = Vec::new();
+                // the terminating '$' can't be part of a match
+                let mut alphabet = self.fm_index.alphabet();
+                alphabet.retain(|c| *c != b'$');
+                self.backtrack(
+                    pattern,
+                    &alphabet,
+                    0..self.fm_index.len(),
+                    0,
+                    max_mismatches,
+                    &mut matches,
+                );
+                matches
+            }
+        };
+        matches.sort_unstable();
+        matches
+    }
+
+    /// Matches of every pattern, searched in parallel over `num_threads` threads
+    /// and sorted by pattern then position
+    pub fn find_all(
+        &self,
+        patterns: &[&[u8]],
+        max_mismatches: usize,
+        strategy: SearchStrategy,
+        num_threads: usize,
+    ) -> Vec<ApproximateMatch> {
+        let chunk_size = chunk_size(patterns.len(), num_threads);
+        thread::scope(|scope| {
+            let handles: Vec<_> = patterns
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(chunk_index, chunk)| {
+                    scope.spawn(move || {
+                        let mut matches = Vec::new();
+                        for (offset, pattern) in chunk.iter().enumerate() {
+                            let pattern_index = chunk_index * chunk_size + offset;
+                            matches.extend(
+                                self.find(pattern, max_mismatches, strategy)
+                                    .into_iter()
+                                    .map(|(position, mismatches)| ApproximateMatch {
+                                        pattern_index,
+                                        position,
+                                        mismatches,
+                                    }),
+                            );
+                        }
+                        matches
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+
+    fn seed_and_verify(&self, pattern: &[u8], max_mismatches: usize) -> Vec<(usize, usize)> {
+        let num_seeds = (max_mismatches + 1).min(pattern.len());
+        let seed_length = pattern.len() / num_seeds;
+        // candidate start position -> number of mismatches (None if too many)
+        let mut candidates: HashMap<usize, Option<usize>> = HashMap::new();
+        for seed_index in 0..num_seeds {
+            let start = seed_index * seed_length;
+            let end = if seed_index == num_seeds - 1 {
+                pattern.len()
+            } else {
+                start + seed_length
+            };
+            for seed_position in self.fm_index.locate(&pattern[start..end]) {
+                if seed_position < start || seed_position - start + pattern.len() > self.text.len()
+                {
+                    continue;
+                }
+                let position = seed_position - start;
+                candidates.entry(position).or_insert_with(|| {
+                    let mismatches = self.text[position..position + pattern.len()]
+                        .iter()
+                        .zip(pattern)
+                        .filter(|(a, b)| a != b)
+                        .count();
+                    Some(mismatches).filter(|m| *m <= max_mismatches)
+                });
+            }
+        }
+        candidates
+            .into_iter()
+            .filter_map(|(position, mismatches)| mismatches.map(|m| (position, m)))
+            .collect()
+    }
+
+    /// Match `pattern` backwards from its end, `rows` being the rows starting with
+    /// the already-matched suffix of the pattern
+    fn backtrack(
+        &self,
+        pattern: &[u8],
+        alphabet: &[u8],
+        rows: Range<usize>,
+        mismatches: usize,
+        max_mismatches: usize,
+        matches: &mut Vec<(usize, usize)>,
+    ) {
+        let (c, rest) = match pattern.split_last() {
+            Some(split) => split,
+            None => {
+                matches.extend(rows.map(|row| (self.fm_index.suffix_position(row), mismatches)));
+                return;
+            }
+        };
+        for a in alphabet {
+            let mismatches = mismatches + (a != c) as usize;
+            if mismatches > max_mismatches {
+                continue;
+            }
+            let extended_rows = self.fm_index.extend(rows.clone(), *a);
+            if !extended_rows.is_empty() {
+                self.backtrack(
+                    rest,
+                    alphabet,
+                    extended_rows,
+                    mismatches,
+                    max_mismatches,
+                    matches,
+                );
+            }
+        }
+    }
+}
+
+/// Patterns per thread, so that there are at most `num_threads` chunks
+fn chunk_size(num_patterns: usize, num_threads: usize) -> usize {
+    num_patterns.div_ceil(num_threads.max(1)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use utility::io::Parseable;
+
+    use super::*;
+
+    #[test]
+    fn ba9o() -> Result<(), Error> {
+        let (input_file, output_file) = utility::testing::get_input_output_file("rosalind_ba9o")?;
+        let output = usize::parse_line(&utility::io::input_from_file(&output_file)?)?;
+        assert_eq!(rosalind_ba9o(&input_file)?, output);
+        Ok(())
+    }
+
+    #[test]
+    fn approximate_strategies() {
+        let text = "ACATGCTACTTTACATGCTAGGTCAGATTACA";
+        let matcher = ApproximateMatcher::new(text);
+        let patterns: Vec<&[u8]> = vec![b"ATT", b"GCC", b"GCTA", b"TATT", b"ACAT", b"CAGATTAC"];
+        for max_mismatches in 0..3 {
+            let mut expected = Vec::new();
+            for (pattern_index, pattern) in patterns.iter().enumerate() {
+                for position in 0..=(text.len() - pattern.len()) {
+                    let mismatches = text.as_bytes()[position..position + pattern.len()]
+                        .iter()
+                        .zip(pattern.iter())
+                        .filter(|(a, b)| a != b)
+                        .count();
+                    if mismatches <= max_mismatches {
+                        expected.push(ApproximateMatch {
+                            pattern_index,
+                            position,
+                            mismatches,
+                        });
+                    }
+                }
+            }
+            for strategy in &[SearchStrategy::SeedAndVerify, SearchStrategy::Backtracking] {
+                assert_eq!(
+                    matcher.find_all(&patterns, max_mismatches, *strategy, 3),
+                    expected
+                );
+            }
+        }
+
+        // symbols sorting before '$' are still searched
+        let matcher = ApproximateMatcher::new("AC\tGT#A");
+        assert_eq!(
+            matcher.find(b"C\tG", 1, SearchStrategy::Backtracking),
+            vec![(1, 0)]
+        );
+        assert_eq!(
+            matcher.find(b"T#A", 1, SearchStrategy::Backtracking),
+            vec![(4, 0)]
+        );
+    }
+
+    #[test]
+    fn thread_chunks() {
+        assert_eq!(chunk_size(10, 4), 3);
+        for num_patterns in 0..50 {
+            for num_threads in 0..10 {
+                let chunk_size = chunk_size(num_patterns, num_threads);
+                assert!(num_patterns.div_ceil(chunk_size) <= num_threads.max(1));
+            }
+        }
+    }
+}
@@ -0,0 +1,590 @@
+//! Lazy enumeration of words, permutations and subsets, with ranking and unranking.
+//!
+//! Each `Enumeration` lists its objects in a fixed order, so that rank i ↔ i-th object.
+//! Iterators start by unranking their first object and then step from one object to the next,
+//! so a range of ranks (e.g. one shard of the work) can be enumerated on its own.
+use std::convert::TryFrom;
+use std::ops::Range;
+
+pub trait Enumeration {
+    type Item: Clone;
+
+    /// Number of objects
+    fn size(&self) -> u128;
+
+    /// Object of rank `rank`, which must be less than `size`
+    fn unrank(&self, rank: u128) -> Self::Item;
+
+    /// Rank of an object, None if it isn't one of the enumerated objects
+    fn rank(&self, item: &Self::Item) -> Option<u128>;
+
+    /// Step to the next object in order, returning false if `item` was the last one
+    fn advance(&self, item: &mut Self::Item) -> bool;
+
+    fn iter(&self) -> EnumerationIter<&Self>
+    where
+        Self: Sized,
+    {
+        self.iter_range(0..self.size())
+    }
+
+    /// The objects with ranks in `ranks`
+    fn iter_range(&self, ranks: Range<u128>) -> EnumerationIter<&Self>
+    where
+        Self: Sized,
+    {
+        EnumerationIter::new(self, ranks)
+    }
+
+    /// Like `iter_range`, for an iterator that outlives the borrow of the enumeration
+    fn into_iter_range(self, ranks: Range<u128>) -> EnumerationIter<Self>
+    where
+        Self: Sized,
+    {
+        EnumerationIter::new(self, ranks)
+    }
+
+    /// The `index`-th of `shards` contiguous and (nearly) equal parts of the enumeration
+    fn shard(&self, index: usize, shards: usize) -> EnumerationIter<&Self>
+    where
+        Self: Sized,
+    {
+        assert!(index < shards, "shard index out of range");
+        let (size, shards) = (self.size(), shards as u128);
+        // size * i / shards, without overflowing
+        let boundary = |i: u128| size / shards * i + size % shards * i / shards;
+        self.iter_range(boundary(index as u128)..boundary(index as u128 + 1))
+    }
+}
+
+impl<E: Enumeration> Enumeration for &E {
+    type Item = E::Item;
+
+    fn size(&self) -> u128 {
+        (*self).size()
+    }
+
+    fn unrank(&self, rank: u128) -> Self::Item {
+        (*self).unrank(rank)
+    }
+
+    fn rank(&self, item: &Self::Item) -> Option<u128> {
+        (*self).rank(item)
+    }
+
+    fn advance(&self, item: &mut Self::Item) -> bool {
+        (*self).advance(item)
+    }
+}
+
+pub struct EnumerationIter<E: Enumeration> {
+    enumeration: E,
+    current: Option<E::Item>,
+    remaining: u128,
+}
+
+impl<E: Enumeration> EnumerationIter<E> {
+    fn new(enumeration: E, ranks: Range<u128>) -> Self {
+        let end = ranks.end.min(enumeration.size());
+        let current = if ranks.start < end {
+            Some(enumeration.unrank(ranks.start))
+        } else {
+            None
+        };
+        EnumerationIter {
+            enumeration,
+            current,
+            remaining: end.saturating_sub(ranks.start),
+        }
+    }
+}
+
+impl<E: Enumeration> Iterator for EnumerationIter<E> {
+    type Item = E::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let current = self.current.take()?;
+        if self.remaining > 0 {
+            let mut next = current.clone();
+            if self.enumeration.advance(&mut next) {
+                self.current = Some(next);
+            }
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining).unwrap_or(usize::MAX);
+        (remaining, usize::try_from(self.remaining).ok())
+    }
+}
+
+fn checked_size(size: Option<u128>) -> u128 {
+    size.expect("too many objects to rank in a u128")
+}
+
+/// Position of each symbol of a word in the ordered symbols
+fn digits<T: PartialEq>(symbols: &[T], word: &[T]) -> Option<Vec<usize>> {
+    word.iter()
+        .map(|c| symbols.iter().position(|symbol| symbol == c))
+        .collect()
+}
+
+/// Words of a fixed length over ordered symbols, in lexicographic order
+/// (the order of the symbols, not their natural one)
+#[derive(Debug, Clone)]
+pub struct Words<T> {
+    symbols: Vec<T>,
+    length: usize,
+    size: u128,
+}
+
+impl<T: Clone + PartialEq> Words<T> {
+    pub fn new(symbols: Vec<T>, length: usize) -> Self {
+        assert!(!symbols.is_empty(), "no symbols");
+        let size = checked_size((symbols.len() as u128).checked_pow(length as u32));
+        Words {
+            symbols,
+            length,
+            size,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Enumeration for Words<T> {
+    type Item = Vec<T>;
+
+    fn size(&self) -> u128 {
+        self.size
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<T> {
+        assert!(rank < self.size, "rank out of range");
+        let base = self.symbols.len() as u128;
+        let mut rank = rank;
+        let mut word = vec![self.symbols[0].clone(); self.length];
+        for c in word.iter_mut().rev() {
+            *c = self.symbols[(rank % base) as usize].clone();
+            rank /= base;
+        }
+        word
+    }
+
+    fn rank(&self, word: &Vec<T>) -> Option<u128> {
+        if word.len() != self.length {
+            return None;
+        }
+        let base = self.symbols.len() as u128;
+        Some(
+            digits(&self.symbols, word)?
+                .into_iter()
+                .fold(0, |rank, digit| rank * base + digit as u128),
+        )
+    }
+
+    fn advance(&self, word: &mut Vec<T>) -> bool {
+        for c in word.iter_mut().rev() {
+            let digit = self.symbols.iter().position(|symbol| symbol == c).unwrap();
+            if digit + 1 < self.symbols.len() {
+                *c = self.symbols[digit + 1].clone();
+                return true;
+            }
+            *c = self.symbols[0].clone();
+        }
+        false
+    }
+}
+
+/// Non-empty words of length at most `max_length` over ordered symbols, in lexicographic order,
+/// where a word comes right before its extensions (A, AA, AB, B, BA, BB)
+#[derive(Debug, Clone)]
+pub struct VariableLengthWords<T> {
+    symbols: Vec<T>,
+    max_length: usize,
+    /// `subtree_sizes[j]`: number of words of length at most j, including the empty one
+    subtree_sizes: Vec<u128>,
+}
+
+impl<T: Clone + PartialEq> VariableLengthWords<T> {
+    pub fn new(symbols: Vec<T>, max_length: usize) -> Self {
+        assert!(!symbols.is_empty(), "no symbols");
+        let base = symbols.len() as u128;
+        let mut subtree_sizes = vec![1u128];
+        for _ in 0..max_length {
+            let size = subtree_sizes.last().unwrap().checked_mul(base);
+            subtree_sizes.push(checked_size(size.and_then(|size| size.checked_add(1))));
+        }
+        VariableLengthWords {
+            symbols,
+            max_length,
+            subtree_sizes,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Enumeration for VariableLengthWords<T> {
+    type Item = Vec<T>;
+
+    fn size(&self) -> u128 {
+        self.subtree_sizes[self.max_length] - 1
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<T> {
+        assert!(rank < self.size(), "rank out of range");
+        let mut word = Vec::new();
+        let mut rank = rank;
+        loop {
+            let subtree_size = self.subtree_sizes[self.max_length - 1 - word.len()];
+            word.push(self.symbols[(rank / subtree_size) as usize].clone());
+            rank %= subtree_size;
+            if rank == 0 {
+                return word;
+            }
+            // skip the word itself, which comes before its extensions
+            rank -= 1;
+        }
+    }
+
+    fn rank(&self, word: &Vec<T>) -> Option<u128> {
+        if word.is_empty() || word.len() > self.max_length {
+            return None;
+        }
+        let digits = digits(&self.symbols, word)?;
+        Some(
+            digits
+                .iter()
+                .enumerate()
+                .map(|(i, digit)| *digit as u128 * self.subtree_sizes[self.max_length - 1 - i])
+                .sum::<u128>()
+                + (word.len() - 1) as u128,
+        )
+    }
+
+    fn advance(&self, word: &mut Vec<T>) -> bool {
+        if word.len() < self.max_length {
+            word.push(self.symbols[0].clone());
+            return true;
+        }
+        while let Some(c) = word.pop() {
+            let digit = self.symbols.iter().position(|symbol| *symbol == c).unwrap();
+            if digit + 1 < self.symbols.len() {
+                word.push(self.symbols[digit + 1].clone());
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Ordered arrangements of `k` distinct numbers from 1..=n, in lexicographic order
+/// (all permutations of 1..=n when k = n)
+#[derive(Debug, Clone)]
+pub struct PartialPermutations {
+    n: usize,
+    k: usize,
+    /// `arrangements[i]`: number of arrangements of i numbers from the n - (k - i) left over
+    arrangements: Vec<u128>,
+}
+
+impl PartialPermutations {
+    pub fn new(n: usize, k: usize) -> Self {
+        assert!(k <= n, "can't arrange more numbers than there are");
+        let mut arrangements = vec![1u128];
+        for i in 1..=k {
+            let size = arrangements[i - 1].checked_mul((n - k + i) as u128);
+            arrangements.push(checked_size(size));
+        }
+        PartialPermutations { n, k, arrangements }
+    }
+}
+
+impl Enumeration for PartialPermutations {
+    type Item = Vec<usize>;
+
+    fn size(&self) -> u128 {
+        self.arrangements[self.k]
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<usize> {
+        assert!(rank < self.size(), "rank out of range");
+        let mut unused: Vec<usize> = (1..=self.n).collect();
+        let mut rank = rank;
+        (0..self.k)
+            .map(|i| {
+                let block = self.arrangements[self.k - 1 - i];
+                let index = (rank / block) as usize;
+                rank %= block;
+                unused.remove(index)
+            })
+            .collect()
+    }
+
+    fn rank(&self, arrangement: &Vec<usize>) -> Option<u128> {
+        if arrangement.len() != self.k {
+            return None;
+        }
+        let mut used = vec![false; self.n + 1];
+        let mut rank = 0;
+        for (i, number) in arrangement.iter().enumerate() {
+            if *number == 0 || *number > self.n || used[*number] {
+                return None;
+            }
+            let smaller_unused = (1..*number).filter(|smaller| !used[*smaller]).count();
+            rank += smaller_unused as u128 * self.arrangements[self.k - 1 - i];
+            used[*number] = true;
+        }
+        Some(rank)
+    }
+
+    fn advance(&self, arrangement: &mut Vec<usize>) -> bool {
+        let mut used = vec![false; self.n + 2];
+        for number in arrangement.iter() {
+            used[*number] = true;
+        }
+        // the rightmost position that can take a larger unused number
+        for i in (0..self.k).rev() {
+            used[arrangement[i]] = false;
+            if let Some(larger) = (arrangement[i] + 1..=self.n).find(|larger| !used[*larger]) {
+                arrangement[i] = larger;
+                used[larger] = true;
+                // followed by the smallest unused numbers, in increasing order
+                let mut smallest = (1..=self.n).filter(|number| !used[*number]);
+                for number in arrangement[i + 1..].iter_mut() {
+                    *number = smallest.next().unwrap();
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Permutations of 1..=n, in lexicographic order
+#[derive(Debug, Clone)]
+pub struct Permutations(PartialPermutations);
+
+impl Permutations {
+    pub fn new(n: usize) -> Self {
+        Permutations(PartialPermutations::new(n, n))
+    }
+}
+
+impl Enumeration for Permutations {
+    type Item = Vec<usize>;
+
+    fn size(&self) -> u128 {
+        self.0.size()
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<usize> {
+        self.0.unrank(rank)
+    }
+
+    fn rank(&self, permutation: &Vec<usize>) -> Option<u128> {
+        self.0.rank(permutation)
+    }
+
+    /// The next lexicographic permutation, in amortized constant time
+    fn advance(&self, permutation: &mut Vec<usize>) -> bool {
+        let pivot = match (1..permutation.len())
+            .rev()
+            .find(|i| permutation[i - 1] < permutation[*i])
+        {
+            Some(i) => i - 1,
+            None => return false,
+        };
+        let successor = (pivot + 1..permutation.len())
+            .rev()
+            .find(|i| permutation[*i] > permutation[pivot])
+            .unwrap();
+        permutation.swap(pivot, successor);
+        permutation[pivot + 1..].reverse();
+        true
+    }
+}
+
+/// Signed permutations of 1..=n: each permutation in lexicographic order of absolute values,
+/// with its 2^n sign patterns in turn (+ before -, the first sign changing slowest)
+#[derive(Debug, Clone)]
+pub struct SignedPermutations {
+    permutations: Permutations,
+    n: usize,
+    size: u128,
+}
+
+impl SignedPermutations {
+    pub fn new(n: usize) -> Self {
+        let permutations = Permutations::new(n);
+        let size = checked_size(
+            1u128
+                .checked_shl(n as u32)
+                .and_then(|signs| signs.checked_mul(permutations.size())),
+        );
+        SignedPermutations {
+            permutations,
+            n,
+            size,
+        }
+    }
+}
+
+impl Enumeration for SignedPermutations {
+    type Item = Vec<i64>;
+
+    fn size(&self) -> u128 {
+        self.size
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<i64> {
+        assert!(rank < self.size, "rank out of range");
+        let signs = rank % (1 << self.n);
+        self.permutations
+            .unrank(rank >> self.n)
+            .into_iter()
+            .enumerate()
+            .map(|(i, number)| {
+                if signs >> (self.n - 1 - i) & 1 == 1 {
+                    -(number as i64)
+                } else {
+                    number as i64
+                }
+            })
+            .collect()
+    }
+
+    fn rank(&self, permutation: &Vec<i64>) -> Option<u128> {
+        let absolute: Vec<_> = permutation
+            .iter()
+            .map(|x| x.unsigned_abs() as usize)
+            .collect();
+        let signs = permutation
+            .iter()
+            .fold(0, |signs, x| signs << 1 | (*x < 0) as u128);
+        Some(self.permutations.rank(&absolute)? << self.n | signs)
+    }
+
+    fn advance(&self, permutation: &mut Vec<i64>) -> bool {
+        // count up in binary with - as 1
+        for x in permutation.iter_mut().rev() {
+            *x = -*x;
+            if *x < 0 {
+                return true;
+            }
+        }
+        let mut absolute: Vec<_> = permutation.iter().map(|x| *x as usize).collect();
+        if !self.permutations.advance(&mut absolute) {
+            return false;
+        }
+        for (x, number) in permutation.iter_mut().zip(absolute) {
+            *x = number as i64;
+        }
+        true
+    }
+}
+
+/// Subsets of 1..=n as increasing lists, ordered by the binary number whose i-th lowest bit says
+/// whether i + 1 is in the subset
+#[derive(Debug, Clone)]
+pub struct Subsets {
+    n: usize,
+}
+
+impl Subsets {
+    pub fn new(n: usize) -> Self {
+        assert!(n < 128, "too many objects to rank in a u128");
+        Subsets { n }
+    }
+}
+
+impl Enumeration for Subsets {
+    type Item = Vec<usize>;
+
+    fn size(&self) -> u128 {
+        1 << self.n
+    }
+
+    fn unrank(&self, rank: u128) -> Vec<usize> {
+        assert!(rank < self.size(), "rank out of range");
+        (1..=self.n).filter(|i| rank >> (i - 1) & 1 == 1).collect()
+    }
+
+    fn rank(&self, subset: &Vec<usize>) -> Option<u128> {
+        let mut rank = 0u128;
+        let mut previous = 0;
+        for number in subset {
+            if *number <= previous || *number > self.n {
+                return None;
+            }
+            rank |= 1 << (number - 1);
+            previous = *number;
+        }
+        Some(rank)
+    }
+
+    fn advance(&self, subset: &mut Vec<usize>) -> bool {
+        // adding one to the binary number: clear the run of lowest numbers 1, 2, .. and add the
+        // first number missing from it
+        let run = subset
+            .iter()
+            .enumerate()
+            .take_while(|(i, number)| **number == i + 1)
+            .count();
+        if run == self.n {
+            return false;
+        }
+        subset.splice(..run, std::iter::once(run + 1));
+        true
+    }
+}
+
+/// Heap's algorithm: every permutation of the items, each one swap away from the previous one.
+/// Faster than `Permutations` when the order doesn't matter, but not rankable.
+pub struct HeapPermutations<T> {
+    items: Vec<T>,
+    /// stack state of the iterative algorithm
+    counters: Vec<usize>,
+    index: usize,
+    started: bool,
+}
+
+impl<T: Clone> HeapPermutations<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        HeapPermutations {
+            counters: vec![0; items.len()],
+            items,
+            index: 0,
+            started: false,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for HeapPermutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(self.items.clone());
+        }
+        while self.index < self.items.len() {
+            let i = self.index;
+            if self.counters[i] < i {
+                if i.is_multiple_of(2) {
+                    self.items.swap(0, i);
+                } else {
+                    self.items.swap(self.counters[i], i);
+                }
+                self.counters[i] += 1;
+                self.index = 0;
+                return Some(self.items.clone());
+            }
+            self.counters[i] = 0;
+            self.index += 1;
+        }
+        None
+    }
+}
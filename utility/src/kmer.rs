@@ -0,0 +1,498 @@
+//! k-mers packed into an integer, `bits_per_symbol` bits per symbol with the first symbol in the
+//! highest bits, so that k-mers of the same length compare in lexicographic order.
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+/// Unsigned integer a k-mer can be packed into
+pub trait KmerWord:
+    Copy
+    + Eq
+    + Ord
+    + Hash
+    + Debug
+    + Default
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+{
+    const BITS: u32;
+
+    /// Truncating conversion
+    fn from_u128(value: u128) -> Self;
+    fn to_u128(self) -> u128;
+}
+
+macro_rules! impl_kmer_word {
+    ($($word:ty),*) => {
+        $(
+            impl KmerWord for $word {
+                const BITS: u32 = <$word>::BITS;
+
+                fn from_u128(value: u128) -> Self {
+                    value as $word
+                }
+
+                fn to_u128(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_kmer_word!(u32, u64, u128);
+
+const INVALID: u8 = u8::MAX;
+
+/// Ordered symbols of an alphabet and their codes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    symbols: Vec<u8>,
+    /// code of each byte, INVALID if it isn't in the alphabet
+    codes: Vec<u8>,
+    bits_per_symbol: u32,
+    /// code of the complement of each symbol, if the alphabet has complements
+    complements: Option<Vec<u8>>,
+}
+
+impl Alphabet {
+    /// Alphabet of `symbols` in lexicographic order. Lowercase letters are read as uppercase ones.
+    pub fn new(symbols: &[u8]) -> Self {
+        assert!(
+            !symbols.is_empty() && symbols.len() < INVALID as usize,
+            "alphabet size must be between 1 and 254"
+        );
+        let mut codes = vec![INVALID; 256];
+        for (code, symbol) in symbols.iter().enumerate() {
+            assert_eq!(codes[*symbol as usize], INVALID, "repeated symbol");
+            codes[*symbol as usize] = code as u8;
+            if symbol.is_ascii_uppercase() {
+                codes[symbol.to_ascii_lowercase() as usize] = code as u8;
+            }
+        }
+        Alphabet {
+            symbols: symbols.to_vec(),
+            codes,
+            bits_per_symbol: (usize::BITS - (symbols.len() - 1).leading_zeros()).max(1),
+            complements: None,
+        }
+    }
+
+    /// Alphabet where `complements[i]` is the complement of `symbols[i]`
+    pub fn with_complements(symbols: &[u8], complements: &[u8]) -> Self {
+        let mut alphabet = Alphabet::new(symbols);
+        let complements = complements
+            .iter()
+            .map(|c| {
+                alphabet
+                    .encode(*c)
+                    .expect("complement outside the alphabet") as u8
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            complements.len(),
+            symbols.len(),
+            "one complement per symbol"
+        );
+        alphabet.complements = Some(complements);
+        alphabet
+    }
+
+    /// A, C, G, T with Watson-Crick complements
+    pub fn dna() -> Self {
+        Alphabet::with_complements(b"ACGT", b"TGCA")
+    }
+
+    pub fn size(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn symbols(&self) -> &[u8] {
+        &self.symbols
+    }
+
+    pub fn bits_per_symbol(&self) -> u32 {
+        self.bits_per_symbol
+    }
+
+    /// Longest k-mer that fits in `W`
+    pub fn max_k<W: KmerWord>(&self) -> usize {
+        (W::BITS / self.bits_per_symbol) as usize
+    }
+
+    pub fn encode(&self, symbol: u8) -> Option<u32> {
+        match self.codes[symbol as usize] {
+            INVALID => None,
+            code => Some(code as u32),
+        }
+    }
+
+    pub fn decode(&self, code: u32) -> u8 {
+        self.symbols[code as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Kmer<W: KmerWord = u64> {
+    value: W,
+    k: usize,
+}
+
+impl<W: KmerWord> Kmer<W> {
+    pub fn new(value: W, k: usize) -> Self {
+        Kmer { value, k }
+    }
+
+    /// The packed symbols
+    pub fn value(self) -> W {
+        self.value
+    }
+
+    pub fn k(self) -> usize {
+        self.k
+    }
+
+    /// Mask of the lowest `length` symbols
+    fn mask(length: usize, alphabet: &Alphabet) -> W {
+        let bits = length as u32 * alphabet.bits_per_symbol;
+        if bits == W::BITS {
+            !W::default()
+        } else {
+            !(!W::default() << bits)
+        }
+    }
+
+    fn shift(&self, index: usize, alphabet: &Alphabet) -> u32 {
+        (self.k - 1 - index) as u32 * alphabet.bits_per_symbol
+    }
+
+    /// Pack a sequence, or None if it is too long for `W` or has a symbol outside the alphabet
+    pub fn encode(sequence: &[u8], alphabet: &Alphabet) -> Option<Self> {
+        if sequence.len() > alphabet.max_k::<W>() {
+            return None;
+        }
+        let mut value = W::default();
+        for symbol in sequence {
+            value =
+                value << alphabet.bits_per_symbol | W::from_u128(alphabet.encode(*symbol)? as u128);
+        }
+        Some(Kmer {
+            value,
+            k: sequence.len(),
+        })
+    }
+
+    pub fn decode(self, alphabet: &Alphabet) -> String {
+        (0..self.k)
+            .map(|index| alphabet.decode(self.code_at(index, alphabet)) as char)
+            .collect()
+    }
+
+    /// Code of the symbol at `index`
+    pub fn code_at(self, index: usize, alphabet: &Alphabet) -> u32 {
+        let code = self.value >> self.shift(index, alphabet) & Self::mask(1, alphabet);
+        code.to_u128() as u32
+    }
+
+    /// The k-mer with the symbol at `index` replaced by `code`
+    pub fn with_code_at(self, index: usize, code: u32, alphabet: &Alphabet) -> Self {
+        let shift = self.shift(index, alphabet);
+        let cleared = self.value & !(Self::mask(1, alphabet) << shift);
+        Kmer {
+            value: cleared | W::from_u128(code as u128) << shift,
+            k: self.k,
+        }
+    }
+
+    /// Roll the k-mer one symbol along the sequence: drop the first symbol and append `code`
+    pub fn push(self, code: u32, alphabet: &Alphabet) -> Self {
+        Kmer {
+            value: (self.value << alphabet.bits_per_symbol | W::from_u128(code as u128))
+                & Self::mask(self.k, alphabet),
+            k: self.k,
+        }
+    }
+
+    /// Panics if the alphabet has no complements
+    pub fn reverse_complement(self, alphabet: &Alphabet) -> Self {
+        let complements = alphabet
+            .complements
+            .as_ref()
+            .expect("alphabet has no complements");
+        let mut value = W::default();
+        for index in (0..self.k).rev() {
+            let complement = complements[self.code_at(index, alphabet) as usize];
+            value = value << alphabet.bits_per_symbol | W::from_u128(complement as u128);
+        }
+        Kmer { value, k: self.k }
+    }
+
+    /// The lexicographically smaller of the k-mer and its reverse complement
+    pub fn canonical(self, alphabet: &Alphabet) -> Self {
+        self.min(self.reverse_complement(alphabet))
+    }
+
+    pub fn hamming_distance(self, other: Self, alphabet: &Alphabet) -> usize {
+        let difference = self.value ^ other.value;
+        let symbol_mask = Self::mask(1, alphabet);
+        (0..self.k)
+            .filter(|index| {
+                difference >> (*index as u32 * alphabet.bits_per_symbol) & symbol_mask
+                    != W::default()
+            })
+            .count()
+    }
+
+    /// All k-mers within Hamming distance `distance` (including this one), sorted
+    pub fn hamming_neighbours(self, distance: usize, alphabet: &Alphabet) -> Vec<Self> {
+        let mut neighbours: Vec<_> = self.neighbours(distance, alphabet).collect();
+        neighbours.sort_unstable();
+        neighbours
+    }
+
+    /// Lazy iterator over the k-mers within Hamming distance `distance` (including this one),
+    /// in no particular order
+    pub fn neighbours(self, distance: usize, alphabet: &Alphabet) -> HammingNeighbours<'_, W> {
+        HammingNeighbours::new(self, distance, alphabet)
+    }
+
+    /// Index of the k-mer among all k-mers in lexicographic order,
+    /// i.e. its value as a base-|alphabet| number
+    pub fn rank(self, alphabet: &Alphabet) -> u128 {
+        (0..self.k).fold(0, |rank, index| {
+            rank * alphabet.size() as u128 + self.code_at(index, alphabet) as u128
+        })
+    }
+
+    pub fn from_rank(rank: u128, k: usize, alphabet: &Alphabet) -> Self {
+        let size = alphabet.size() as u128;
+        let mut rank = rank;
+        let mut kmer = Kmer {
+            value: W::default(),
+            k,
+        };
+        for index in (0..k).rev() {
+            kmer = kmer.with_code_at(index, (rank % size) as u32, alphabet);
+            rank /= size;
+        }
+        kmer
+    }
+}
+
+/// Number of k-mers within Hamming distance `distance` of a k-mer
+pub fn neighbourhood_size(k: usize, distance: usize, alphabet_size: usize) -> u128 {
+    let mut size = 0;
+    // C(k, j) * (alphabet_size - 1)^j
+    let mut term: u128 = 1;
+    for j in 0..=distance.min(k) {
+        size += term;
+        term = term * (k - j) as u128 / (j + 1) as u128 * (alphabet_size as u128 - 1);
+    }
+    size
+}
+
+/// Longest k-mer (with one bit per symbol) a `HammingNeighbours` can enumerate
+const MAX_NEIGHBOURHOOD_K: usize = 128;
+
+/// Iterator over a Hamming neighbourhood that doesn't allocate: for each number of substitutions
+/// up to the distance, it steps through the combinations of substituted positions and, for each,
+/// through the replacement symbols like an odometer.
+pub struct HammingNeighbours<'a, W: KmerWord = u64> {
+    kmer: Kmer<W>,
+    alphabet: &'a Alphabet,
+    distance: usize,
+    /// number of substituted positions
+    substitutions: usize,
+    /// substituted positions, increasing
+    positions: [u8; MAX_NEIGHBOURHOOD_K],
+    /// amount (between 1 and |alphabet| - 1) added to the code at each substituted position
+    offsets: [u8; MAX_NEIGHBOURHOOD_K],
+    done: bool,
+}
+
+impl<'a, W: KmerWord> HammingNeighbours<'a, W> {
+    fn new(kmer: Kmer<W>, distance: usize, alphabet: &'a Alphabet) -> Self {
+        let distance = if alphabet.size() > 1 {
+            distance.min(kmer.k)
+        } else {
+            0
+        };
+        HammingNeighbours {
+            kmer,
+            alphabet,
+            distance,
+            substitutions: 0,
+            positions: [0; MAX_NEIGHBOURHOOD_K],
+            offsets: [1; MAX_NEIGHBOURHOOD_K],
+            done: false,
+        }
+    }
+
+    fn current(&self) -> Kmer<W> {
+        let size = self.alphabet.size() as u32;
+        (0..self.substitutions).fold(self.kmer, |kmer, i| {
+            let position = self.positions[i] as usize;
+            let code = (self.kmer.code_at(position, self.alphabet) + self.offsets[i] as u32) % size;
+            kmer.with_code_at(position, code, self.alphabet)
+        })
+    }
+
+    /// Move to the next neighbour, returning false when there are none left
+    fn advance(&mut self) -> bool {
+        let (j, k) = (self.substitutions, self.kmer.k);
+        let largest_offset = (self.alphabet.size() - 1) as u8;
+        // next replacement symbols for the same positions
+        for i in (0..j).rev() {
+            if self.offsets[i] < largest_offset {
+                self.offsets[i] += 1;
+                return true;
+            }
+            self.offsets[i] = 1;
+        }
+        // next combination of positions
+        for i in (0..j).rev() {
+            if (self.positions[i] as usize) < k - j + i {
+                self.positions[i] += 1;
+                for next in i + 1..j {
+                    self.positions[next] = self.positions[next - 1] + 1;
+                }
+                return true;
+            }
+        }
+        // one more substitution
+        if j == self.distance {
+            return false;
+        }
+        self.substitutions += 1;
+        for i in 0..=j {
+            self.positions[i] = i as u8;
+        }
+        true
+    }
+}
+
+impl<'a, W: KmerWord> Iterator for HammingNeighbours<'a, W> {
+    type Item = Kmer<W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let kmer = self.current();
+        self.done = !self.advance();
+        Some(kmer)
+    }
+}
+
+/// Rolling iterator over the (position, k-mer) pairs of a sequence,
+/// skipping windows with symbols outside the alphabet
+pub struct Kmers<'a, W: KmerWord = u64> {
+    sequence: &'a [u8],
+    alphabet: &'a Alphabet,
+    k: usize,
+    /// next position to read
+    position: usize,
+    current: Kmer<W>,
+    /// number of valid symbols read since the last invalid one
+    valid_length: usize,
+}
+
+impl<'a, W: KmerWord> Kmers<'a, W> {
+    pub fn new(sequence: &'a [u8], k: usize, alphabet: &'a Alphabet) -> Self {
+        assert!(
+            k > 0 && k <= alphabet.max_k::<W>(),
+            "k-mer too long for its word"
+        );
+        Kmers {
+            sequence,
+            alphabet,
+            k,
+            position: 0,
+            current: Kmer::new(W::default(), k),
+            valid_length: 0,
+        }
+    }
+}
+
+impl<'a, W: KmerWord> Iterator for Kmers<'a, W> {
+    type Item = (usize, Kmer<W>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.sequence.len() {
+            let symbol = self.sequence[self.position];
+            self.position += 1;
+            match self.alphabet.encode(symbol) {
+                Some(code) => {
+                    self.current = self.current.push(code, self.alphabet);
+                    self.valid_length += 1;
+                    if self.valid_length >= self.k {
+                        return Some((self.position - self.k, self.current));
+                    }
+                }
+                None => self.valid_length = 0,
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kmer_codec() {
+        let dna = Alphabet::dna();
+        let kmer: Kmer = Kmer::encode(b"GATTACA", &dna).unwrap();
+        assert_eq!(kmer.decode(&dna), "GATTACA");
+        assert_eq!(
+            Kmer::<u128>::encode(b"gattaca", &dna).unwrap().decode(&dna),
+            "GATTACA"
+        );
+        assert_eq!(kmer.rank(&dna), 0b10_00_11_11_00_01_00);
+        assert_eq!(Kmer::from_rank(kmer.rank(&dna), 7, &dna), kmer);
+        assert_eq!(kmer.reverse_complement(&dna).decode(&dna), "TGTAATC");
+        assert_eq!(kmer.canonical(&dna).decode(&dna), "GATTACA");
+        assert_eq!(kmer.push(2, &dna).decode(&dna), "ATTACAG");
+        let other: Kmer = Kmer::encode(b"GACTAGA", &dna).unwrap();
+        assert_eq!(kmer.hamming_distance(other, &dna), 2);
+        let neighbours = kmer.hamming_neighbours(2, &dna);
+        assert_eq!(neighbours.len(), 1 + 7 * 3 + 21 * 9);
+        assert!(neighbours.windows(2).all(|w| w[0] < w[1]));
+        assert!(neighbours.contains(&other));
+        let mut lazy: Vec<_> = kmer.neighbours(2, &dna).collect();
+        lazy.sort_unstable();
+        assert_eq!(lazy, neighbours);
+        assert_eq!(neighbourhood_size(7, 2, 4), neighbours.len() as u128);
+        assert_eq!(kmer.neighbours(7, &dna).count(), 1 << 14);
+        let binary = Alphabet::new(b"01");
+        let long = Kmer::<u128>::encode(&[b'1'; 128], &binary).unwrap();
+        assert_eq!(long.neighbours(1, &binary).count(), 129);
+        assert!(Kmer::<u128>::encode(&[b'1'; 129], &binary).is_none());
+
+        let kmers: Vec<_> = Kmers::<u64>::new(b"ACGNACGTA", 3, &dna)
+            .map(|(position, kmer)| (position, kmer.decode(&dna)))
+            .collect();
+        assert_eq!(
+            kmers,
+            vec![
+                (0, String::from("ACG")),
+                (4, String::from("ACG")),
+                (5, String::from("CGT")),
+                (6, String::from("GTA")),
+            ]
+        );
+
+        // ranks over a non-power-of-two alphabet differ from the packed values
+        let rna_and_gap = Alphabet::new(b"-ACGU");
+        let kmer: Kmer<u32> = Kmer::encode(b"U-A", &rna_and_gap).unwrap();
+        assert_eq!(rna_and_gap.bits_per_symbol(), 3);
+        assert_eq!(kmer.rank(&rna_and_gap), 4 * 25 + 1);
+        assert_eq!(Kmer::from_rank(101, 3, &rna_and_gap), kmer);
+        assert_eq!(Alphabet::dna().max_k::<u128>(), 64);
+    }
+}
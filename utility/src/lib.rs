@@ -1,6 +1,8 @@
+pub mod enumeration;
 pub mod errors;
 pub mod graph;
 pub mod io;
+pub mod kmer;
 pub mod math;
 pub mod string;
 pub mod testing;
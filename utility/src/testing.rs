@@ -6,6 +6,8 @@ use std::time::Duration;
 use crate::errors::RosalindParseError;
 use anyhow::Error;
 use glob::glob;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use reqwest;
 use scraper;
 
@@ -26,6 +28,15 @@ pub fn get_input_file(question_name: &str) -> Result<PathBuf, Error> {
     Ok(input_file)
 }
 
+/// Reproducible random string over an ASCII `alphabet`, for tests checking against a naive
+/// implementation
+pub fn random_sequence(length: usize, alphabet: &[u8], seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..length)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
 pub async fn get_sample_data_for_question(question_name: &str, folder: &Path) -> Result<(), Error> {
     let body = reqwest::get(&format!("{}/{}", ROSALIND_URL, question_name))
         .await?